@@ -0,0 +1,396 @@
+//! Derive macros for `commandrs`, which are usually used through its `derive` feature rather than
+//! depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Expr, Field, Fields, LitStr, Variant};
+
+/// Derive `commandrs::FromProgram` and `commandrs::FromParsedProgram` for a struct with named
+/// fields, where every field is a flag.
+///
+/// Each field can be configured with a `#[flag(...)]` attribute:
+///
+/// - `required` makes the flag required.
+/// - `default = <expr>` gives an optional flag its default value. Optional flags without a
+///   `default` use `Default::default()`.
+/// - `desc = "..."` describes the flag in the help text.
+/// - `name = "..."` overrides the flag name, which is otherwise the field name in kebab-case.
+/// - `raw` makes the flag a raw flag, which must be a `Vec<String>`.
+/// - `value_enum` makes the flag accept the values of its type, which must implement
+///   `commandrs::value_enum::ValueEnum`, showing them in the help text.
+#[proc_macro_derive(FromProgram, attributes(flag))]
+pub fn derive_from_program(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_program(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct FlagAttrs {
+    name: String,
+    desc: String,
+    is_required: bool,
+    is_raw: bool,
+    is_value_enum: bool,
+    default: Option<Expr>,
+}
+
+impl FlagAttrs {
+    fn from_field(field: &Field) -> syn::Result<FlagAttrs> {
+        let ident = field
+            .ident
+            .as_ref()
+            .expect("named fields always have an ident");
+        let mut attrs = FlagAttrs {
+            name: ident.to_string().trim_start_matches("r#").replace('_', "-"),
+            desc: String::new(),
+            is_required: false,
+            is_raw: false,
+            is_value_enum: false,
+            default: None,
+        };
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("flag")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("required") {
+                    attrs.is_required = true;
+                } else if meta.path.is_ident("raw") {
+                    attrs.is_raw = true;
+                } else if meta.path.is_ident("value_enum") {
+                    attrs.is_value_enum = true;
+                } else if meta.path.is_ident("desc") {
+                    attrs.desc = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("name") {
+                    attrs.name = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("default") {
+                    attrs.default = Some(meta.value()?.parse::<Expr>()?);
+                } else {
+                    return Err(meta.error("unsupported flag attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        if attrs.is_required && attrs.default.is_some() {
+            return Err(Error::new_spanned(
+                ident,
+                "a required flag cannot have a default",
+            ));
+        }
+
+        Ok(attrs)
+    }
+}
+
+fn expand_from_program(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "FromProgram can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "FromProgram can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut registrations = vec![];
+    let mut extractions = vec![];
+    for field in fields {
+        let attrs = FlagAttrs::from_field(field)?;
+        let ident = &field.ident;
+        let ty = &field.ty;
+        let FlagAttrs { name, desc, .. } = &attrs;
+
+        let (with_required_flag, with_optional_flag) = match attrs.is_value_enum {
+            true => (
+                quote! { with_required_choice_flag },
+                quote! { with_optional_choice_flag },
+            ),
+            false => (quote! { with_required_flag }, quote! { with_optional_flag }),
+        };
+        registrations.push(if attrs.is_raw {
+            quote! { let program = program.with_raw_flag(#name, #desc)?; }
+        } else if attrs.is_required {
+            quote! { let program = program.#with_required_flag::<#ty>(#name, #desc)?; }
+        } else {
+            let default = match &attrs.default {
+                Some(default) => quote! { #default },
+                None => quote! { <#ty as ::std::default::Default>::default() },
+            };
+            quote! {
+                let program = program.#with_optional_flag::<#ty>(
+                    #name,
+                    ::commandrs::__private::IntoDefault::into_default(#default),
+                    #desc,
+                )?;
+            }
+        });
+
+        extractions.push(if attrs.is_raw {
+            quote! { #ident: program.get_raw(#name)? }
+        } else {
+            quote! { #ident: program.get::<#ty>(#name)? }
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::commandrs::FromProgram for #ident #ty_generics #where_clause {
+            fn register<'a>(
+                program: ::commandrs::Program<'a>,
+            ) -> ::std::result::Result<::commandrs::Program<'a>, ::commandrs::error::ProgramError> {
+                #(#registrations)*
+                ::std::result::Result::Ok(program)
+            }
+        }
+
+        impl #impl_generics ::commandrs::FromParsedProgram for #ident #ty_generics #where_clause {
+            fn from_parsed_program(
+                program: &::commandrs::Program,
+            ) -> ::std::result::Result<Self, ::commandrs::error::ProgramError> {
+                ::std::result::Result::Ok(#ident {
+                    #(#extractions),*
+                })
+            }
+        }
+    })
+}
+
+/// Derive `commandrs::Subcommands` for an enum, where every variant is a subcommand.
+///
+/// Variants either have no fields, for subcommands without flags, or a single unnamed field
+/// whose type implements `commandrs::FromProgram`, holding the config of the subcommand.
+///
+/// Each variant can be configured with a `#[command(...)]` attribute:
+///
+/// - `desc = "..."` describes the subcommand in the help text.
+/// - `name = "..."` overrides the subcommand name, which is otherwise the variant name in
+///   kebab-case.
+#[proc_macro_derive(Subcommands, attributes(command))]
+pub fn derive_subcommands(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_subcommands(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct CommandAttrs {
+    name: String,
+    desc: String,
+}
+
+impl CommandAttrs {
+    fn from_variant(variant: &Variant) -> syn::Result<CommandAttrs> {
+        let mut attrs = CommandAttrs {
+            name: kebab_case(&variant.ident.to_string()),
+            desc: String::new(),
+        };
+
+        for attr in variant
+            .attrs
+            .iter()
+            .filter(|a| a.path().is_ident("command"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("desc") {
+                    attrs.desc = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("name") {
+                    attrs.name = meta.value()?.parse::<LitStr>()?.value();
+                } else {
+                    return Err(meta.error("unsupported command attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(attrs)
+    }
+}
+
+fn expand_subcommands(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Subcommands can only be derived for enums",
+            ))
+        }
+    };
+
+    let ident = &input.ident;
+    let mut registrations = vec![];
+    let mut extractions = vec![];
+    for variant in variants {
+        let CommandAttrs { name, desc } = CommandAttrs::from_variant(variant)?;
+        let variant_ident = &variant.ident;
+
+        match &variant.fields {
+            Fields::Unit => {
+                registrations.push(quote! {
+                    let program = program.with_subcommand(
+                        #name,
+                        ::commandrs::Program::new().with_description(#desc),
+                    )?;
+                });
+                extractions.push(quote! {
+                    #name => ::std::option::Option::Some(::std::result::Result::Ok(#ident::#variant_ident))
+                });
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+                registrations.push(quote! {
+                    let program = program.with_subcommand(
+                        #name,
+                        ::commandrs::Program::new()
+                            .with_description(#desc)
+                            .with_flags_from::<#ty>()?,
+                    )?;
+                });
+                extractions.push(quote! {
+                    #name => ::std::option::Option::Some(
+                        subcommand.into_struct::<#ty>().map(#ident::#variant_ident)
+                    )
+                });
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    variant_ident,
+                    "subcommand variants must have no fields or a single unnamed field",
+                ))
+            }
+        }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::commandrs::Subcommands for #ident #ty_generics #where_clause {
+            fn register<'a>(
+                program: ::commandrs::Program<'a>,
+            ) -> ::std::result::Result<::commandrs::Program<'a>, ::commandrs::error::ProgramError> {
+                #(#registrations)*
+                ::std::result::Result::Ok(program)
+            }
+
+            fn from_subcommand(
+                name: &str,
+                subcommand: &::commandrs::Program,
+            ) -> ::std::option::Option<
+                ::std::result::Result<Self, ::commandrs::error::ProgramError>,
+            > {
+                match name {
+                    #(#extractions,)*
+                    _ => ::std::option::Option::None,
+                }
+            }
+        }
+    })
+}
+
+/// Derive `commandrs::value_enum::ValueEnum`, along with `FromStr` and `Display`, for an enum
+/// whose variants have no fields, where every variant is a value a flag accepts.
+///
+/// Each value is the variant name in kebab-case, such as `dry-run` for `DryRun`, unless it is
+/// given with a `#[value(name = "...")]` attribute.
+#[proc_macro_derive(ValueEnum, attributes(value))]
+pub fn derive_value_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_value_enum(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_value_enum(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "ValueEnum can only be derived for enums",
+            ))
+        }
+    };
+
+    let mut idents = vec![];
+    let mut values = vec![];
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                &variant.ident,
+                "value variants cannot have fields",
+            ));
+        }
+
+        let mut value = kebab_case(&variant.ident.to_string());
+        for attr in variant.attrs.iter().filter(|a| a.path().is_ident("value")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    value = meta.value()?.parse::<LitStr>()?.value();
+                } else {
+                    return Err(meta.error("unsupported value attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        idents.push(&variant.ident);
+        values.push(value);
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::commandrs::value_enum::ValueEnum for #ident #ty_generics #where_clause {
+            fn values() -> &'static [&'static str] {
+                &[#(#values),*]
+            }
+        }
+
+        impl #impl_generics ::std::str::FromStr for #ident #ty_generics #where_clause {
+            type Err = ::commandrs::value_enum::ParseValueEnumError;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                match s {
+                    #(#values => ::std::result::Result::Ok(#ident::#idents),)*
+                    _ => ::std::result::Result::Err(
+                        ::commandrs::value_enum::ParseValueEnumError::new(
+                            <Self as ::commandrs::value_enum::ValueEnum>::values(),
+                        ),
+                    ),
+                }
+            }
+        }
+
+        impl #impl_generics ::std::fmt::Display for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    #(#ident::#idents => f.write_str(#values),)*
+                }
+            }
+        }
+    })
+}
+
+/// Such as `run-tests` for `RunTests`.
+fn kebab_case(ident: &str) -> String {
+    let mut kebab = String::new();
+    for (i, c) in ident.trim_start_matches("r#").chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            kebab.push('-');
+        }
+        kebab.extend(c.to_lowercase());
+    }
+    kebab
+}
@@ -0,0 +1,177 @@
+use commandrs::error::ProgramError;
+use commandrs::{FromProgram, Program, Subcommands, ValueEnum};
+
+#[derive(FromProgram, Debug, PartialEq)]
+struct Config {
+    #[flag(required, desc = "Port number")]
+    port: u16,
+    #[flag(default = false, desc = "TLS PLS?")]
+    use_tls: bool,
+    #[flag(name = "bind", default = "localhost", desc = "Host to bind")]
+    host: String,
+    #[flag(desc = "Number of workers")]
+    workers: usize,
+    #[flag(default = 30, desc = "Timeout in seconds")]
+    timeout: u64,
+    #[flag(raw, desc = "Command to run on startup")]
+    cmd: Vec<String>,
+}
+
+#[test]
+fn should_register_flags_and_extract_them_when_derived() {
+    let program = Program::new()
+        .with_flags_from::<Config>()
+        .unwrap()
+        .parse_from_str_arr(&["--port", "8080", "--bind", "0.0.0.0", "--cmd", "echo", "hi"])
+        .unwrap();
+
+    assert_eq!(
+        Config {
+            port: 8080,
+            use_tls: false,
+            host: "0.0.0.0".to_string(),
+            workers: 0,
+            timeout: 30,
+            cmd: vec!["echo".to_string(), "hi".to_string()],
+        },
+        program.into_struct::<Config>().unwrap()
+    );
+}
+
+#[test]
+fn should_use_kebab_case_field_names_when_derived() {
+    let program = Program::new()
+        .with_flags_from::<Config>()
+        .unwrap()
+        .parse_from_str_arr(&["--port", "443", "--use-tls"])
+        .unwrap();
+
+    assert!(program.into_struct::<Config>().unwrap().use_tls);
+}
+
+#[test]
+fn should_result_in_an_error_when_required_flag_is_missing_when_derived() {
+    let err = Program::new()
+        .with_flags_from::<Config>()
+        .unwrap()
+        .parse_from_str_arr(&[])
+        .unwrap_err();
+
+    assert_eq!(
+        ProgramError::RequiredArgWasNotGiven {
+            name: "port".to_string(),
+            prefix: "--".to_string(),
+            index: None,
+        },
+        err
+    );
+}
+
+#[derive(FromProgram, Debug, PartialEq)]
+struct BuildConfig {
+    #[flag(default = false, desc = "Build with optimisations")]
+    release: bool,
+}
+
+#[derive(Subcommands, Debug, PartialEq)]
+enum Command {
+    #[command(desc = "Build the project")]
+    Build(BuildConfig),
+    #[command(desc = "Run the tests")]
+    RunTests,
+    #[command(name = "fmt")]
+    Format,
+}
+
+#[test]
+fn should_register_subcommands_and_extract_the_given_one_when_derived() {
+    let program = Program::new().with_subcommands_from::<Command>().unwrap();
+
+    assert_eq!(
+        Command::Build(BuildConfig { release: true }),
+        program
+            .parse_from(["build", "--release"])
+            .unwrap()
+            .into_program()
+            .subcommand_as::<Command>()
+            .unwrap()
+    );
+    assert_eq!(
+        Command::RunTests,
+        program
+            .parse_from(["run-tests"])
+            .unwrap()
+            .into_program()
+            .subcommand_as::<Command>()
+            .unwrap()
+    );
+    assert_eq!(
+        Command::Format,
+        program
+            .parse_from(["fmt"])
+            .unwrap()
+            .into_program()
+            .subcommand_as::<Command>()
+            .unwrap()
+    );
+}
+
+#[test]
+fn should_result_in_an_error_when_no_subcommand_was_given_when_derived() {
+    let err = Program::new()
+        .with_subcommands_from::<Command>()
+        .unwrap()
+        .parse_from_str_arr(&[])
+        .unwrap()
+        .subcommand_as::<Command>()
+        .unwrap_err();
+
+    assert_eq!(ProgramError::SubcommandNotGiven, err);
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Default)]
+enum Speed {
+    #[default]
+    Fast,
+    DryRun,
+    #[value(name = "auto")]
+    Automatic,
+}
+
+#[derive(FromProgram, Debug, PartialEq)]
+struct RunConfig {
+    #[flag(value_enum, desc = "How fast to go")]
+    speed: Speed,
+}
+
+#[test]
+fn should_accept_kebab_case_variant_names_of_value_enums_when_derived() {
+    let program = Program::new().with_flags_from::<RunConfig>().unwrap();
+
+    assert_eq!(
+        RunConfig {
+            speed: Speed::DryRun
+        },
+        program
+            .parse_from(["--speed", "dry-run"])
+            .unwrap()
+            .into_program()
+            .into_struct::<RunConfig>()
+            .unwrap()
+    );
+    assert_eq!(
+        Speed::Automatic,
+        program
+            .parse_from(["--speed", "auto"])
+            .unwrap()
+            .get::<Speed>("speed")
+            .unwrap()
+    );
+    assert_eq!(
+        "Could not parse speed as type of derive::Speed, expected one of fast, dry-run or auto",
+        program
+            .parse_from(["--speed", "slow"])
+            .unwrap_err()
+            .to_string()
+    );
+}
@@ -0,0 +1,176 @@
+//! Sizes given in bytes or with a unit, such as `64KiB` or `2GB`, for cache size and limit flags.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The units a size can be given in, largest first within each convention. `KB` and friends are
+/// powers of 1000, while `KiB` and friends are powers of 1024.
+const UNITS: [(&str, u64); 10] = [
+    ("PiB", 1 << 50),
+    ("TiB", 1 << 40),
+    ("GiB", 1 << 30),
+    ("MiB", 1 << 20),
+    ("KiB", 1 << 10),
+    ("PB", 1_000_000_000_000_000),
+    ("TB", 1_000_000_000_000),
+    ("GB", 1_000_000_000),
+    ("MB", 1_000_000),
+    ("KB", 1_000),
+];
+
+/// A number of bytes, which can be given as a plain number such as `512`, or with a unit such as
+/// `64KiB` or `2GB`. Units are not case sensitive, and the number can have a fraction, such as
+/// `1.5GiB`, as long as it comes out as a whole number of bytes.
+///
+/// `KB`, `MB`, `GB`, `TB` and `PB` are powers of 1000, and `KiB`, `MiB`, `GiB`, `TiB` and `PiB`
+/// are powers of 1024. This is also explained in the help text of flags of this type.
+///
+/// ```
+/// use commandrs::byte_size::ByteSize;
+/// use commandrs::Program;
+///
+/// let program = Program::new()
+///     .with_optional_flag("cache-size", ByteSize(64 << 20), "Size of the cache")
+///     .unwrap()
+///     .parse_from_str_arr(&["--cache-size", "2GB"])
+///     .unwrap();
+///
+/// assert_eq!(2_000_000_000, program.get::<ByteSize>("cache-size").unwrap().0);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct ByteSize(pub u64);
+
+impl From<ByteSize> for u64 {
+    fn from(size: ByteSize) -> u64 {
+        size.0
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> ByteSize {
+        ByteSize(bytes)
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = ParseByteSizeError;
+
+    fn from_str(s: &str) -> Result<ByteSize, ParseByteSizeError> {
+        let error = |reason: &str| ParseByteSizeError {
+            reason: reason.to_string(),
+        };
+
+        let s = s.trim();
+        let number_len = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(number_len);
+        if number.is_empty() {
+            return Err(error("expected a number"));
+        }
+
+        let unit = unit.trim();
+        let multiplier = match unit {
+            "" => 1,
+            _ if unit.eq_ignore_ascii_case("B") => 1,
+            _ => UNITS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(unit))
+                .map(|(_, multiplier)| *multiplier)
+                .ok_or_else(|| error(&format!("unknown unit {}", unit)))?,
+        };
+
+        let bytes = match number.split_once('.') {
+            None => number
+                .parse::<u64>()
+                .ok()
+                .and_then(|n| n.checked_mul(multiplier))
+                .ok_or_else(|| error("the size is too large"))?,
+            Some(_) => {
+                let bytes = number
+                    .parse::<f64>()
+                    .map_err(|_| error("expected a number"))?
+                    * multiplier as f64;
+                if bytes.fract() != 0.0 {
+                    return Err(error("the size is not a whole number of bytes"));
+                }
+                if bytes >= u64::MAX as f64 {
+                    return Err(error("the size is too large"));
+                }
+                bytes as u64
+            }
+        };
+
+        Ok(ByteSize(bytes))
+    }
+}
+
+impl Display for ByteSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let unit = UNITS
+            .iter()
+            .filter(|(_, multiplier)| self.0 >= *multiplier && self.0.is_multiple_of(*multiplier))
+            .max_by_key(|(_, multiplier)| *multiplier);
+
+        match unit {
+            Some((name, multiplier)) => write!(f, "{}{}", self.0 / multiplier, name),
+            None => write!(f, "{}", self.0),
+        }
+    }
+}
+
+/// Why a `ByteSize` could not be parsed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseByteSizeError {
+    reason: String,
+}
+
+impl Display for ParseByteSizeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl Error for ParseByteSizeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<u64, String> {
+        s.parse::<ByteSize>()
+            .map(u64::from)
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn should_parse_sizes_with_and_without_units() {
+        assert_eq!(Ok(512), parse("512"));
+        assert_eq!(Ok(512), parse("512B"));
+        assert_eq!(Ok(65_536), parse("64KiB"));
+        assert_eq!(Ok(2_000_000_000), parse("2GB"));
+        assert_eq!(Ok(2_000_000_000), parse("2 gb"));
+        assert_eq!(Ok(1_610_612_736), parse("1.5GiB"));
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_invalid_sizes() {
+        assert_eq!(Err("unknown unit XB".to_string()), parse("2XB"));
+        assert_eq!(Err("expected a number".to_string()), parse("KiB"));
+        assert_eq!(
+            Err("the size is not a whole number of bytes".to_string()),
+            parse("1.5B")
+        );
+        assert_eq!(Err("the size is too large".to_string()), parse("100000PiB"));
+    }
+
+    #[test]
+    fn should_display_sizes_with_the_largest_exact_unit() {
+        assert_eq!("512", ByteSize(512).to_string());
+        assert_eq!("64KiB", ByteSize(65_536).to_string());
+        assert_eq!("2GB", ByteSize(2_000_000_000).to_string());
+        assert_eq!("1KB", ByteSize(1000).to_string());
+        assert_eq!("0", ByteSize(0).to_string());
+    }
+}
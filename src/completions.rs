@@ -0,0 +1,464 @@
+//! Shell completion scripts, generated from the flags and subcommands of a `Program`, so users
+//! can tab-complete them in bash, zsh and fish.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::error::ProgramError;
+use crate::parser::BOOL_TYPE_ID;
+use crate::subcommand::HELP_SUBCOMMAND;
+use crate::value_enum::{ParseValueEnumError, ValueEnum};
+use crate::Program;
+
+/// The hidden flag added by `Program::with_completions_flag`.
+pub(crate) const COMPLETIONS_FLAG: &str = "generate-completions";
+const SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+/// A shell which completion scripts can be generated for.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl ValueEnum for Shell {
+    fn values() -> &'static [&'static str] {
+        &SHELLS
+    }
+}
+
+impl FromStr for Shell {
+    type Err = ParseValueEnumError;
+
+    fn from_str(s: &str) -> Result<Shell, ParseValueEnumError> {
+        match s.to_ascii_lowercase().as_str() {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(ParseValueEnumError::new(Shell::values())),
+        }
+    }
+}
+
+impl Display for Shell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Shell::Bash => write!(f, "bash"),
+            Shell::Zsh => write!(f, "zsh"),
+            Shell::Fish => write!(f, "fish"),
+        }
+    }
+}
+
+/// A flag as it is completed, which is the same for every shell.
+struct CompletedFlag {
+    arg: String,
+    name: String,
+    summary: String,
+    takes_value: bool,
+    values: Vec<String>,
+}
+
+impl<'a> Program<'a> {
+    /// Accept a hidden `--generate-completions <shell>` flag, which writes the completion script
+    /// for the shell to stdout instead of parsing, failing with
+    /// `ProgramError::CompletionsGenerated`. The shell is one of `bash`, `zsh` or `fish`.
+    ///
+    /// Like the help flag, `Program::parse_or_exit` and `Program::run` exit successfully after
+    /// writing it, so packaging scripts can run `mytool --generate-completions zsh > _mytool`.
+    pub fn with_completions_flag(mut self) -> Program<'a> {
+        self.completions_flag = true;
+        self
+    }
+
+    /// The completion script for the shell, completing every flag and subcommand, along with the
+    /// possible values of flags which have them. The `Program` needs a name to complete.
+    pub fn completions(&self, shell: Shell) -> String {
+        let name = self.name().unwrap_or_default();
+        match shell {
+            Shell::Bash => self.bash_completions(name),
+            Shell::Zsh => self.zsh_completions(name),
+            Shell::Fish => self.fish_completions(name),
+        }
+    }
+
+    /// Writes the completion script for the shell given after the completions flag at `at`.
+    pub(crate) fn show_completions(&self, shell: Option<&str>, at: usize) -> ProgramError {
+        let shell = match shell {
+            Some(shell) => shell,
+            None => {
+                return ProgramError::RequiredArgWasNotGiven {
+                    name: COMPLETIONS_FLAG.to_string(),
+                    prefix: self.settings.prefix.to_string(),
+                    index: Some(at),
+                }
+            }
+        };
+
+        match shell.parse::<Shell>() {
+            Ok(shell) => {
+                print!("{}", self.completions(shell));
+                ProgramError::CompletionsGenerated
+            }
+            Err(_) => ProgramError::FlagValueNotPossible {
+                name: COMPLETIONS_FLAG.to_string(),
+                prefix: self.settings.prefix.to_string(),
+                value: shell.to_string(),
+                possible_values: SHELLS.iter().map(|s| s.to_string()).collect(),
+                index: Some(at + 1),
+            },
+        }
+    }
+
+    /// The subcommands to complete, with the one-line summaries of each, including `help`.
+    fn completed_commands(&self) -> Vec<(String, Program<'a>)> {
+        let mut commands: Vec<(String, Program<'a>)> = self
+            .subcommands
+            .iter()
+            .map(|s| {
+                (
+                    s.name().unwrap_or_default().to_string(),
+                    self.inherit(s.clone()),
+                )
+            })
+            .collect();
+        if self.has_help_subcommand() {
+            let help = Program::new().with_description(self.strings.0.help_command());
+            commands.push((HELP_SUBCOMMAND.to_string(), help));
+        }
+        commands
+    }
+
+    fn completed_flags(&self) -> Vec<CompletedFlag> {
+        let mut flags: Vec<CompletedFlag> = self
+            .flags
+            .iter()
+            .map(|f| CompletedFlag {
+                arg: format!("{}{}", self.settings.prefix, f.name),
+                name: f.name.to_string(),
+                summary: summary(&f.desc),
+                takes_value: f.type_id != *BOOL_TYPE_ID,
+                values: f.possible_values.iter().map(|v| v.to_string()).collect(),
+            })
+            .collect();
+
+        let help_flag = self.settings.help_flag.as_deref();
+        let version_flag = Some("version").filter(|_| self.version.is_some());
+        for name in help_flag.into_iter().chain(version_flag) {
+            if self.flag(name).is_none() {
+                flags.push(CompletedFlag {
+                    arg: format!("{}{}", self.settings.prefix, name),
+                    name: name.to_string(),
+                    summary: String::new(),
+                    takes_value: false,
+                    values: vec![],
+                });
+            }
+        }
+        flags
+    }
+
+    fn bash_completions(&self, name: &str) -> String {
+        let commands = self.completed_commands();
+        let programs: Vec<(&str, &Program<'a>)> = std::iter::once(("", self))
+            .chain(commands.iter().map(|(name, s)| (name.as_str(), s)))
+            .collect();
+
+        let mut script = format!(
+            "{}() {{\n    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n    \
+             local prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"\n    local cmd=\"\"\n",
+            function_name(name)
+        );
+        if !commands.is_empty() {
+            let names: Vec<&str> = commands.iter().map(|(name, _)| name.as_str()).collect();
+            script += &format!(
+                "    local word\n    for word in \"${{COMP_WORDS[@]:1:COMP_CWORD-1}}\"; do\n        \
+                 case \"$word\" in\n            {}) cmd=\"$word\"; break ;;\n        esac\n    \
+                 done\n",
+                names.join("|")
+            );
+        }
+
+        script += "    case \"$cmd:$prev\" in\n";
+        for (command, program) in &programs {
+            for flag in program.completed_flags().iter().filter(|f| f.takes_value) {
+                let words = match flag.values.is_empty() {
+                    true => "-f".to_string(),
+                    false => format!("-W {}", sh_quote(&flag.values.join(" "))),
+                };
+                script += &format!(
+                    "        {}) COMPREPLY=($(compgen {} -- \"$cur\")); return ;;\n",
+                    sh_quote(&format!("{}:{}", command, flag.arg)),
+                    words
+                );
+            }
+        }
+        script += "    esac\n    case \"$cmd\" in\n";
+        for (command, program) in &programs {
+            let mut words: Vec<String> = program
+                .completed_flags()
+                .into_iter()
+                .map(|f| f.arg)
+                .collect();
+            if command.is_empty() {
+                words.extend(commands.iter().map(|(name, _)| name.clone()));
+            }
+            script += &format!(
+                "        {}) COMPREPLY=($(compgen -W {} -- \"$cur\")) ;;\n",
+                sh_quote(command),
+                sh_quote(&words.join(" "))
+            );
+        }
+        script += &format!(
+            "    esac\n}}\n\ncomplete -F {} {}\n",
+            function_name(name),
+            name
+        );
+        script
+    }
+
+    fn zsh_completions(&self, name: &str) -> String {
+        let commands = self.completed_commands();
+        let mut specs: Vec<String> = self.zsh_flag_specs();
+        if !commands.is_empty() {
+            specs.push(sh_quote("1: :->command"));
+            specs.push(sh_quote("*:: :->args"));
+        }
+
+        let mut script = format!(
+            "#compdef {}\n\n{}() {{\n    local state\n    _arguments -C",
+            name,
+            function_name(name)
+        );
+        for spec in &specs {
+            script += &format!(" \\\n        {}", spec);
+        }
+        script += "\n";
+
+        if !commands.is_empty() {
+            let described: Vec<String> = commands
+                .iter()
+                .map(|(name, s)| sh_quote(&format!("{}:{}", name, summary(&s.desc))))
+                .collect();
+            script += &format!(
+                "    case $state in\n        command)\n            local -a commands\n            \
+                 commands=({})\n            _describe 'command' commands\n            ;;\n        \
+                 args)\n            case $words[1] in\n",
+                described.join(" ")
+            );
+            for (command, program) in &commands {
+                let mut arguments = "_arguments".to_string();
+                for spec in program.zsh_flag_specs() {
+                    arguments += &format!(" {}", spec);
+                }
+                script += &format!("                {}) {} ;;\n", sh_quote(command), arguments);
+            }
+            script += "            esac\n            ;;\n    esac\n";
+        }
+
+        script += &format!("}}\n\n{} \"$@\"\n", function_name(name));
+        script
+    }
+
+    fn zsh_flag_specs(&self) -> Vec<String> {
+        let escape = |s: &str| {
+            s.replace('\\', "\\\\")
+                .replace('[', "\\[")
+                .replace(']', "\\]")
+                .replace(':', "\\:")
+        };
+        self.completed_flags()
+            .iter()
+            .map(|f| {
+                let mut spec = format!("{}[{}]", f.arg, escape(&f.summary));
+                if f.takes_value && f.values.is_empty() {
+                    spec += &format!(":{}:_files", escape(&f.name));
+                } else if f.takes_value {
+                    spec += &format!(":{}:({})", escape(&f.name), f.values.join(" "));
+                }
+                sh_quote(&spec)
+            })
+            .collect()
+    }
+
+    fn fish_completions(&self, name: &str) -> String {
+        let commands = self.completed_commands();
+        let mut script = format!("complete -c {} -f\n", name);
+
+        let top_condition = match commands.is_empty() {
+            true => String::new(),
+            false => format!(" -n {}", sh_quote("__fish_use_subcommand")),
+        };
+        for (command, subcommand) in &commands {
+            script += &format!(
+                "complete -c {}{} -a {} -d {}\n",
+                name,
+                top_condition,
+                sh_quote(command),
+                sh_quote(&summary(&subcommand.desc))
+            );
+        }
+        script += &self.fish_flag_completions(name, &top_condition);
+        for (command, subcommand) in &commands {
+            let condition = format!(
+                " -n {}",
+                sh_quote(&format!("__fish_seen_subcommand_from {}", command))
+            );
+            script += &subcommand.fish_flag_completions(name, &condition);
+        }
+        script
+    }
+
+    fn fish_flag_completions(&self, name: &str, condition: &str) -> String {
+        let option = match self.settings.prefix.as_ref() {
+            "-" => "-o",
+            _ => "-l",
+        };
+        self.completed_flags()
+            .iter()
+            .map(|f| {
+                let mut line = format!(
+                    "complete -c {}{} {} {}",
+                    name,
+                    condition,
+                    option,
+                    sh_quote(&f.name)
+                );
+                if !f.summary.is_empty() {
+                    line += &format!(" -d {}", sh_quote(&f.summary));
+                }
+                if f.takes_value && f.values.is_empty() {
+                    line += " -r -F";
+                } else if f.takes_value {
+                    line += &format!(" -r -a {}", sh_quote(&f.values.join(" ")));
+                }
+                line + "\n"
+            })
+            .collect()
+    }
+}
+
+/// The first line of a description, which is all there is room for in completions.
+fn summary(desc: &str) -> String {
+    desc.lines().next().unwrap_or_default().trim().to_string()
+}
+
+/// Such as `_my_tool` for `my-tool`.
+fn function_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("_{}", name)
+}
+
+/// Single quotes the text for a shell, so nothing in it is expanded.
+fn sh_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program() -> Program<'static> {
+        Program::new()
+            .with_name("mytool")
+            .with_completions_flag()
+            .with_optional_flag::<bool>("verbose", false, "Log everything")
+            .unwrap()
+            .with_optional_choice_flag("shell", Shell::Bash, "Shell to use")
+            .unwrap()
+            .with_subcommand(
+                "build",
+                Program::new()
+                    .with_description("Build the project")
+                    .with_required_flag::<String>("target", "Target to build")
+                    .unwrap(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn should_complete_flags_values_and_subcommands_in_bash() {
+        let script = program().completions(Shell::Bash);
+
+        assert!(script.starts_with("_mytool() {\n"));
+        assert!(script.contains("            build|help) cmd=\"$word\"; break ;;\n"));
+        assert!(script.contains(
+            "        ':--shell') COMPREPLY=($(compgen -W 'bash zsh fish' -- \"$cur\")); return ;;\n"
+        ));
+        assert!(script.contains(
+            "        'build:--target') COMPREPLY=($(compgen -f -- \"$cur\")); return ;;\n"
+        ));
+        assert!(script.contains(
+            "        '') COMPREPLY=($(compgen -W '--verbose --shell --help build help' -- \"$cur\")) ;;\n"
+        ));
+        assert!(script.ends_with("complete -F _mytool mytool\n"));
+    }
+
+    #[test]
+    fn should_complete_flags_values_and_subcommands_in_zsh() {
+        let script = program().completions(Shell::Zsh);
+
+        assert!(script.starts_with("#compdef mytool\n\n_mytool() {\n"));
+        assert!(script.contains("        '--verbose[Log everything]' \\\n"));
+        assert!(script.contains("        '--shell[Shell to use]:shell:(bash zsh fish)' \\\n"));
+        assert!(script.contains(
+            "commands=('build:Build the project' 'help:Show the help text of a command')"
+        ));
+        assert!(script.contains(
+            "                'build') _arguments '--target[Target to build]:target:_files' '--help[]' ;;\n"
+        ));
+    }
+
+    #[test]
+    fn should_complete_flags_values_and_subcommands_in_fish() {
+        let script = program().completions(Shell::Fish);
+
+        assert!(script.starts_with("complete -c mytool -f\n"));
+        assert!(script.contains(
+            "complete -c mytool -n '__fish_use_subcommand' -a 'build' -d 'Build the project'\n"
+        ));
+        assert!(script.contains(
+            "complete -c mytool -n '__fish_use_subcommand' -l 'shell' -d 'Shell to use' -r -a 'bash zsh fish'\n"
+        ));
+        assert!(script.contains(
+            "complete -c mytool -n '__fish_seen_subcommand_from build' -l 'target' -d 'Target to build' -r -F\n"
+        ));
+    }
+
+    #[test]
+    fn should_generate_completions_instead_of_parsing() {
+        let program = program();
+
+        assert_eq!(
+            ProgramError::CompletionsGenerated,
+            program
+                .parse_from(["--generate-completions", "fish"])
+                .unwrap_err()
+        );
+        assert_eq!(
+            ProgramError::FlagValueNotPossible {
+                name: "generate-completions".to_string(),
+                prefix: "--".to_string(),
+                value: "tcsh".to_string(),
+                possible_values: vec!["bash".to_string(), "zsh".to_string(), "fish".to_string()],
+                index: Some(1),
+            },
+            program
+                .parse_from(["--generate-completions", "tcsh"])
+                .unwrap_err()
+        );
+    }
+
+    #[test]
+    fn should_not_generate_completions_unless_enabled() {
+        let program = Program::new()
+            .parse_from_str_arr(&["--generate-completions", "zsh"])
+            .unwrap();
+
+        assert!(program.raw_args().contains(&"zsh".to_string()));
+    }
+}
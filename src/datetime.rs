@@ -0,0 +1,183 @@
+//! Dates and timestamps as flag values, for schedulers and report tools.
+//!
+//! With the `chrono` feature, `chrono::DateTime<FixedOffset>` and `chrono::DateTime<Utc>` take
+//! RFC 3339 timestamps such as `2024-05-01T09:30:00Z`, and `chrono::NaiveDate` takes simple dates
+//! such as `2024-05-01`, as they can already be parsed from strings.
+//!
+//! The `time` crate's types can't be parsed from strings directly, so with the `time` feature
+//! `Timestamp` and `Date` wrap them instead.
+
+#[cfg(feature = "time")]
+pub use self::time_types::*;
+
+#[cfg(feature = "time")]
+mod time_types {
+    use std::error::Error;
+    use std::fmt::{Display, Formatter};
+    use std::str::FromStr;
+
+    use time::format_description::well_known::Rfc3339;
+    use time::{Month, OffsetDateTime};
+
+    /// An RFC 3339 timestamp, such as `2024-05-01T09:30:00Z`, as a `time::OffsetDateTime`.
+    ///
+    /// This is only available with the `time` feature.
+    ///
+    /// ```
+    /// use commandrs::datetime::Timestamp;
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_required_flag::<Timestamp>("since", "Only report changes after this")
+    ///     .unwrap()
+    ///     .parse_from_str_arr(&["--since", "2024-05-01T09:30:00+02:00"])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(9, program.get::<Timestamp>("since").unwrap().0.hour());
+    /// ```
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+    pub struct Timestamp(pub OffsetDateTime);
+
+    impl FromStr for Timestamp {
+        type Err = ParseDateTimeError;
+
+        fn from_str(s: &str) -> Result<Timestamp, ParseDateTimeError> {
+            OffsetDateTime::parse(s, &Rfc3339)
+                .map(Timestamp)
+                .map_err(|err| ParseDateTimeError(err.to_string()))
+        }
+    }
+
+    impl Display for Timestamp {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            let formatted = self.0.format(&Rfc3339).map_err(|_| std::fmt::Error)?;
+            write!(f, "{}", formatted)
+        }
+    }
+
+    /// A simple date, such as `2024-05-01`, as a `time::Date`.
+    ///
+    /// This is only available with the `time` feature.
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+    pub struct Date(pub time::Date);
+
+    impl FromStr for Date {
+        type Err = ParseDateTimeError;
+
+        fn from_str(s: &str) -> Result<Date, ParseDateTimeError> {
+            let error = || ParseDateTimeError("expected a date such as 2024-05-01".to_string());
+
+            let mut parts = s.splitn(3, '-');
+            let mut next = || {
+                parts
+                    .next()
+                    .filter(|part| !part.is_empty())
+                    .ok_or_else(error)
+            };
+            let year: i32 = next()?.parse().map_err(|_| error())?;
+            let month: u8 = next()?.parse().map_err(|_| error())?;
+            let day: u8 = next()?.parse().map_err(|_| error())?;
+
+            let month =
+                Month::try_from(month).map_err(|err| ParseDateTimeError(err.to_string()))?;
+            time::Date::from_calendar_date(year, month, day)
+                .map(Date)
+                .map_err(|err| ParseDateTimeError(err.to_string()))
+        }
+    }
+
+    impl Display for Date {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    /// Why a `Timestamp` or `Date` could not be parsed.
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    pub struct ParseDateTimeError(String);
+
+    impl Display for ParseDateTimeError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl Error for ParseDateTimeError {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Program;
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn should_parse_chrono_timestamps_and_dates() {
+        use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+
+        let program = Program::new()
+            .with_required_flag::<DateTime<FixedOffset>>("since", "")
+            .unwrap()
+            .with_required_flag::<DateTime<Utc>>("until", "")
+            .unwrap()
+            .with_required_flag::<NaiveDate>("on", "")
+            .unwrap()
+            .parse_from_str_arr(&[
+                "--since",
+                "2024-05-01T09:30:00+02:00",
+                "--until",
+                "2024-05-02T00:00:00Z",
+                "--on",
+                "2024-05-01",
+            ])
+            .unwrap();
+
+        assert_eq!(
+            "2024-05-01 09:30:00 +02:00",
+            program
+                .get::<DateTime<FixedOffset>>("since")
+                .unwrap()
+                .to_string()
+        );
+        assert_eq!(
+            "2024-05-02 00:00:00 UTC",
+            program.get::<DateTime<Utc>>("until").unwrap().to_string()
+        );
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+            program.get::<NaiveDate>("on").unwrap()
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn should_parse_time_timestamps_and_dates() {
+        use super::{Date, Timestamp};
+
+        let program = Program::new()
+            .with_required_flag::<Timestamp>("since", "")
+            .unwrap()
+            .with_optional_flag::<Date>("on", "2024-01-01".parse().unwrap(), "")
+            .unwrap()
+            .parse_from_str_arr(&["--since", "2024-05-01T09:30:00Z", "--on", "2024-05-01"])
+            .unwrap();
+
+        assert_eq!(
+            "2024-05-01T09:30:00Z",
+            program.get::<Timestamp>("since").unwrap().to_string()
+        );
+        assert_eq!("2024-05-01", program.get::<Date>("on").unwrap().to_string());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn should_result_in_an_error_for_invalid_dates() {
+        use super::Date;
+
+        assert_eq!(
+            "expected a date such as 2024-05-01",
+            "May 1st".parse::<Date>().unwrap_err().to_string()
+        );
+        assert!("2024-02-30".parse::<Date>().is_err());
+        assert!("2024-13-01".parse::<Date>().is_err());
+    }
+}
@@ -0,0 +1,358 @@
+use serde::de::value::{SeqDeserializer, StrDeserializer};
+use serde::de::{DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::{forward_to_deserialize_any, Deserializer};
+
+use crate::error::{ProgramError, ValueParseError};
+use crate::flag::FlagValue;
+use crate::Program;
+
+impl<'a> Program<'a> {
+    /// Fill any `serde::Deserialize` struct from the parsed values of this `Program`. Each field
+    /// is taken from the flag with the same name, where `_` in the field name also matches `-` in
+    /// the flag name, so `use_tls` is filled by `--use-tls`.
+    ///
+    /// `Option` fields are `None` when there is no such flag, and `Vec` fields are filled with
+    /// every value of a raw flag.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     port: u16,
+    ///     use_tls: bool,
+    ///     user: Option<String>,
+    /// }
+    ///
+    /// let config: Config = Program::new()
+    ///     .with_required_flag::<u16>("port", "Port number")
+    ///     .unwrap()
+    ///     .with_optional_flag::<bool>("use-tls", false, "TLS PLS?")
+    ///     .unwrap()
+    ///     .parse_from_str_arr(&["--port", "8080", "--use-tls"])
+    ///     .unwrap()
+    ///     .deserialize()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(8080, config.port);
+    /// assert!(config.use_tls);
+    /// assert_eq!(None, config.user);
+    /// ```
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T, ProgramError> {
+        T::deserialize(ProgramDeserializer { program: self })
+    }
+
+    /// Finds the value of the flag matching a field name, trying the field name as it is first.
+    fn field_value(&self, field: &str) -> Option<&FlagValue<'a>> {
+        self.flag_values
+            .get(field)
+            .or_else(|| self.flag_values.get(field.replace('_', "-").as_str()))
+    }
+}
+
+impl serde::de::Error for ProgramError {
+    fn custom<T: std::fmt::Display>(msg: T) -> ProgramError {
+        ProgramError::FailedToDeserialize {
+            reason: msg.to_string(),
+        }
+    }
+}
+
+struct ProgramDeserializer<'p, 'a> {
+    program: &'p Program<'a>,
+}
+
+impl<'de> Deserializer<'de> for ProgramDeserializer<'_, '_> {
+    type Error = ProgramError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProgramError> {
+        let fields: Vec<&str> = self.program.flags.iter().map(|f| f.name.as_ref()).collect();
+        visitor.visit_map(FlagValues::new(self.program, &fields))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ProgramError> {
+        visitor.visit_map(FlagValues::new(self.program, fields))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Gives back each field with the value of its flag, skipping fields with no such flag so serde
+/// can decide whether they are missing or optional.
+struct FlagValues<'p, 'a> {
+    fields: std::vec::IntoIter<(&'p str, &'p FlagValue<'a>)>,
+    value: Option<&'p FlagValue<'a>>,
+}
+
+impl<'p, 'a> FlagValues<'p, 'a> {
+    fn new(program: &'p Program<'a>, fields: &[&'p str]) -> FlagValues<'p, 'a> {
+        let fields: Vec<(&str, &FlagValue)> = fields
+            .iter()
+            .filter_map(|&field| program.field_value(field).map(|value| (field, value)))
+            .collect();
+
+        FlagValues {
+            fields: fields.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for FlagValues<'_, '_> {
+    type Error = ProgramError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ProgramError> {
+        match self.fields.next() {
+            Some((field, value)) => {
+                self.value = Some(value);
+                let field: StrDeserializer<ProgramError> = field.into_deserializer();
+                seed.deserialize(field).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, ProgramError> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| ProgramError::FailedToDeserialize {
+                reason: "a value was asked for before its key".to_string(),
+            })?;
+        seed.deserialize(ValueDeserializer { value })
+    }
+}
+
+struct ValueDeserializer<'p, 'a> {
+    value: &'p FlagValue<'a>,
+}
+
+impl ValueDeserializer<'_, '_> {
+    /// The parsed value is used when the flag is of the type being deserialized, as it may have
+    /// been given in a form `FromStr` doesn't accept, such as `yes` for a boolean or `0xFF` for an
+    /// integer.
+    fn parse<T>(&self) -> Result<T, ProgramError>
+    where
+        T: std::str::FromStr + Clone + 'static,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        if let Some(value) = self.value.value.downcast_ref::<T>() {
+            return Ok(value.clone());
+        }
+        self.value
+            .str_value
+            .parse()
+            .map_err(|err| ProgramError::FailedToParseFlagValue {
+                name: self.value.name.to_string(),
+                type_name: std::any::type_name::<T>().to_string(),
+                value: self.value.shown_value(),
+                index: None,
+                source: ValueParseError::new(err),
+            })
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProgramError> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'_, '_> {
+    type Error = ProgramError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProgramError> {
+        visitor.visit_str(&self.value.str_value)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProgramError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ProgramError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Raw flags give back every one of their values, while any other flag is a single value.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ProgramError> {
+        let values = match self.value.value.downcast_ref::<Vec<String>>() {
+            Some(values) => values.as_slice(),
+            None => std::slice::from_ref(&self.value.str_value),
+        };
+        visitor.visit_seq(SeqDeserializer::new(values.iter().map(String::as_str)))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ProgramError> {
+        let variant: StrDeserializer<ProgramError> =
+            self.value.str_value.as_str().into_deserializer();
+        visitor.visit_enum(variant)
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Level {
+        Debug,
+        Info,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Config {
+        port: u16,
+        use_tls: bool,
+        host: String,
+        level: Level,
+        user: Option<String>,
+        cmd: Vec<String>,
+    }
+
+    fn program() -> Program<'static> {
+        Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<bool>("use-tls", false, "TLS PLS?")
+            .unwrap()
+            .with_optional_flag::<String>("host", "localhost".to_string(), "Host to bind")
+            .unwrap()
+            .with_optional_flag::<String>("level", "info".to_string(), "Log level")
+            .unwrap()
+            .with_raw_flag("cmd", "Command to run on startup")
+            .unwrap()
+    }
+
+    #[test]
+    fn should_deserialize_flags_into_struct_fields() {
+        let config: Config = program()
+            .parse_from_str_arr(&["--port", "8080", "--level", "debug", "--cmd", "ls", "-la"])
+            .unwrap()
+            .deserialize()
+            .unwrap();
+
+        assert_eq!(
+            Config {
+                port: 8080,
+                use_tls: false,
+                host: "localhost".to_string(),
+                level: Level::Debug,
+                user: None,
+                cmd: vec!["ls".to_string(), "-la".to_string()],
+            },
+            config
+        );
+    }
+
+    #[test]
+    fn should_deserialize_booleans_given_as_yes_or_no() {
+        let config: Config = program()
+            .parse_from_str_arr(&["--port", "8080", "--use-tls", "yes"])
+            .unwrap()
+            .deserialize()
+            .unwrap();
+
+        assert!(config.use_tls);
+    }
+
+    #[test]
+    fn should_result_in_an_error_when_a_field_has_no_flag() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Missing {
+            workers: usize,
+        }
+
+        let err = program()
+            .parse_from_str_arr(&["--port", "8080"])
+            .unwrap()
+            .deserialize::<Missing>()
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::FailedToDeserialize {
+                reason: "missing field `workers`".to_string()
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn should_result_in_an_error_when_a_value_does_not_fit_the_field() {
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct WrongType {
+            host: u32,
+        }
+
+        let err = program()
+            .parse_from_str_arr(&["--port", "8080"])
+            .unwrap()
+            .deserialize::<WrongType>()
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::FailedToParseFlagValue {
+                name: "host".to_string(),
+                type_name: "u32".to_string(),
+                value: "localhost".to_string(),
+                index: None,
+                source: ValueParseError::new("localhost".parse::<u32>().unwrap_err()),
+            },
+            err
+        );
+    }
+}
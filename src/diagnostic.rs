@@ -0,0 +1,257 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use miette::{Diagnostic, LabeledSpan, SourceCode};
+
+use crate::error::ProgramError;
+use crate::error::ProgramError::*;
+use crate::flag::REDACTED;
+use crate::Program;
+
+impl Diagnostic for ProgramError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let code = match self {
+            FlagAlreadyExistsWithName { .. } => "commandrs::flag_already_exists",
+            NoSuchFlagExistsWithName { .. } => "commandrs::no_such_flag",
+            FailedToParseFlagValue { .. } => "commandrs::failed_to_parse",
+            FlagTypeMismatch { .. } => "commandrs::type_mismatch",
+            RequiredArgWasNotGiven { .. } => "commandrs::required_arg",
+            RequiredArgsWereNotGiven { .. } => "commandrs::required_args",
+            AmbiguousFlagAbbreviation { .. } => "commandrs::ambiguous_abbreviation",
+            UnknownFlag { .. } => "commandrs::unknown_flag",
+            InvalidSpec { .. } => "commandrs::invalid_spec",
+            FailedToDeserialize { .. } => "commandrs::failed_to_deserialize",
+            InvalidFlagDefinition { .. } => "commandrs::invalid_flag_definition",
+            InvalidProgramDefinition { .. } => "commandrs::invalid_program_definition",
+            FlagValueNotPossible { .. } => "commandrs::value_not_possible",
+            InvalidFlagValue { .. } => "commandrs::invalid_value",
+            SubcommandAlreadyExistsWithName { .. } => "commandrs::subcommand_already_exists",
+            UnknownSubcommand { .. } => "commandrs::unknown_subcommand",
+            SubcommandNotGiven => "commandrs::subcommand_not_given",
+            ArgNotUnicode { .. } => "commandrs::arg_not_unicode",
+            CompletionsGenerated => "commandrs::completions",
+            ManPageGenerated => "commandrs::man_page",
+            SpecDumped => "commandrs::spec_dumped",
+            HelpFlagGiven => "commandrs::help",
+            VersionFlagGiven => "commandrs::version",
+            NoArgsGiven => "commandrs::no_args",
+        };
+        Some(Box::new(code))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let help = match self {
+            RequiredArgWasNotGiven { name, prefix, .. } => {
+                format!("give a value with {}{}", prefix, name)
+            }
+            RequiredArgsWereNotGiven { names, prefix } => {
+                format!(
+                    "give values with {}{}",
+                    prefix,
+                    names.join(&format!(", {}", prefix))
+                )
+            }
+            AmbiguousFlagAbbreviation {
+                prefix, candidates, ..
+            } => format!(
+                "use one of {}{}",
+                prefix,
+                candidates.join(&format!(", {}", prefix))
+            ),
+            FlagValueNotPossible {
+                possible_values, ..
+            } => format!("use one of {}", possible_values.join(", ")),
+            FailedToParseFlagValue { type_name, .. } => format!("give a valid {}", type_name),
+            _ => return None,
+        };
+        Some(Box::new(help))
+    }
+}
+
+/// A `ProgramError` together with the command line it came from, so it can be reported with the
+/// offending argument pointed out, like:
+///
+/// ```text
+///   × Could not parse port as type of u16
+///    ╭────
+///  1 │ --port eighty
+///    ·        ───┬──
+///    ·           ╰── not a valid u16
+///    ╰────
+/// ```
+///
+/// This is only available with the `miette` feature.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgsReport {
+    err: ProgramError,
+    command_line: String,
+    span: Option<(usize, usize)>,
+}
+
+impl ArgsReport {
+    /// Point out where in the arguments the error was caused, using `ProgramError::index`. The
+    /// `args` must be the same arguments which were parsed by the `program`.
+    ///
+    /// The values of the program's secret flags are redacted, as they are in errors, wherever
+    /// they are in the arguments, such as `--token ****` or `--token=****`.
+    pub fn new(program: &Program, err: ProgramError, args: &[String]) -> ArgsReport {
+        let mut args = program.redact_args(args);
+        let redacted = err.index().filter(|_| err.value() == Some(REDACTED));
+        if let Some(arg) = redacted.and_then(|i| args.get_mut(i)) {
+            *arg = Cow::Borrowed(REDACTED);
+        }
+
+        let offsets: Vec<usize> = args
+            .iter()
+            .scan(0, |offset, arg| {
+                let start = *offset;
+                *offset += arg.len() + 1;
+                Some(start)
+            })
+            .collect();
+        let span = err
+            .index()
+            .filter(|&i| i < args.len())
+            .map(|i| (offsets[i], args[i].len()));
+
+        ArgsReport {
+            err,
+            command_line: args.join(" "),
+            span,
+        }
+    }
+
+    /// The error being reported.
+    pub fn error(&self) -> &ProgramError {
+        &self.err
+    }
+
+    fn label(&self) -> String {
+        match &self.err {
+            FailedToParseFlagValue { type_name, .. } => format!("not a valid {}", type_name),
+            FlagValueNotPossible { .. } => "not a possible value".to_string(),
+            InvalidFlagValue { reason, .. } => reason.to_string(),
+            AmbiguousFlagAbbreviation { .. } => "ambiguous".to_string(),
+            UnknownFlag { .. } => "unknown flag".to_string(),
+            UnknownSubcommand { .. } => "unknown command".to_string(),
+            ArgNotUnicode { .. } => "not valid Unicode".to_string(),
+            RequiredArgWasNotGiven { .. } => "missing a value".to_string(),
+            _ => "here".to_string(),
+        }
+    }
+}
+
+impl Display for ArgsReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.err)
+    }
+}
+
+impl Error for ArgsReport {}
+
+impl Diagnostic for ArgsReport {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.err.code()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.err.help()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        Some(&self.command_line)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let (offset, len) = self.span?;
+        let label = LabeledSpan::new(Some(self.label()), offset, len);
+        Some(Box::new(std::iter::once(label)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn labels(report: &ArgsReport) -> Vec<LabeledSpan> {
+        report.labels().map(|l| l.collect()).unwrap_or_default()
+    }
+
+    #[test]
+    fn should_label_the_value_which_failed_to_parse() {
+        let args = args(&["--host", "localhost", "--port", "eighty"]);
+        let program = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap();
+        let err = program.parse_from(args.clone()).unwrap_err();
+
+        let report = ArgsReport::new(&program, err, &args);
+
+        assert_eq!(
+            "commandrs::failed_to_parse",
+            report.code().unwrap().to_string()
+        );
+        assert_eq!(
+            vec![LabeledSpan::new(Some("not a valid u16".to_string()), 24, 6)],
+            labels(&report)
+        );
+    }
+
+    #[test]
+    fn should_label_the_flag_which_is_missing_a_value() {
+        let args = args(&["--port"]);
+        let program = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap();
+        let err = program.parse_from(args.clone()).unwrap_err();
+
+        let report = ArgsReport::new(&program, err, &args);
+
+        assert_eq!(
+            vec![LabeledSpan::new(Some("missing a value".to_string()), 0, 6)],
+            labels(&report)
+        );
+        assert_eq!(
+            "give a value with --port",
+            report.help().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn should_not_label_anything_for_flags_which_were_not_given() {
+        let program = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap();
+        let err = program.parse_from(Vec::<String>::new()).unwrap_err();
+
+        assert!(labels(&ArgsReport::new(&program, err, &[])).is_empty());
+    }
+
+    #[test]
+    fn should_redact_every_secret_value_in_the_command_line() {
+        let args = args(&["--token", "s3cret", "--key=k3y", "--port", "eighty"]);
+        let program = Program::new()
+            .with_secret_flag("token", "API token")
+            .unwrap()
+            .with_optional_flag::<String>("key", String::new(), "Signing key")
+            .unwrap()
+            .with_secret("key")
+            .unwrap()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap();
+        let err = program.parse_from(args.clone()).unwrap_err();
+
+        let report = ArgsReport::new(&program, err, &args);
+
+        assert_eq!("--token **** --key=**** --port eighty", report.command_line);
+        assert_eq!(
+            vec![LabeledSpan::new(Some("not a valid u16".to_string()), 31, 6)],
+            labels(&report)
+        );
+    }
+}
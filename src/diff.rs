@@ -0,0 +1,375 @@
+//! Differences between a `Program` and a spec exported from an older version of it, so release
+//! automation can catch breaking changes to the command line interface and write changelogs.
+
+use std::fmt::{Display, Formatter};
+
+use crate::error::ProgramError;
+use crate::json::Json;
+use crate::spec::invalid_spec;
+use crate::Program;
+
+/// The fields of a flag which are compared, along with whether a change to each breaks existing
+/// invocations.
+const FLAG_FIELDS: [(&str, bool); 5] = [
+    ("type", true),
+    ("required", true),
+    ("raw", true),
+    ("default", false),
+    ("description", false),
+];
+
+/// A single difference between an older spec and a `Program`. The `command` is the subcommand it
+/// is in, such as `build`, which is empty for the `Program` itself.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum SpecChange {
+    FlagAdded {
+        command: String,
+        name: String,
+        is_required: bool,
+    },
+    FlagRemoved {
+        command: String,
+        name: String,
+    },
+    FlagChanged {
+        command: String,
+        name: String,
+        field: String,
+        old: String,
+        new: String,
+    },
+    SubcommandAdded {
+        command: String,
+        name: String,
+    },
+    SubcommandRemoved {
+        command: String,
+        name: String,
+    },
+    PrefixChanged {
+        command: String,
+        old: String,
+        new: String,
+    },
+}
+
+impl SpecChange {
+    /// Whether the change can break existing invocations, such as a removed flag, a new required
+    /// flag, or a flag changing type.
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            SpecChange::FlagAdded { is_required, .. } => *is_required,
+            SpecChange::FlagChanged {
+                field, old, new, ..
+            } => match field.as_str() {
+                "required" => old == "false" && new == "true",
+                field => FLAG_FIELDS.contains(&(field, true)),
+            },
+            SpecChange::FlagRemoved { .. }
+            | SpecChange::SubcommandRemoved { .. }
+            | SpecChange::PrefixChanged { .. } => true,
+            SpecChange::SubcommandAdded { .. } => false,
+        }
+    }
+}
+
+impl Display for SpecChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (command, in_command) =
+            match self {
+                SpecChange::FlagAdded { command, .. }
+                | SpecChange::SubcommandAdded { command, .. } => (command, "to"),
+                SpecChange::FlagRemoved { command, .. }
+                | SpecChange::SubcommandRemoved { command, .. } => (command, "from"),
+                SpecChange::FlagChanged { command, .. }
+                | SpecChange::PrefixChanged { command, .. } => (command, "in"),
+            };
+
+        match self {
+            SpecChange::FlagAdded {
+                name, is_required, ..
+            } => match is_required {
+                true => write!(f, "Added required flag {}", name),
+                false => write!(f, "Added flag {}", name),
+            },
+            SpecChange::FlagRemoved { name, .. } => write!(f, "Removed flag {}", name),
+            SpecChange::FlagChanged {
+                name,
+                field,
+                old,
+                new,
+                ..
+            } => write!(
+                f,
+                "Changed {} of flag {} from {} to {}",
+                field, name, old, new
+            ),
+            SpecChange::SubcommandAdded { name, .. } => write!(f, "Added command {}", name),
+            SpecChange::SubcommandRemoved { name, .. } => write!(f, "Removed command {}", name),
+            SpecChange::PrefixChanged { old, new, .. } => {
+                write!(f, "Changed prefix from {} to {}", old, new)
+            }
+        }?;
+
+        match command.is_empty() {
+            true => Ok(()),
+            false => write!(f, " {} {}", in_command, command),
+        }
+    }
+}
+
+/// Every difference between an older spec and a `Program`, from `Program::diff`. Its `Display`
+/// lists each change on its own line, ready for a changelog.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SpecDiff {
+    changes: Vec<SpecChange>,
+}
+
+impl SpecDiff {
+    pub fn changes(&self) -> &[SpecChange] {
+        &self.changes
+    }
+
+    /// Whether the `Program` is the same as the older spec.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether any of the changes can break existing invocations.
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|c| c.is_breaking())
+    }
+
+    /// Only the changes which can break existing invocations.
+    pub fn breaking_changes(&self) -> impl Iterator<Item = &SpecChange> {
+        self.changes.iter().filter(|c| c.is_breaking())
+    }
+}
+
+impl Display for SpecDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "- {}", change)?;
+        }
+        Ok(())
+    }
+}
+
+impl Program<'_> {
+    /// Compare the `Program` against a spec exported from an older version of it with
+    /// `Program::to_spec_json`, giving back every flag and subcommand which was added, removed or
+    /// changed, including in subcommands.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let old_spec = Program::new()
+    ///     .with_optional_flag::<u16>("port", 8080, "Port number")
+    ///     .unwrap()
+    ///     .to_spec_json();
+    ///
+    /// let diff = Program::new()
+    ///     .with_required_flag::<u16>("port", "Port number")
+    ///     .unwrap()
+    ///     .diff(&old_spec)
+    ///     .unwrap();
+    ///
+    /// assert!(diff.is_breaking());
+    /// assert_eq!(
+    ///     "- Changed required of flag --port from false to true\n\
+    ///      - Changed default of flag --port from 8080 to none\n",
+    ///     diff.to_string()
+    /// );
+    /// ```
+    pub fn diff(&self, old_spec: &str) -> Result<SpecDiff, ProgramError> {
+        let old = Json::parse(old_spec).map_err(invalid_spec)?;
+        let mut changes = vec![];
+        diff_specs(&old, &self.to_spec(), "", &mut changes);
+        Ok(SpecDiff { changes })
+    }
+}
+
+fn diff_specs(old: &Json, new: &Json, command: &str, changes: &mut Vec<SpecChange>) {
+    let old_prefix = text(old.get("prefix"), "--");
+    let prefix = text(new.get("prefix"), "--");
+    if old_prefix != prefix {
+        changes.push(SpecChange::PrefixChanged {
+            command: command.to_string(),
+            old: old_prefix,
+            new: prefix.clone(),
+        });
+    }
+
+    let old_flags = named(old.get("flags"));
+    let flags = named(new.get("flags"));
+    let flag_name = |name: &str| format!("{}{}", prefix, name);
+    for (name, old_flag) in &old_flags {
+        let Some((_, flag)) = flags.iter().find(|(n, _)| n == name) else {
+            changes.push(SpecChange::FlagRemoved {
+                command: command.to_string(),
+                name: flag_name(name),
+            });
+            continue;
+        };
+
+        for (field, _) in FLAG_FIELDS {
+            let old_value = text(old_flag.get(field), "none");
+            let new_value = text(flag.get(field), "none");
+            if old_value != new_value {
+                changes.push(SpecChange::FlagChanged {
+                    command: command.to_string(),
+                    name: flag_name(name),
+                    field: field.to_string(),
+                    old: old_value,
+                    new: new_value,
+                });
+            }
+        }
+    }
+    for (name, flag) in flags
+        .iter()
+        .filter(|(n, _)| !old_flags.iter().any(|(o, _)| o == n))
+    {
+        changes.push(SpecChange::FlagAdded {
+            command: command.to_string(),
+            name: flag_name(name),
+            is_required: flag.get("required") == Some(&Json::Bool(true)),
+        });
+    }
+
+    let old_subcommands = named(old.get("subcommands"));
+    let subcommands = named(new.get("subcommands"));
+    let subcommand_path = |name: &str| match command.is_empty() {
+        true => name.to_string(),
+        false => format!("{} {}", command, name),
+    };
+    for (name, old_subcommand) in &old_subcommands {
+        match subcommands.iter().find(|(n, _)| n == name) {
+            Some((_, subcommand)) => {
+                diff_specs(old_subcommand, subcommand, &subcommand_path(name), changes)
+            }
+            None => changes.push(SpecChange::SubcommandRemoved {
+                command: command.to_string(),
+                name: name.to_string(),
+            }),
+        }
+    }
+    for (name, _) in subcommands
+        .iter()
+        .filter(|(n, _)| !old_subcommands.iter().any(|(o, _)| o == n))
+    {
+        changes.push(SpecChange::SubcommandAdded {
+            command: command.to_string(),
+            name: name.to_string(),
+        });
+    }
+}
+
+/// The objects of an array by their `name`, leaving out any without one.
+fn named(json: Option<&Json>) -> Vec<(String, &Json)> {
+    match json {
+        Some(Json::Array(items)) => items
+            .iter()
+            .filter_map(|item| match item.get("name") {
+                Some(Json::String(name)) => Some((name.to_string(), item)),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// The value as it is shown in a change, with `missing` for null or missing values.
+fn text(json: Option<&Json>, missing: &str) -> String {
+    match json {
+        None | Some(Json::Null) => missing.to_string(),
+        Some(Json::String(s)) => s.to_string(),
+        Some(json) => json.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program() -> Program<'static> {
+        Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<bool>("use-tls", false, "TLS PLS?")
+            .unwrap()
+            .with_subcommand(
+                "build",
+                Program::new()
+                    .with_optional_flag::<String>("target", "x86".to_string(), "Target")
+                    .unwrap(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn should_have_no_changes_against_its_own_spec() {
+        let program = program();
+
+        assert!(program.diff(&program.to_spec_json()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_report_added_removed_and_changed_flags_and_subcommands() {
+        let old_spec = program().to_spec_json();
+        let program = Program::new()
+            .with_required_flag::<u32>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<u8>("workers", 4, "Number of workers")
+            .unwrap()
+            .with_subcommand("build", Program::new())
+            .unwrap()
+            .with_subcommand("test", Program::new())
+            .unwrap();
+
+        let diff = program.diff(&old_spec).unwrap();
+
+        assert_eq!(
+            "- Changed type of flag --port from u16 to u32\n\
+             - Removed flag --use-tls\n\
+             - Added flag --workers\n\
+             - Removed flag --target from build\n\
+             - Added command test\n",
+            diff.to_string()
+        );
+        assert_eq!(
+            vec![
+                "Changed type of flag --port from u16 to u32",
+                "Removed flag --use-tls",
+                "Removed flag --target from build",
+            ],
+            diff.breaking_changes()
+                .map(|c| c.to_string())
+                .collect::<Vec<String>>()
+        );
+    }
+
+    #[test]
+    fn should_only_break_for_new_flags_when_they_are_required() {
+        let old_spec = Program::new().to_spec_json();
+
+        let optional = Program::new()
+            .with_optional_flag::<bool>("dry-run", false, "Do nothing")
+            .unwrap();
+        let required = Program::new()
+            .with_required_flag::<String>("token", "API token")
+            .unwrap();
+
+        assert!(!optional.diff(&old_spec).unwrap().is_breaking());
+        assert!(required.diff(&old_spec).unwrap().is_breaking());
+    }
+
+    #[test]
+    fn should_fail_for_invalid_specs() {
+        assert!(matches!(
+            program().diff("{ not json"),
+            Err(ProgramError::InvalidSpec { .. })
+        ));
+    }
+}
@@ -0,0 +1,181 @@
+//! Human friendly durations, such as `30s` or `2h30m`, for timeout and interval flags.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The units a duration can be given in, largest first, with how many nanoseconds are in each.
+const UNITS: [(&str, u128); 7] = [
+    ("d", 86_400_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+/// A `Duration` which is given as a number followed by a unit, such as `30s`, `5m` or `2h30m`.
+/// The units are `d`, `h`, `m`, `s`, `ms`, `us` (or `µs`) and `ns`, and a number without a unit
+/// is not accepted, as it would be unclear whether it is seconds or milliseconds.
+///
+/// It is shown the same way, such as in the help text for defaults.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use commandrs::duration::HumanDuration;
+/// use commandrs::Program;
+///
+/// let program = Program::new()
+///     .with_optional_flag("timeout", HumanDuration(Duration::from_secs(30)), "Request timeout")
+///     .unwrap()
+///     .parse_from_str_arr(&["--timeout", "1m30s"])
+///     .unwrap();
+///
+/// let timeout: Duration = program.get::<HumanDuration>("timeout").unwrap().into();
+/// assert_eq!(Duration::from_secs(90), timeout);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct HumanDuration(pub Duration);
+
+impl From<HumanDuration> for Duration {
+    fn from(duration: HumanDuration) -> Duration {
+        duration.0
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> HumanDuration {
+        HumanDuration(duration)
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = ParseDurationError;
+
+    fn from_str(s: &str) -> Result<HumanDuration, ParseDurationError> {
+        let error = |reason: &str| ParseDurationError {
+            reason: reason.to_string(),
+        };
+        if s.is_empty() {
+            return Err(error("the duration is empty"));
+        }
+
+        let mut nanos: u128 = 0;
+        let mut rest = s;
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            if digits == 0 {
+                return Err(error("expected a number"));
+            }
+            let number: u128 = rest[..digits]
+                .parse()
+                .map_err(|_| error("the number is too large"))?;
+            rest = &rest[digits..];
+
+            let unit_len = rest
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let unit = match &rest[..unit_len] {
+                "µs" => "us",
+                unit => unit,
+            };
+            let (_, unit_nanos) =
+                UNITS
+                    .iter()
+                    .find(|(name, _)| *name == unit)
+                    .ok_or_else(|| match unit {
+                        "" => error("expected a unit, such as s or m"),
+                        _ => error(&format!("unknown unit {}", unit)),
+                    })?;
+            rest = &rest[unit_len..];
+
+            nanos = number
+                .checked_mul(*unit_nanos)
+                .and_then(|n| n.checked_add(nanos))
+                .ok_or_else(|| error("the duration is too long"))?;
+        }
+
+        let secs =
+            u64::try_from(nanos / 1_000_000_000).map_err(|_| error("the duration is too long"))?;
+        Ok(HumanDuration(Duration::new(
+            secs,
+            (nanos % 1_000_000_000) as u32,
+        )))
+    }
+}
+
+impl Display for HumanDuration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut nanos = self.0.as_nanos();
+        if nanos == 0 {
+            return write!(f, "0s");
+        }
+
+        // Days are left out, as something like 48h is easier to read than 2d.
+        for (unit, unit_nanos) in &UNITS[1..] {
+            if nanos >= *unit_nanos {
+                write!(f, "{}{}", nanos / unit_nanos, unit)?;
+                nanos %= unit_nanos;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why a `HumanDuration` could not be parsed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseDurationError {
+    reason: String,
+}
+
+impl Display for ParseDurationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl Error for ParseDurationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Duration, String> {
+        s.parse::<HumanDuration>()
+            .map(Duration::from)
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn should_parse_durations_with_units() {
+        assert_eq!(Ok(Duration::from_secs(30)), parse("30s"));
+        assert_eq!(Ok(Duration::from_secs(300)), parse("5m"));
+        assert_eq!(Ok(Duration::from_secs(9000)), parse("2h30m"));
+        assert_eq!(Ok(Duration::from_secs(86_400)), parse("1d"));
+        assert_eq!(Ok(Duration::from_millis(1500)), parse("1s500ms"));
+        assert_eq!(Ok(Duration::from_micros(20)), parse("20µs"));
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_invalid_durations() {
+        assert_eq!(
+            Err("expected a unit, such as s or m".to_string()),
+            parse("30")
+        );
+        assert_eq!(Err("unknown unit y".to_string()), parse("1y"));
+        assert_eq!(Err("expected a number".to_string()), parse("m"));
+        assert_eq!(Err("the duration is empty".to_string()), parse(""));
+    }
+
+    #[test]
+    fn should_display_what_it_parses() {
+        for s in ["0s", "30s", "2h30m", "48h", "1m1s1ms1us1ns"] {
+            assert_eq!(s, s.parse::<HumanDuration>().unwrap().to_string());
+        }
+    }
+}
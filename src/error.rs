@@ -1,15 +1,101 @@
 use std::error::Error;
-use std::fmt::{Display, Formatter};
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
 
 use crate::error::ProgramError::*;
 
+/// Everything which can go wrong when building, parsing or reading a `Program`.
+///
+/// New kinds of errors are added over time, so matching on this must have a wildcard arm. The
+/// accessor methods, such as `ProgramError::flag_name`, are a more stable way to get at the
+/// details of an error.
 #[derive(Debug, PartialEq, Clone)]
+#[non_exhaustive]
 pub enum ProgramError {
-    FlagAlreadyExistsWithName { name: String },
-    NoSuchFlagExistsWithName { name: String },
-    FailedToParseFlagValue { name: String, type_name: String },
-    RequiredArgWasNotGiven { name: String },
+    FlagAlreadyExistsWithName {
+        name: String,
+    },
+    NoSuchFlagExistsWithName {
+        name: String,
+    },
+    FailedToParseFlagValue {
+        name: String,
+        type_name: String,
+        value: String,
+        index: Option<usize>,
+        source: ValueParseError,
+    },
+    FlagTypeMismatch {
+        name: String,
+        expected: String,
+        requested: String,
+    },
+    RequiredArgWasNotGiven {
+        name: String,
+        prefix: String,
+        index: Option<usize>,
+    },
+    RequiredArgsWereNotGiven {
+        names: Vec<String>,
+        prefix: String,
+    },
+    AmbiguousFlagAbbreviation {
+        name: String,
+        prefix: String,
+        candidates: Vec<String>,
+        index: Option<usize>,
+    },
+    UnknownFlag {
+        name: String,
+        prefix: String,
+        index: Option<usize>,
+    },
+    InvalidSpec {
+        reason: String,
+    },
+    FailedToDeserialize {
+        reason: String,
+    },
+    InvalidFlagDefinition {
+        name: String,
+        reason: String,
+    },
+    InvalidProgramDefinition {
+        reason: String,
+    },
+    FlagValueNotPossible {
+        name: String,
+        prefix: String,
+        value: String,
+        possible_values: Vec<String>,
+        index: Option<usize>,
+    },
+    InvalidFlagValue {
+        name: String,
+        prefix: String,
+        value: String,
+        reason: String,
+        index: Option<usize>,
+    },
+    SubcommandAlreadyExistsWithName {
+        name: String,
+    },
+    UnknownSubcommand {
+        name: String,
+        suggestions: Vec<String>,
+        index: Option<usize>,
+    },
+    SubcommandNotGiven,
+    ArgNotUnicode {
+        arg: String,
+        index: Option<usize>,
+    },
+    CompletionsGenerated,
+    ManPageGenerated,
+    SpecDumped,
     HelpFlagGiven,
+    VersionFlagGiven,
+    NoArgsGiven,
 }
 
 impl Display for ProgramError {
@@ -21,17 +107,318 @@ impl Display for ProgramError {
             NoSuchFlagExistsWithName { name } => {
                 write!(f, "No such flag exists with name {}", name)
             }
-            FailedToParseFlagValue { name, type_name } => {
-                write!(f, "Could not parse {} as type of {}", name, type_name)
+            SubcommandAlreadyExistsWithName { name } => {
+                write!(f, "Subcommand already exists with name {}", name)
             }
-            RequiredArgWasNotGiven { name } => {
-                write!(f, "Required args was not given with name {}", name)
+            UnknownSubcommand {
+                name, suggestions, ..
+            } => {
+                write!(f, "Unknown command '{}'", name)?;
+                if !suggestions.is_empty() {
+                    let suggestions: Vec<String> =
+                        suggestions.iter().map(|s| format!("'{}'", s)).collect();
+                    write!(f, ", did you mean {}?", suggestions.join(" or "))?;
+                }
+                Ok(())
+            }
+            SubcommandNotGiven => write!(f, "No command was given"),
+            ArgNotUnicode { arg, .. } => write!(f, "Argument '{}' is not valid Unicode", arg),
+            CompletionsGenerated => write!(f, "Completions were generated"),
+            ManPageGenerated => write!(f, "Man page was generated"),
+            SpecDumped => write!(f, "Spec was dumped"),
+            FailedToParseFlagValue {
+                name,
+                type_name,
+                source,
+                ..
+            } => {
+                write!(
+                    f,
+                    "Could not parse {} as type of {}, {}",
+                    name, type_name, source
+                )
+            }
+            FlagTypeMismatch {
+                name,
+                expected,
+                requested,
+            } => {
+                write!(
+                    f,
+                    "Flag {} was registered as type of {}, but was requested as {}",
+                    name, expected, requested
+                )
+            }
+            RequiredArgWasNotGiven { name, prefix, .. } => {
+                write!(
+                    f,
+                    "Required args was not given with name {}{}",
+                    prefix, name
+                )
+            }
+            RequiredArgsWereNotGiven { names, prefix } => {
+                let names: Vec<String> = names
+                    .iter()
+                    .map(|name| format!("{}{}", prefix, name))
+                    .collect();
+                write!(
+                    f,
+                    "Required args were not given with names {}",
+                    names.join(", ")
+                )
+            }
+            AmbiguousFlagAbbreviation {
+                name,
+                prefix,
+                candidates,
+                ..
+            } => {
+                let candidates: Vec<String> = candidates
+                    .iter()
+                    .map(|c| format!("{}{}", prefix, c))
+                    .collect();
+                write!(
+                    f,
+                    "Flag {}{} is ambiguous, could be any of {}",
+                    prefix,
+                    name,
+                    candidates.join(", ")
+                )
+            }
+            UnknownFlag { name, prefix, .. } => {
+                write!(f, "Unknown flag {}{}", prefix, name)
+            }
+            InvalidSpec { reason } => {
+                write!(f, "Invalid program spec, {}", reason)
+            }
+            FailedToDeserialize { reason } => {
+                write!(f, "Could not deserialize flags, {}", reason)
+            }
+            InvalidFlagDefinition { name, reason } => {
+                write!(f, "Flag {} is not defined correctly, {}", name, reason)
+            }
+            InvalidProgramDefinition { reason } => {
+                write!(f, "Program is not defined correctly, {}", reason)
+            }
+            FlagValueNotPossible {
+                name,
+                prefix,
+                value,
+                possible_values,
+                ..
+            } => {
+                write!(
+                    f,
+                    "Value {} is not possible for {}{} [possible values: {}]",
+                    value,
+                    prefix,
+                    name,
+                    possible_values.join(", ")
+                )
+            }
+            InvalidFlagValue {
+                name,
+                prefix,
+                value,
+                reason,
+                ..
+            } => {
+                write!(
+                    f,
+                    "Value {} is not valid for {}{}, {}",
+                    value, prefix, name, reason
+                )
             }
             HelpFlagGiven => {
                 write!(f, "Help flag was given")
             }
+            VersionFlagGiven => {
+                write!(f, "Version flag was given")
+            }
+            NoArgsGiven => {
+                write!(f, "No args were given")
+            }
+        }
+    }
+}
+
+impl Error for ProgramError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FailedToParseFlagValue { source, .. } => Some(source.0.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl ProgramError {
+    /// The name of the flag the error is about, if it is about one.
+    pub fn flag_name(&self) -> Option<&str> {
+        match self {
+            FlagAlreadyExistsWithName { name }
+            | NoSuchFlagExistsWithName { name }
+            | FailedToParseFlagValue { name, .. }
+            | FlagTypeMismatch { name, .. }
+            | RequiredArgWasNotGiven { name, .. }
+            | AmbiguousFlagAbbreviation { name, .. }
+            | UnknownFlag { name, .. }
+            | InvalidFlagDefinition { name, .. }
+            | FlagValueNotPossible { name, .. }
+            | InvalidFlagValue { name, .. } => Some(name),
+            RequiredArgsWereNotGiven { .. }
+            | InvalidSpec { .. }
+            | InvalidProgramDefinition { .. }
+            | FailedToDeserialize { .. }
+            | SubcommandAlreadyExistsWithName { .. }
+            | UnknownSubcommand { .. }
+            | SubcommandNotGiven
+            | ArgNotUnicode { .. }
+            | CompletionsGenerated
+            | ManPageGenerated
+            | SpecDumped
+            | HelpFlagGiven
+            | VersionFlagGiven
+            | NoArgsGiven => None,
+        }
+    }
+
+    /// The type the flag's value was expected to be, if the error is about the type of a value.
+    pub fn expected_type(&self) -> Option<&str> {
+        match self {
+            FailedToParseFlagValue { type_name, .. } => Some(type_name),
+            FlagTypeMismatch { expected, .. } => Some(expected),
+            _ => None,
+        }
+    }
+
+    /// The value given by the user which caused the error, if there was one.
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            FailedToParseFlagValue { value, .. }
+            | FlagValueNotPossible { value, .. }
+            | InvalidFlagValue { value, .. } => Some(value),
+            _ => None,
         }
     }
+
+    /// The index of the argument which caused the error, if it came from a command line argument
+    /// rather than somewhere else, such as an environment variable or a prompt. This is the value
+    /// when the value was the problem, or the flag itself otherwise.
+    pub fn index(&self) -> Option<usize> {
+        match self {
+            FailedToParseFlagValue { index, .. }
+            | RequiredArgWasNotGiven { index, .. }
+            | AmbiguousFlagAbbreviation { index, .. }
+            | UnknownFlag { index, .. }
+            | UnknownSubcommand { index, .. }
+            | ArgNotUnicode { index, .. }
+            | FlagValueNotPossible { index, .. }
+            | InvalidFlagValue { index, .. } => *index,
+            _ => None,
+        }
+    }
+
+    /// Points the error at the argument at `at`, for errors which can be caused by an argument.
+    pub(crate) fn at_index(mut self, at: usize) -> ProgramError {
+        match &mut self {
+            FailedToParseFlagValue { index, .. }
+            | RequiredArgWasNotGiven { index, .. }
+            | AmbiguousFlagAbbreviation { index, .. }
+            | UnknownFlag { index, .. }
+            | UnknownSubcommand { index, .. }
+            | ArgNotUnicode { index, .. }
+            | FlagValueNotPossible { index, .. }
+            | InvalidFlagValue { index, .. } => *index = Some(at),
+            _ => {}
+        }
+        self
+    }
+}
+
+/// The error given back by a flag type's `FromStr` implementation, explaining why a value could
+/// not be parsed, such as a number being out of range rather than not being a number at all.
+#[derive(Clone)]
+pub struct ValueParseError(Arc<dyn Error + Send + Sync>);
+
+impl ValueParseError {
+    pub fn new(err: impl Error + Send + Sync + 'static) -> ValueParseError {
+        ValueParseError(Arc::new(err))
+    }
+}
+
+impl Display for ValueParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Debug for ValueParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+// The underlying errors can't be compared, so they are compared by their messages instead.
+impl PartialEq for ValueParseError {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string() == other.to_string()
+    }
 }
 
-impl Error for ProgramError {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_give_back_details_of_the_error() {
+        let err = FlagValueNotPossible {
+            name: "speed".to_string(),
+            prefix: "--".to_string(),
+            value: "ludicrous".to_string(),
+            possible_values: vec!["fast".to_string()],
+            index: Some(1),
+        };
+
+        assert_eq!(Some("speed"), err.flag_name());
+        assert_eq!(Some("ludicrous"), err.value());
+        assert_eq!(Some(1), err.index());
+        assert_eq!(None, err.expected_type());
+    }
+
+    #[test]
+    fn should_give_back_expected_type_of_type_errors() {
+        let err = FailedToParseFlagValue {
+            name: "port".to_string(),
+            type_name: "u16".to_string(),
+            value: "eighty".to_string(),
+            index: None,
+            source: ValueParseError::new("eighty".parse::<u16>().unwrap_err()),
+        };
+
+        assert_eq!(Some("port"), err.flag_name());
+        assert_eq!(Some("u16"), err.expected_type());
+        assert_eq!(Some("eighty"), err.value());
+        assert_eq!(None, err.index());
+        assert_eq!(None, HelpFlagGiven.flag_name());
+    }
+
+    #[test]
+    fn should_keep_why_a_value_could_not_be_parsed() {
+        let err = FailedToParseFlagValue {
+            name: "port".to_string(),
+            type_name: "u16".to_string(),
+            value: "70000".to_string(),
+            index: Some(1),
+            source: ValueParseError::new("70000".parse::<u16>().unwrap_err()),
+        };
+
+        assert_eq!(
+            "Could not parse port as type of u16, number too large to fit in target type",
+            err.to_string()
+        );
+        assert_eq!(
+            "number too large to fit in target type",
+            err.source().unwrap().to_string()
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! Process exit codes for errors, following the BSD `sysexits.h` conventions so scripts calling a
+//! tool can tell a misused command apart from a bug in the tool.
+
+use std::any::Any;
+use std::error::Error;
+use std::fmt::Display;
+use std::process::{self, ExitCode};
+
+use crate::error::ProgramError;
+use crate::error::ProgramError::*;
+use crate::matches::Matches;
+use crate::source::{ArgSource, ProcessArgs};
+use crate::Program;
+
+/// Everything went fine, which is also used when the help text or version was asked for.
+pub const EX_OK: u8 = 0;
+/// The application failed for its own reasons, rather than any of the reasons below.
+pub const EX_FAILURE: u8 = 1;
+/// The command was used incorrectly, such as a missing flag or a value of the wrong type.
+pub const EX_USAGE: u8 = 64;
+/// The given values could not be turned into what the program needed.
+pub const EX_DATAERR: u8 = 65;
+/// The program itself is wrong, such as a flag being defined twice.
+pub const EX_SOFTWARE: u8 = 70;
+/// The program's configuration is wrong, such as an invalid spec.
+pub const EX_CONFIG: u8 = 78;
+
+/// The exit code used for an error unless the application gives its own with
+/// `Program::with_exit_codes`.
+pub fn default_exit_code(err: &ProgramError) -> u8 {
+    match err {
+        HelpFlagGiven | VersionFlagGiven | CompletionsGenerated | ManPageGenerated | SpecDumped => {
+            EX_OK
+        }
+        NoArgsGiven
+        | FailedToParseFlagValue { .. }
+        | RequiredArgWasNotGiven { .. }
+        | RequiredArgsWereNotGiven { .. }
+        | AmbiguousFlagAbbreviation { .. }
+        | UnknownFlag { .. }
+        | UnknownSubcommand { .. }
+        | SubcommandNotGiven
+        | ArgNotUnicode { .. }
+        | FlagValueNotPossible { .. }
+        | InvalidFlagValue { .. } => EX_USAGE,
+        FailedToDeserialize { .. } => EX_DATAERR,
+        InvalidSpec { .. } => EX_CONFIG,
+        FlagAlreadyExistsWithName { .. }
+        | NoSuchFlagExistsWithName { .. }
+        | SubcommandAlreadyExistsWithName { .. }
+        | FlagTypeMismatch { .. }
+        | InvalidFlagDefinition { .. }
+        | InvalidProgramDefinition { .. } => EX_SOFTWARE,
+    }
+}
+
+/// Decides the exit code for an error.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ExitCodes(pub fn(&ProgramError) -> u8);
+
+impl Default for ExitCodes {
+    fn default() -> ExitCodes {
+        ExitCodes(default_exit_code)
+    }
+}
+
+// Like flag parsers, function pointers can't be reliably compared, and the exit codes only affect
+// how a `Program` finishes, not what it is.
+impl PartialEq for ExitCodes {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl<'a> Program<'a> {
+    /// Replace how errors are mapped to process exit codes by `Program::parse_or_exit` and
+    /// `Program::run`, which is `default_exit_code` otherwise.
+    pub fn with_exit_codes(mut self, exit_codes: fn(&ProgramError) -> u8) -> Program<'a> {
+        self.exit_codes = ExitCodes(exit_codes);
+        self
+    }
+
+    /// The process exit code for an error.
+    pub fn exit_code(&self, err: &ProgramError) -> u8 {
+        (self.exit_codes.0)(err)
+    }
+
+    /// Parse the command line arguments, or exit the process if they can't be parsed. The error
+    /// is written to stderr with `Program::print_error`, and the exit code is decided by
+    /// `Program::exit_code`.
+    ///
+    /// Like `Program::parse`, the first argument is taken as the name of the `Program`.
+    pub fn parse_or_exit(mut self) -> Program<'a> {
+        let parsed = self
+            .take_args(ProcessArgs)
+            .and_then(|args| self.parse_from(args).map(Matches::into_program));
+        match parsed {
+            Ok(program) => {
+                if let Some(code) = program.dispatch_external_subcommand() {
+                    process::exit(code.into());
+                }
+                program
+            }
+            Err(err) => {
+                self.print_error(&err);
+                process::exit(self.exit_code(&err).into())
+            }
+        }
+    }
+
+    /// Parse the command line arguments and run `main` with the parsed `Program`, giving back the
+    /// exit code to return from the real `main`. The help text and version are shown when asked
+    /// for, without running `main`.
+    ///
+    /// Errors, both from parsing and from `main`, are written to stderr like
+    /// `Program::parse_or_exit` does. A `ProgramError` from `main`, including one in a
+    /// `Box<dyn Error>`, exits with the code decided by `Program::exit_code`, while any other
+    /// error is written with `Program::print_failure` and exits with `EX_FAILURE`.
+    ///
+    /// ```no_run
+    /// use std::error::Error;
+    /// use std::process::ExitCode;
+    ///
+    /// use commandrs::Program;
+    ///
+    /// fn main() -> ExitCode {
+    ///     Program::new()
+    ///         .with_version(env!("CARGO_PKG_VERSION"))
+    ///         .with_required_flag::<u16>("port", "Port number")
+    ///         .unwrap()
+    ///         .run(|program| -> Result<(), Box<dyn Error>> {
+    ///             let listener = std::net::TcpListener::bind(("0.0.0.0", program.get("port")?))?;
+    ///             println!("Listening on {}", listener.local_addr()?);
+    ///             Ok(())
+    ///         })
+    /// }
+    /// ```
+    pub fn run<F, E>(self, main: F) -> ExitCode
+    where
+        F: FnOnce(&Program<'a>) -> Result<(), E>,
+        E: Display + 'static,
+    {
+        ExitCode::from(self.run_with_args(ProcessArgs, main))
+    }
+
+    fn run_with_args<F, E>(mut self, args: impl ArgSource, main: F) -> u8
+    where
+        F: FnOnce(&Program<'a>) -> Result<(), E>,
+        E: Display + 'static,
+    {
+        let parsed = self
+            .take_args(args)
+            .and_then(|args| self.parse_from(args).map(Matches::into_program));
+        let program = match parsed {
+            Ok(program) => program,
+            Err(err) => {
+                self.print_error(&err);
+                return self.exit_code(&err);
+            }
+        };
+        if let Some(code) = program.dispatch_external_subcommand() {
+            return code;
+        }
+
+        let err = match main(&program) {
+            Ok(()) => return EX_OK,
+            Err(err) => err,
+        };
+        match as_program_error(&err) {
+            Some(err) => {
+                self.print_error(err);
+                self.exit_code(err)
+            }
+            None => {
+                self.print_failure(&err);
+                EX_FAILURE
+            }
+        }
+    }
+}
+
+/// The error from `main` as a `ProgramError`, when it is one or is a boxed one, such as from `?`
+/// in a function giving back `Result<(), Box<dyn Error>>`.
+fn as_program_error<E: 'static>(err: &E) -> Option<&ProgramError> {
+    let err = err as &dyn Any;
+    err.downcast_ref::<ProgramError>()
+        .or_else(|| {
+            err.downcast_ref::<Box<dyn Error>>()
+                .and_then(|e| e.downcast_ref())
+        })
+        .or_else(|| {
+            err.downcast_ref::<Box<dyn Error + Send + Sync>>()
+                .and_then(|e| e.downcast_ref())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program() -> Program<'static> {
+        Program::new()
+            .with_help_pager(false)
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+    }
+
+    #[test]
+    fn should_use_sysexits_codes_by_default() {
+        let program = program();
+
+        assert_eq!(
+            EX_USAGE,
+            program.exit_code(&program.parse_from(["--port", "eighty"]).unwrap_err())
+        );
+        assert_eq!(EX_OK, program.exit_code(&HelpFlagGiven));
+        assert_eq!(
+            EX_SOFTWARE,
+            program.exit_code(&program.get::<String>("port").unwrap_err())
+        );
+    }
+
+    #[test]
+    fn should_use_the_exit_codes_of_the_application() {
+        let program = program().with_exit_codes(|err| match err {
+            HelpFlagGiven => 0,
+            _ => 2,
+        });
+
+        assert_eq!(
+            2,
+            program.run_with_args(Vec::<String>::new(), |_| Ok::<(), ProgramError>(()))
+        );
+    }
+
+    #[test]
+    fn should_run_main_with_the_parsed_program() {
+        let code = program().run_with_args(vec!["--port".to_string(), "8080".to_string()], |p| {
+            assert_eq!(8080, p.get::<u16>("port")?);
+            Ok::<(), ProgramError>(())
+        });
+
+        assert_eq!(EX_OK, code);
+    }
+
+    #[test]
+    fn should_exit_with_the_code_of_errors_from_main() {
+        let code = program().run_with_args(vec!["--port".to_string(), "8080".to_string()], |p| {
+            p.get::<String>("port").map(|_| ())
+        });
+
+        assert_eq!(EX_SOFTWARE, code);
+    }
+
+    #[test]
+    fn should_exit_with_the_code_of_boxed_errors_from_main() {
+        let args = || vec!["--port".to_string(), "8080".to_string()];
+
+        let code = program().run_with_args(args(), |p| -> Result<(), Box<dyn Error>> {
+            p.get::<String>("port")?;
+            Ok(())
+        });
+        assert_eq!(EX_SOFTWARE, code);
+
+        let code =
+            program().run_with_args(args(), |p| -> Result<(), Box<dyn Error + Send + Sync>> {
+                p.get::<String>("port")?;
+                Ok(())
+            });
+        assert_eq!(EX_SOFTWARE, code);
+    }
+
+    #[test]
+    fn should_exit_with_failure_for_other_errors_from_main() {
+        let code = program().run_with_args(vec!["--port".to_string(), "8080".to_string()], |_| {
+            Err("disk full")
+        });
+
+        assert_eq!(EX_FAILURE, code);
+    }
+
+    #[test]
+    fn should_exit_with_a_usage_error_after_showing_help_for_no_args() {
+        let code = program()
+            .with_args_required_else_help(true)
+            .run_with_args(Vec::<String>::new(), |_| -> Result<(), &str> {
+                panic!("main should not run")
+            });
+
+        assert_eq!(EX_USAGE, code);
+    }
+
+    #[test]
+    fn should_not_run_main_when_the_version_is_asked_for() {
+        let code = program()
+            .with_version("1.2.3")
+            .run_with_args(vec!["--version".to_string()], |_| -> Result<(), &str> {
+                panic!("main should not run")
+            });
+
+        assert_eq!(EX_OK, code);
+    }
+}
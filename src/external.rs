@@ -0,0 +1,215 @@
+//! External subcommands, which are executables on `PATH` named after the `Program`, such as
+//! `mytool-deploy` for `mytool deploy`, so other packages can add subcommands as plugins.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+use crate::exit::EX_FAILURE;
+use crate::Program;
+
+/// An external subcommand which was given, along with the arguments forwarded to it.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct ExternalSubcommand {
+    pub name: String,
+    pub path: PathBuf,
+    pub args: Vec<String>,
+}
+
+impl<'a> Program<'a> {
+    /// Look for external subcommands on `PATH`, which are executables named after the `Program`
+    /// and the subcommand, such as `mytool-deploy` for `mytool deploy`. They are listed in the
+    /// help text, and every argument after one is forwarded to it rather than parsed.
+    ///
+    /// `Program::parse_or_exit` and `Program::run` run an external subcommand when it is given,
+    /// exiting with its exit code. Otherwise, it can be run with
+    /// `Program::run_external_subcommand`. The `Program` needs a name for them to be found.
+    pub fn with_external_subcommands(mut self, enabled: bool) -> Program<'a> {
+        self.external_subcommands = enabled;
+        self
+    }
+
+    /// The names of the external subcommands on `PATH`, such as `deploy` for `mytool-deploy`,
+    /// leaving out any with the same name as a subcommand of the `Program`.
+    pub fn external_subcommands(&self) -> Vec<String> {
+        let mut names = vec![];
+        for dir in search_path() {
+            for path in fs::read_dir(dir).into_iter().flatten().flatten() {
+                let file_name = path.file_name();
+                let name = file_name
+                    .to_str()
+                    .and_then(|f| self.external_subcommand_name(f));
+                if let Some(name) = name.filter(|_| is_executable(&path.path())) {
+                    if self.find_subcommand(name).is_none() && !names.iter().any(|n| n == name) {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+        }
+        names.sort();
+        names
+    }
+
+    /// The name of the external subcommand which was given, such as `deploy`, if one was.
+    pub fn external_subcommand(&self) -> Option<&str> {
+        self.external_subcommand.as_ref().map(|e| e.name.as_str())
+    }
+
+    /// Run the external subcommand which was given, if one was, with the arguments after it,
+    /// waiting for it to finish.
+    pub fn run_external_subcommand(&self) -> Option<io::Result<ExitStatus>> {
+        let external = self.given_external_subcommand()?;
+        Some(Command::new(&external.path).args(&external.args).status())
+    }
+
+    /// Runs the external subcommand which was given, if one was, giving back the code to exit
+    /// with, which is its own exit code when it has one.
+    pub(crate) fn dispatch_external_subcommand(&self) -> Option<u8> {
+        let code = match self.run_external_subcommand()? {
+            Ok(status) => status.code().and_then(|c| u8::try_from(c).ok()),
+            Err(err) => {
+                self.print_failure(&err);
+                None
+            }
+        };
+        Some(code.unwrap_or(EX_FAILURE))
+    }
+
+    /// Finds the executable of the external subcommand with the given name, if they are enabled.
+    pub(crate) fn find_external_subcommand(&self, name: &str) -> Option<PathBuf> {
+        self.find_external_subcommand_in(name, search_path())
+    }
+
+    fn find_external_subcommand_in(
+        &self,
+        name: &str,
+        dirs: impl IntoIterator<Item = PathBuf>,
+    ) -> Option<PathBuf> {
+        // The name must not be able to point anywhere other than the directories being searched.
+        let is_plain_name = !name.is_empty()
+            && !name.starts_with('-')
+            && !name.contains(|c: char| std::path::is_separator(c));
+        if !self.external_subcommands || !is_plain_name {
+            return None;
+        }
+
+        let file_name = format!(
+            "{}{}{}",
+            self.external_subcommand_prefix()?,
+            name,
+            env::consts::EXE_SUFFIX
+        );
+        dirs.into_iter()
+            .map(|dir| dir.join(&file_name))
+            .find(|path| is_executable(path))
+    }
+
+    /// The name of the subcommand the executable is for, if it is for one of this `Program`.
+    fn external_subcommand_name<'f>(&self, file_name: &'f str) -> Option<&'f str> {
+        let name = file_name.strip_prefix(&self.external_subcommand_prefix()?)?;
+        let name = name.strip_suffix(env::consts::EXE_SUFFIX).unwrap_or(name);
+        Some(name).filter(|n| !n.is_empty())
+    }
+
+    /// Such as `mytool-` for `mytool`, or `mytool-build-` for its `build` subcommand.
+    fn external_subcommand_prefix(&self) -> Option<String> {
+        Some(format!("{}-", self.full_name()?.replace(' ', "-")))
+    }
+
+    /// The external subcommand given to this `Program`, or to the subcommand which was given.
+    fn given_external_subcommand(&self) -> Option<&ExternalSubcommand> {
+        self.external_subcommand.as_ref().or_else(|| {
+            self.subcommand
+                .as_ref()
+                .and_then(|s| s.given_external_subcommand())
+        })
+    }
+}
+
+fn search_path() -> Vec<PathBuf> {
+    env::var_os("PATH")
+        .map(|path| env::split_paths(&path).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program() -> Program<'static> {
+        Program::new()
+            .with_name("mytool")
+            .with_external_subcommands(true)
+    }
+
+    #[test]
+    fn should_only_find_external_subcommands_with_plain_names() {
+        let dir = env::temp_dir();
+        let dirs = || vec![dir.clone()];
+
+        assert_eq!(None, program().find_external_subcommand_in("", dirs()));
+        assert_eq!(None, program().find_external_subcommand_in("../sh", dirs()));
+        assert_eq!(
+            None,
+            program().find_external_subcommand_in("--help", dirs())
+        );
+    }
+
+    #[test]
+    fn should_not_find_external_subcommands_unless_enabled() {
+        let program = program().with_external_subcommands(false);
+
+        assert_eq!(
+            None,
+            program.find_external_subcommand_in("deploy", search_path())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_find_executables_named_after_the_program() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = env::temp_dir().join(format!("commandrs-external-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let executable = dir.join("mytool-deploy");
+        fs::write(&executable, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&executable, fs::Permissions::from_mode(0o755)).unwrap();
+        let not_executable = dir.join("mytool-notes");
+        fs::write(&not_executable, "").unwrap();
+
+        let program = program();
+        let found = program.find_external_subcommand_in("deploy", vec![dir.clone()]);
+        let not_found = program.find_external_subcommand_in("notes", vec![dir.clone()]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(Some(executable), found);
+        assert_eq!(None, not_found);
+    }
+
+    #[test]
+    fn should_name_external_subcommands_after_their_executables() {
+        let program = program();
+
+        assert_eq!(
+            Some("deploy"),
+            program.external_subcommand_name(&format!("mytool-deploy{}", env::consts::EXE_SUFFIX))
+        );
+        assert_eq!(None, program.external_subcommand_name("mytool-"));
+        assert_eq!(None, program.external_subcommand_name("othertool-deploy"));
+    }
+}
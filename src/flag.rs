@@ -1,15 +1,274 @@
-use std::any::TypeId;
+use std::any::{type_name, Any, TypeId};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+use std::sync::Arc;
 
-#[derive(PartialEq, Debug)]
+use crate::error::{ProgramError, ValueParseError};
+use crate::net::address_parser;
+use crate::number::int_parser;
+
+/// Shown in place of the values of secret flags, so they don't end up in logs.
+pub(crate) const REDACTED: &str = "****";
+
+/// A value which has already been parsed into the type its flag was registered with.
+pub(crate) type ParsedValue = Arc<dyn Any + Send + Sync>;
+
+/// The values of flags, by the names of the flags.
+pub(crate) type FlagValues<'a> = HashMap<Cow<'a, str>, FlagValue<'a>>;
+
+/// Parses the string representation of a value into the type a flag was registered with, giving
+/// back why it could not be parsed otherwise.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ValueParser(pub fn(&str) -> Result<ParsedValue, ValueParseError>);
+
+// Function pointers can't be reliably compared, but the parser is always decided by the flag's
+// type, which is compared by its `TypeId` instead.
+impl PartialEq for ValueParser {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+type ValidateFn = dyn Fn(&(dyn Any + Send + Sync)) -> Result<(), String> + Send + Sync;
+
+/// Checks a value after it has been parsed, giving back why it is not valid otherwise.
+#[derive(Clone)]
+pub(crate) struct ValueValidator(pub Arc<ValidateFn>);
+
+// Like parsers, validators can't be compared or printed, and they only decide which values are
+// accepted rather than what the flag is.
+impl PartialEq for ValueValidator {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Debug for ValueValidator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ValueValidator")
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
 pub(crate) struct Flag<'a> {
-    pub name: &'a str,
-    pub desc: &'a str,
+    pub name: Cow<'a, str>,
+    pub desc: Cow<'a, str>,
     pub is_required: bool,
     pub type_id: TypeId,
+    pub type_name: &'static str,
+    pub parser: ValueParser,
+    pub allow_hyphen_values: bool,
+    pub is_raw: bool,
+    pub is_secret: bool,
+    pub is_global: bool,
+    pub hide_default: bool,
+    pub default_display: Option<Cow<'a, str>>,
+    pub env_var: Option<Cow<'a, str>>,
+    pub possible_values: Vec<Cow<'a, str>>,
+    pub examples: Vec<Cow<'a, str>>,
+    pub validators: Vec<ValueValidator>,
+}
+
+impl<'a> Flag<'a> {
+    pub fn new<T>(
+        name: impl Into<Cow<'a, str>>,
+        desc: impl Into<Cow<'a, str>>,
+        is_required: bool,
+    ) -> Flag<'a>
+    where
+        T: FromStr + Send + Sync + 'static,
+        T::Err: Error + Send + Sync + 'static,
+    {
+        let parser = builtin_parser::<T>().unwrap_or(ValueParser(parse_value::<T>));
+        Flag::with_parser::<T>(name, desc, is_required, parser)
+    }
+
+    pub fn with_parser<T: 'static>(
+        name: impl Into<Cow<'a, str>>,
+        desc: impl Into<Cow<'a, str>>,
+        is_required: bool,
+        parser: ValueParser,
+    ) -> Flag<'a> {
+        Flag {
+            name: name.into(),
+            desc: desc.into(),
+            is_required,
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+            parser,
+            allow_hyphen_values: false,
+            is_raw: false,
+            is_secret: false,
+            is_global: false,
+            hide_default: false,
+            default_display: None,
+            env_var: None,
+            possible_values: vec![],
+            examples: vec![],
+            validators: vec![],
+        }
+    }
+
+    /// The value as it can be shown to the user, which is redacted for secret flags.
+    pub fn shown_value(&self, value: &str) -> String {
+        match self.is_secret {
+            true => REDACTED.to_string(),
+            false => value.to_string(),
+        }
+    }
 }
 
-#[derive(PartialEq, Debug)]
+/// Where the value of a flag came from.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ValueSource {
+    /// The flag was given on the command line.
+    CommandLine,
+    /// The flag was not given, so its default was used.
+    Default,
+    /// The flag was not given, so the user was prompted for it.
+    Prompt,
+    /// The flag was not given, so its environment variable was used.
+    Environment,
+}
+
+#[derive(Clone)]
 pub(crate) struct FlagValue<'a> {
-    pub name: &'a str,
+    pub name: Cow<'a, str>,
     pub str_value: String,
+    pub value: ParsedValue,
+    pub source: ValueSource,
+    pub occurrences: usize,
+    pub is_secret: bool,
+}
+
+impl<'a> FlagValue<'a> {
+    pub fn new<T: Any + Send + Sync>(
+        name: impl Into<Cow<'a, str>>,
+        str_value: String,
+        value: T,
+    ) -> FlagValue<'a> {
+        FlagValue {
+            name: name.into(),
+            str_value,
+            value: Arc::new(value),
+            source: ValueSource::Default,
+            occurrences: 0,
+            is_secret: false,
+        }
+    }
+
+    pub fn with_source(mut self, source: ValueSource) -> FlagValue<'a> {
+        self.source = source;
+        self
+    }
+
+    pub fn with_secret(mut self, is_secret: bool) -> FlagValue<'a> {
+        self.is_secret = is_secret;
+        self
+    }
+
+    /// The string value as it can be shown to the user, which is redacted for secret flags.
+    pub fn shown_value(&self) -> String {
+        match self.is_secret {
+            true => REDACTED.to_string(),
+            false => self.str_value.clone(),
+        }
+    }
+
+    /// Raw flags keep every one of their values untouched as a `Vec<String>`, though they are
+    /// also joined together as the string value.
+    pub fn raw(name: impl Into<Cow<'a, str>>, raw_values: Vec<String>) -> FlagValue<'a> {
+        FlagValue::new(name, raw_values.join(" "), raw_values)
+    }
+
+    /// Parses the string value using the flag's parser, giving back why it is not valid otherwise.
+    pub fn parse(flag: &Flag<'a>, str_value: String) -> Result<FlagValue<'a>, ProgramError> {
+        match (flag.parser.0)(&str_value) {
+            Ok(value) => Ok(FlagValue {
+                name: flag.name.clone(),
+                str_value,
+                value,
+                source: ValueSource::Default,
+                occurrences: 0,
+                is_secret: flag.is_secret,
+            }),
+            Err(source) => Err(ProgramError::FailedToParseFlagValue {
+                name: flag.name.to_string(),
+                type_name: flag.type_name.to_string(),
+                value: flag.shown_value(&str_value),
+                index: None,
+                source,
+            }),
+        }
+    }
+}
+
+// The parsed value can't be compared or printed, but it is always derived from the string value.
+impl PartialEq for FlagValue<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.str_value == other.str_value && self.source == other.source
+    }
+}
+
+impl Debug for FlagValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FlagValue")
+            .field("name", &self.name)
+            .field("str_value", &self.shown_value())
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+/// The parser for types which are parsed more leniently, or explain themselves better, than
+/// their `FromStr` does.
+fn builtin_parser<T: Any>() -> Option<ValueParser> {
+    if TypeId::of::<T>() == TypeId::of::<bool>() {
+        return Some(ValueParser(parse_bool));
+    }
+    int_parser::<T>().or_else(address_parser::<T>)
+}
+
+/// Booleans can also be given as `yes`/`no`, `on`/`off` or `1`/`0`, in any case, as that is how
+/// scripts often pass them.
+pub(crate) fn parse_bool(s: &str) -> Result<ParsedValue, ValueParseError> {
+    let value = if ["true", "yes", "on", "1"]
+        .iter()
+        .any(|t| t.eq_ignore_ascii_case(s))
+    {
+        true
+    } else if ["false", "no", "off", "0"]
+        .iter()
+        .any(|f| f.eq_ignore_ascii_case(s))
+    {
+        false
+    } else {
+        return Err(ValueParseError::new(ParseBoolError));
+    };
+    Ok(Arc::new(value))
+}
+
+/// A boolean which is none of the accepted spellings.
+#[derive(Debug)]
+struct ParseBoolError;
+
+impl Display for ParseBoolError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected one of true, false, yes, no, on, off, 1 or 0")
+    }
+}
+
+impl Error for ParseBoolError {}
+
+fn parse_value<T>(s: &str) -> Result<ParsedValue, ValueParseError>
+where
+    T: FromStr + Send + Sync + 'static,
+    T::Err: Error + Send + Sync + 'static,
+{
+    s.parse::<T>()
+        .map(|value| Arc::new(value) as ParsedValue)
+        .map_err(ValueParseError::new)
 }
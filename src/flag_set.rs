@@ -0,0 +1,143 @@
+use crate::error::ProgramError;
+use crate::Program;
+
+/// A set of related flags which can contribute itself to a `Program`, and be extracted back out
+/// once it is parsed. This lets independent modules, such as logging or database options, each
+/// own their flags without one function having to register everything.
+///
+/// Unlike `FromProgram`, the set is registered by value, so the set given to
+/// `Program::with_flag_set` can carry the defaults to register with.
+///
+/// ```
+/// use commandrs::error::ProgramError;
+/// use commandrs::{FlagSet, Program};
+///
+/// struct LogOptions {
+///     level: String,
+/// }
+///
+/// impl FlagSet for LogOptions {
+///     fn register<'a>(self, program: Program<'a>) -> Result<Program<'a>, ProgramError> {
+///         program.with_optional_flag::<String>("log-level", self.level, "Log level")
+///     }
+///
+///     fn extract(program: &Program) -> Result<LogOptions, ProgramError> {
+///         Ok(LogOptions {
+///             level: program.get::<String>("log-level")?,
+///         })
+///     }
+/// }
+///
+/// let program = Program::new()
+///     .with_flag_set(LogOptions { level: "info".to_string() })?
+///     .parse_from_str_arr(&[])?;
+///
+/// assert_eq!("info", program.extract::<LogOptions>()?.level);
+/// # Ok::<(), ProgramError>(())
+/// ```
+pub trait FlagSet: Sized {
+    /// Register every flag of this set on the `Program`.
+    fn register<'a>(self, program: Program<'a>) -> Result<Program<'a>, ProgramError>;
+
+    /// Build this set from the values of a parsed `Program`.
+    fn extract(program: &Program) -> Result<Self, ProgramError>;
+}
+
+impl<'a> Program<'a> {
+    /// Register every flag of a `FlagSet` on this `Program`.
+    pub fn with_flag_set(self, set: impl FlagSet) -> Result<Program<'a>, ProgramError> {
+        set.register(self)
+    }
+
+    /// Extract a `FlagSet` from the parsed values of this `Program`.
+    pub fn extract<T: FlagSet>(&self) -> Result<T, ProgramError> {
+        T::extract(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct LogOptions {
+        level: String,
+        verbose: bool,
+    }
+
+    impl FlagSet for LogOptions {
+        fn register<'a>(self, program: Program<'a>) -> Result<Program<'a>, ProgramError> {
+            program
+                .with_optional_flag::<String>("log-level", self.level, "Log level")?
+                .with_optional_flag::<bool>("verbose", self.verbose, "Log everything")
+        }
+
+        fn extract(program: &Program) -> Result<LogOptions, ProgramError> {
+            Ok(LogOptions {
+                level: program.get::<String>("log-level")?,
+                verbose: program.get::<bool>("verbose")?,
+            })
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct DbOptions {
+        url: String,
+    }
+
+    impl FlagSet for DbOptions {
+        fn register<'a>(self, program: Program<'a>) -> Result<Program<'a>, ProgramError> {
+            program.with_required_flag::<String>("db-url", "Database URL")
+        }
+
+        fn extract(program: &Program) -> Result<DbOptions, ProgramError> {
+            Ok(DbOptions {
+                url: program.get::<String>("db-url")?,
+            })
+        }
+    }
+
+    #[test]
+    fn should_register_and_extract_independent_flag_sets() {
+        let program = Program::new()
+            .with_flag_set(LogOptions {
+                level: "info".to_string(),
+                verbose: false,
+            })
+            .unwrap()
+            .with_flag_set(DbOptions { url: String::new() })
+            .unwrap()
+            .parse_from_str_arr(&["--db-url", "postgres://localhost", "--verbose"])
+            .unwrap();
+
+        assert_eq!(
+            LogOptions {
+                level: "info".to_string(),
+                verbose: true,
+            },
+            program.extract::<LogOptions>().unwrap()
+        );
+        assert_eq!(
+            DbOptions {
+                url: "postgres://localhost".to_string(),
+            },
+            program.extract::<DbOptions>().unwrap()
+        );
+    }
+
+    #[test]
+    fn should_not_be_able_to_register_flag_sets_with_the_same_flags() {
+        let err = Program::new()
+            .with_flag_set(DbOptions { url: String::new() })
+            .unwrap()
+            .with_flag_set(DbOptions { url: String::new() })
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::FlagAlreadyExistsWithName {
+                name: "db-url".to_string()
+            },
+            err
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use crate::error::ProgramError;
+use crate::Program;
+
+/// A struct which can be built from the values of a parsed `Program`, for extracting a whole
+/// config in one call with `Program::into_struct`.
+///
+/// ```
+/// use commandrs::error::ProgramError;
+/// use commandrs::{FromParsedProgram, Program};
+///
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// impl FromParsedProgram for Config {
+///     fn from_parsed_program(program: &Program) -> Result<Config, ProgramError> {
+///         Ok(Config {
+///             port: program.get::<u16>("port")?,
+///         })
+///     }
+/// }
+///
+/// let config: Config = Program::new()
+///     .with_required_flag::<u16>("port", "Port number")?
+///     .parse_from_str_arr(&["--port", "8080"])?
+///     .into_struct()?;
+///
+/// assert_eq!(8080, config.port);
+/// # Ok::<(), ProgramError>(())
+/// ```
+pub trait FromParsedProgram: Sized {
+    /// Build this struct from the values of a parsed `Program`.
+    fn from_parsed_program(program: &Program) -> Result<Self, ProgramError>;
+}
+
+/// A struct which can register its own flags on a `Program`, and be built back up from the parsed
+/// values of those flags.
+///
+/// This is usually derived with `#[derive(FromProgram)]` when the `derive` feature is enabled,
+/// rather than being implemented by hand.
+///
+/// ```
+/// use commandrs::error::ProgramError;
+/// use commandrs::{FromParsedProgram, FromProgram, Program};
+///
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// impl FromProgram for Config {
+///     fn register<'a>(program: Program<'a>) -> Result<Program<'a>, ProgramError> {
+///         program.with_required_flag::<u16>("port", "Port number")
+///     }
+/// }
+///
+/// impl FromParsedProgram for Config {
+///     fn from_parsed_program(program: &Program) -> Result<Config, ProgramError> {
+///         Ok(Config {
+///             port: program.get::<u16>("port")?,
+///         })
+///     }
+/// }
+///
+/// let config: Config = Program::new()
+///     .with_flags_from::<Config>()?
+///     .parse_from_str_arr(&["--port", "8080"])?
+///     .into_struct()?;
+///
+/// assert_eq!(8080, config.port);
+/// # Ok::<(), ProgramError>(())
+/// ```
+pub trait FromProgram: FromParsedProgram {
+    /// Register every flag needed to build this struct on the `Program`.
+    fn register<'a>(program: Program<'a>) -> Result<Program<'a>, ProgramError>;
+}
+
+/// An enum with a variant for each subcommand of a `Program`, each of which can carry the config
+/// of its subcommand, for matching on the subcommand which was given with
+/// `Program::subcommand_as`.
+///
+/// This is usually derived with `#[derive(Subcommands)]` when the `derive` feature is enabled,
+/// rather than being implemented by hand.
+///
+/// ```
+/// use commandrs::error::ProgramError;
+/// use commandrs::{Program, Subcommands};
+///
+/// enum Command {
+///     Build { release: bool },
+///     Test,
+/// }
+///
+/// impl Subcommands for Command {
+///     fn register<'a>(program: Program<'a>) -> Result<Program<'a>, ProgramError> {
+///         program
+///             .with_subcommand(
+///                 "build",
+///                 Program::new().with_optional_flag::<bool>("release", false, "Optimise")?,
+///             )?
+///             .with_subcommand("test", Program::new())
+///     }
+///
+///     fn from_subcommand(
+///         name: &str,
+///         subcommand: &Program,
+///     ) -> Option<Result<Command, ProgramError>> {
+///         match name {
+///             "build" => Some(subcommand.get("release").map(|release| Command::Build { release })),
+///             "test" => Some(Ok(Command::Test)),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// let program = Program::new()
+///     .with_subcommands_from::<Command>()?
+///     .parse_from_str_arr(&["build", "--release"])?;
+///
+/// match program.subcommand_as::<Command>()? {
+///     Command::Build { release } => assert!(release),
+///     Command::Test => unreachable!(),
+/// }
+/// # Ok::<(), ProgramError>(())
+/// ```
+pub trait Subcommands: Sized {
+    /// Add every subcommand to the `Program`.
+    fn register<'a>(program: Program<'a>) -> Result<Program<'a>, ProgramError>;
+
+    /// Build the variant for the subcommand with the given name from its parsed `Program`, or
+    /// `None` when there is no variant for it.
+    fn from_subcommand(name: &str, subcommand: &Program) -> Option<Result<Self, ProgramError>>;
+}
+
+impl<'a> Program<'a> {
+    /// Add the subcommands of a `Subcommands` enum, which is the same as calling
+    /// `Subcommands::register` with this `Program`.
+    pub fn with_subcommands_from<T: Subcommands>(self) -> Result<Program<'a>, ProgramError> {
+        T::register(self)
+    }
+
+    /// Build the variant of a `Subcommands` enum for the subcommand which was given. This fails
+    /// with `ProgramError::SubcommandNotGiven` when none was.
+    pub fn subcommand_as<T: Subcommands>(&self) -> Result<T, ProgramError> {
+        let subcommand = self.subcommand().ok_or(ProgramError::SubcommandNotGiven)?;
+        let name = subcommand.name().unwrap_or_default();
+        T::from_subcommand(name, subcommand).unwrap_or_else(|| {
+            Err(ProgramError::UnknownSubcommand {
+                name: name.to_string(),
+                suggestions: vec![],
+                index: None,
+            })
+        })
+    }
+
+    /// Register the flags of a `FromProgram` struct, which is the same as calling
+    /// `FromProgram::register` with this `Program`.
+    pub fn with_flags_from<T: FromProgram>(self) -> Result<Program<'a>, ProgramError> {
+        T::register(self)
+    }
+
+    /// Build a struct from the parsed values of this `Program`.
+    pub fn into_struct<T: FromParsedProgram>(&self) -> Result<T, ProgramError> {
+        T::from_parsed_program(self)
+    }
+}
@@ -1,66 +1,313 @@
+use std::any::TypeId;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::byte_size::ByteSize;
+use crate::parser::BOOL_TYPE_ID;
+use crate::spec::short_type_name;
+use crate::subcommand::HELP_SUBCOMMAND;
+use crate::tokenizer::{quote, tokenize};
 use crate::Program;
 
+/// Descriptions are wrapped so each row of the help text fits within this many columns.
+const HELP_WIDTH: usize = 100;
+/// Terminals usually render a tab as this many columns.
+const TAB_WIDTH: usize = 8;
+/// The narrowest a description is wrapped to, however long the rest of the row is.
+const MIN_DESC_WIDTH: usize = 20;
+/// Used when `$PAGER` is not set. `-R` keeps any colours intact.
+const DEFAULT_PAGER: &str = "less -R";
+
 impl Program<'_> {
+    /// Prints the long or short help text to stdout, going through a pager when it is enabled and
+    /// the help text is too long for the terminal.
+    pub(crate) fn print_help(&self, long: bool) {
+        let help_text = match long {
+            true => self.generate_help_text(),
+            false => self.generate_short_help_text(),
+        };
+        if !self.settings.help_pager || !io::stdout().is_terminal() || !is_too_tall(&help_text) {
+            println!("{}", help_text);
+            return;
+        }
+
+        if page(&help_text).is_err() {
+            println!("{}", help_text);
+        }
+    }
+
+    /// A single line showing every argument the `Program` takes, such as
+    /// `Usage: mytool --port <u16> [--use-tls]`, where optional flags are in brackets. The line
+    /// given to `Program::with_usage` is used instead, when there is one.
+    pub fn usage(&self) -> String {
+        if let Some(usage) = &self.usage {
+            return self.strings.0.usage(usage).trim_end().to_string();
+        }
+
+        let flags = self.flags.iter().map(|f| {
+            let flag = format!("{}{}", self.settings.prefix, f.name);
+            let arg = if f.is_raw {
+                format!("{} <args>...", flag)
+            } else if f.type_id == *BOOL_TYPE_ID {
+                flag
+            } else {
+                format!("{} <{}>", flag, short_type_name(f.type_name))
+            };
+
+            if f.is_required {
+                arg
+            } else {
+                format!("[{}]", arg)
+            }
+        });
+        let command = Some("[command]".to_string()).filter(|_| !self.subcommands.is_empty());
+        let args: Vec<String> = self
+            .full_name()
+            .iter()
+            .map(|n| n.to_string())
+            .chain(flags)
+            .chain(command)
+            .collect();
+
+        self.strings.0.usage(&args.join(" ")).trim_end().to_string()
+    }
+
+    /// The long help text, shown for the help flag, such as `--help`.
     pub(crate) fn generate_help_text(&self) -> String {
+        self.help_text(true)
+    }
+
+    /// The short help text, shown for aliases of the help flag, such as `-h`. This leaves out the
+    /// long description and the text after the flags, keeping only the one-line description.
+    pub(crate) fn generate_short_help_text(&self) -> String {
+        self.help_text(false)
+    }
+
+    fn help_text(&self, long: bool) -> String {
         // We need to figure out the longest of each part of the flag.
         // It's just for formatting, though.
+        let strings = &self.strings.0;
         let (longest_name, longest_ref_or_def, flag_data) = self
             .flags
             .iter()
             .map(|f| {
-                let req_or_def = if f.is_required {
-                    "(required)".to_string()
+                let req_or_def = if f.is_secret {
+                    strings.secret()
+                } else if f.is_required {
+                    strings.required()
+                } else if f.is_raw {
+                    strings.remaining_args()
+                } else if f.hide_default {
+                    strings.optional()
+                } else if let Some(default_display) = &f.default_display {
+                    strings.default_value(default_display)
+                } else if let Ok(default) = self.default_flag(&f.name) {
+                    strings.default_value(&default.str_value)
                 } else {
-                    let default_value = self.unwrap_default_flag_value(f.name);
-                    format!("(default: {})", default_value)
+                    strings.optional()
                 };
 
-                (f.name, req_or_def, f.desc)
+                let mut desc = f.desc.to_string();
+                if !f.possible_values.is_empty() {
+                    let possible_values: Vec<&str> =
+                        f.possible_values.iter().map(|v| v.as_ref()).collect();
+                    desc = format!("{} {}", desc, strings.possible_values(&possible_values));
+                }
+                if f.type_id == TypeId::of::<ByteSize>() {
+                    desc = format!("{} {}", desc, strings.byte_size_units());
+                }
+                if let Some(env_var) = &f.env_var {
+                    desc = format!("{} {}", desc, strings.env_var(env_var));
+                }
+                for example in f.examples.iter().filter(|_| long) {
+                    let example = format!("{}{} {}", self.settings.prefix, f.name, quote(example));
+                    desc = format!("{}\n{}", desc, strings.example(&example));
+                }
+                let desc = desc.trim().to_string();
+
+                (f.name.as_ref(), req_or_def, desc)
             })
             .fold(
                 (0, 0, vec![]),
                 |(longest_name, longest_req_or_def, acc), x| {
                     (
-                        longest_name.max(x.0.len()),
-                        longest_req_or_def.max(x.1.len()),
+                        longest_name.max(x.0.width()),
+                        longest_req_or_def.max(x.1.width()),
                         [acc, vec![x]].concat(),
                     )
                 },
             );
 
+        // Every line of a description after the first hangs under where the first one started.
+        let desc_column = self.settings.prefix.width() + longest_name + longest_ref_or_def + 3;
+        let desc_width = HELP_WIDTH
+            .saturating_sub(TAB_WIDTH + desc_column)
+            .max(MIN_DESC_WIDTH);
+        let hanging_indent = format!("\n\t{}", " ".repeat(desc_column));
+
+        let mut about = self.desc.to_string();
+        if let Some(long_about) = self.long_about.as_ref().filter(|_| long) {
+            about = format!(
+                "{}\n\n{}",
+                about,
+                wrap_desc(long_about, HELP_WIDTH).join("\n")
+            );
+        }
+
+        // Each subcommand is listed with the first line of its description.
+        let mut command_data: Vec<(&str, String)> = self
+            .subcommands
+            .iter()
+            .map(|s| {
+                let summary = s.desc.lines().next().unwrap_or_default();
+                (s.name().unwrap_or_default(), summary.to_string())
+            })
+            .collect();
+        if self.has_help_subcommand() {
+            command_data.push((HELP_SUBCOMMAND, strings.help_command()));
+        }
+        let longest_command = command_data
+            .iter()
+            .map(|(name, _)| name.width())
+            .max()
+            .unwrap_or(0);
+        let mut commands = String::new();
+        for (name, summary) in &command_data {
+            commands = format!(
+                "{}\t{}  {}\n",
+                commands,
+                pad_str(name, longest_command),
+                summary
+            );
+        }
+        if !commands.is_empty() {
+            commands = format!("\n{}\n{}", strings.commands(), commands);
+        }
+        let external_commands = match self.external_subcommands {
+            true => self.external_subcommands(),
+            false => vec![],
+        };
+        if !external_commands.is_empty() {
+            commands = format!(
+                "{}\n{}\n\t{}\n",
+                commands,
+                strings.external_commands(),
+                external_commands.join("\n\t")
+            );
+        }
+
+        let mut footer = String::new();
+        if let Some(after_help) = self.after_help.as_ref().filter(|_| long) {
+            footer = format!("{}\n{}\n", footer, after_help);
+        }
+        if let Some(author) = &self.author {
+            footer = format!("{}\n{}\n", footer, strings.author(author));
+        }
+
+        let no_args = strings.no_args();
         format!(
-            "\n{}\n\n{}\n",
-            self.desc,
+            "\n{}\n\n{}\n{}{}",
+            about,
             flag_data
                 .iter()
                 .fold(String::new(), |acc, (name, req_or_def, desc)| format!(
-                    "{}\n\t--{} {}: {}",
+                    "{}\n\t{}{} {}: {}",
                     acc,
-                    pad_str(name.to_string(), longest_name),
-                    pad_str(req_or_def.to_string(), longest_ref_or_def),
-                    desc
+                    self.settings.prefix,
+                    pad_str(name, longest_name),
+                    pad_str(req_or_def, longest_ref_or_def),
+                    wrap_desc(desc, desc_width).join(&hanging_indent)
                 ))
                 .strip_prefix("\n")
-                .unwrap_or("(no args)")
+                .unwrap_or(&no_args),
+            commands,
+            footer
         )
     }
 }
 
-fn pad_str(str: String, n: usize) -> String {
-    (0..n).map(|i| str.chars().nth(i).unwrap_or(' ')).collect()
+#[cfg(any(unix, windows))]
+fn is_too_tall(text: &str) -> bool {
+    use terminal_size::{terminal_size, Height};
+
+    match terminal_size() {
+        Some((_, Height(height))) => text.lines().count() >= height as usize,
+        None => false,
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn is_too_tall(_text: &str) -> bool {
+    false
+}
+
+/// Writes the text to the pager's stdin, waiting for the user to close it.
+fn page(text: &str) -> io::Result<()> {
+    let pager = env::var("PAGER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+    let pager = tokenize(&pager);
+    let (program, args) = pager
+        .split_first()
+        .ok_or_else(|| io::Error::other("pager is empty"))?;
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user may quit the pager before reading everything, which is not an error.
+        let _ = writeln!(stdin, "{}", text);
+    }
+    child.wait().map(|_| ())
+}
+
+/// Splits a description into lines no wider than `width`, keeping any line breaks it already has.
+/// Words longer than `width` are kept whole on their own line.
+fn wrap_desc(desc: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for desc_line in desc.lines() {
+        let mut line = String::new();
+        for word in desc_line.split_whitespace() {
+            if !line.is_empty() && line.width() + 1 + word.width() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// Pads the string with spaces until it takes up `width` columns on the terminal, going by its
+/// display width so wide characters, such as CJK, and combining characters line up.
+fn pad_str(str: &str, width: usize) -> String {
+    format!("{}{}", str, " ".repeat(width.saturating_sub(str.width())))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::strings::Strings;
 
     #[test]
     fn generate_help_text_happy_path() {
         let program = Program::new()
             .with_description("A bunny observing tool!")
-            .with_required_flag::<&str>("rabbit-name", "Name of the rabbit to observe")
+            .with_required_flag::<String>("rabbit-name", "Name of the rabbit to observe")
             .unwrap()
-            .with_required_flag::<&str>("stat", "Rabbit statistic to evaluate")
+            .with_required_flag::<String>("stat", "Rabbit statistic to evaluate")
             .unwrap()
             .with_optional_flag::<bool>("closing-pats", true, "Pat the rabbit when finished?")
             .unwrap();
@@ -77,6 +324,420 @@ A bunny observing tool!
         );
     }
 
+    #[test]
+    fn generate_help_text_with_metadata() {
+        let program = Program::new()
+            .with_description("A bunny observing tool!")
+            .with_long_about(
+                "Rabbits are observed from a safe distance, so they are never disturbed. \
+                Statistics are kept for every rabbit which has been observed before.",
+            )
+            .with_after_help("Report bugs at https://example.com/bunnies")
+            .with_author("Ollie <ollie@example.com>")
+            .with_required_flag::<String>("rabbit-name", "Name of the rabbit to observe")
+            .unwrap();
+
+        assert_eq!(
+            r#"
+A bunny observing tool!
+
+Rabbits are observed from a safe distance, so they are never disturbed. Statistics are kept for
+every rabbit which has been observed before.
+
+	--rabbit-name (required): Name of the rabbit to observe
+
+Report bugs at https://example.com/bunnies
+
+Author: Ollie <ollie@example.com>
+"#,
+            program.generate_help_text()
+        );
+    }
+
+    #[test]
+    fn generate_short_help_text_leaves_out_long_text() {
+        let program = Program::new()
+            .with_description("A bunny observing tool!")
+            .with_long_about("Rabbits are observed from a safe distance.")
+            .with_after_help("Report bugs at https://example.com/bunnies")
+            .with_required_flag::<String>("rabbit-name", "Name of the rabbit to observe")
+            .unwrap();
+
+        assert_eq!(
+            r#"
+A bunny observing tool!
+
+	--rabbit-name (required): Name of the rabbit to observe
+"#,
+            program.generate_short_help_text()
+        );
+    }
+
+    #[test]
+    fn generate_help_text_with_custom_prefix() {
+        let program = Program::new()
+            .with_description("A feature toggler")
+            .with_prefix("+")
+            .with_optional_flag::<bool>("colors", false, "Use colors")
+            .unwrap();
+
+        assert_eq!(
+            r#"
+A feature toggler
+
+	+colors (default: false): Use colors
+"#,
+            program.generate_help_text()
+        );
+    }
+
+    #[test]
+    fn generate_help_text_with_raw_flag() {
+        let program = Program::new()
+            .with_description("A command runner")
+            .with_optional_flag::<bool>("dry-run", false, "Only print the command")
+            .unwrap()
+            .with_raw_flag("cmd", "Command to run")
+            .unwrap();
+
+        assert_eq!(
+            r#"
+A command runner
+
+	--dry-run (default: false): Only print the command
+	--cmd     (remaining args): Command to run
+"#,
+            program.generate_help_text()
+        );
+    }
+
+    #[test]
+    fn generate_help_text_with_hidden_default() {
+        let program = Program::new()
+            .with_description("An API client")
+            .with_optional_flag::<String>("token", "abc123".to_string(), "API token")
+            .unwrap()
+            .with_default_hidden("token")
+            .unwrap()
+            .with_optional_flag::<u16>("retries", 3, "Times to retry")
+            .unwrap();
+
+        assert_eq!(
+            r#"
+An API client
+
+	--token   (optional)  : API token
+	--retries (default: 3): Times to retry
+"#,
+            program.generate_help_text()
+        );
+    }
+
+    #[test]
+    fn generate_help_text_with_default_display() {
+        let program = Program::new()
+            .with_description("An editor")
+            .with_optional_flag::<String>("config", "/home/ollie/.config".to_string(), "Config")
+            .unwrap()
+            .with_default_display("config", "$HOME/.config")
+            .unwrap();
+
+        assert_eq!(
+            r#"
+An editor
+
+	--config (default: $HOME/.config): Config
+"#,
+            program.generate_help_text()
+        );
+        assert_eq!(
+            "/home/ollie/.config",
+            program
+                .parse_from_str_arr(&[])
+                .unwrap()
+                .get::<String>("config")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn generate_help_text_with_env_var() {
+        let program = Program::new()
+            .with_description("An HTTP server")
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_env_var("port", "MYAPP_PORT")
+            .unwrap();
+
+        assert_eq!(
+            r#"
+An HTTP server
+
+	--port (required): Port number [env: MYAPP_PORT]
+"#,
+            program.generate_help_text()
+        );
+    }
+
+    #[test]
+    fn generate_help_text_with_examples() {
+        let program = Program::new()
+            .with_description("A log viewer")
+            .with_optional_flag::<String>("filter", "".to_string(), "Only show matching logs")
+            .unwrap()
+            .with_example("filter", "level>=warn")
+            .unwrap()
+            .with_example("filter", "target=http")
+            .unwrap();
+
+        assert_eq!(
+            r#"
+A log viewer
+
+	--filter (default: ): Only show matching logs
+	                      [example: --filter 'level>=warn']
+	                      [example: --filter target=http]
+"#,
+            program.generate_help_text()
+        );
+        assert_eq!(
+            r#"
+A log viewer
+
+	--filter (default: ): Only show matching logs
+"#,
+            program.generate_short_help_text()
+        );
+    }
+
+    #[test]
+    fn generate_help_text_with_byte_size_units() {
+        let program = Program::new()
+            .with_description("A cache")
+            .with_optional_flag("size", ByteSize(64 << 20), "Size of the cache")
+            .unwrap();
+
+        assert_eq!(
+            r#"
+A cache
+
+	--size (default: 64MiB): Size of the cache [sizes: 1KB = 1000 bytes, 1KiB = 1024 bytes]
+"#,
+            program.generate_help_text()
+        );
+    }
+
+    #[test]
+    fn generate_help_text_with_possible_values() {
+        let program = Program::new()
+            .with_description("A fast tool")
+            .with_optional_flag::<String>("speed", "auto".to_string(), "How fast to go")
+            .unwrap()
+            .with_possible_values("speed", ["fast", "slow", "auto"])
+            .unwrap();
+
+        assert_eq!(
+            r#"
+A fast tool
+
+	--speed (default: auto): How fast to go [possible values: fast, slow, auto]
+"#,
+            program.generate_help_text()
+        );
+    }
+
+    #[test]
+    fn generate_help_text_with_multi_line_and_long_descriptions() {
+        let program = Program::new()
+            .with_description("A formatter")
+            .with_optional_flag::<String>(
+                "style",
+                "default".to_string(),
+                "Style to format with.\nEither default or compact.",
+            )
+            .unwrap()
+            .with_optional_flag::<bool>(
+                "check",
+                false,
+                "Only check whether files are already formatted, without writing any changes to \
+                them, and exit with an error if any of them are not",
+            )
+            .unwrap();
+
+        assert_eq!(
+            r#"
+A formatter
+
+	--style (default: default): Style to format with.
+	                            Either default or compact.
+	--check (default: false)  : Only check whether files are already formatted, without writing
+	                            any changes to them, and exit with an error if any of them are
+	                            not
+"#,
+            program.generate_help_text()
+        );
+    }
+
+    #[test]
+    fn generate_help_text_aligns_wide_and_combining_characters() {
+        let program = Program::new()
+            .with_description("Unicode")
+            .with_optional_flag::<String>("名前", "太郎".to_string(), "Your name")
+            .unwrap()
+            .with_optional_flag::<String>("cafe\u{301}", "noir".to_string(), "Coffee")
+            .unwrap()
+            .with_optional_flag::<String>("long-name", "x".to_string(), "Long")
+            .unwrap();
+
+        assert_eq!(
+            r#"
+Unicode
+
+	--名前      (default: 太郎): Your name
+	--café      (default: noir): Coffee
+	--long-name (default: x)   : Long
+"#,
+            program.generate_help_text()
+        );
+    }
+
+    #[test]
+    fn pad_str_never_truncates() {
+        assert_eq!("flag", pad_str("flag", 2));
+        assert_eq!("名前 ", pad_str("名前", 5));
+    }
+
+    #[test]
+    fn wrap_desc_keeps_long_words_whole() {
+        assert_eq!(
+            vec!["a", "supercalifragilistic", "b c"],
+            wrap_desc("a supercalifragilistic b c", 5)
+        );
+        assert_eq!(vec![""], wrap_desc("", 5));
+    }
+
+    #[test]
+    fn generate_help_text_with_localised_strings() {
+        struct French;
+
+        impl Strings for French {
+            fn required(&self) -> String {
+                "(obligatoire)".to_string()
+            }
+
+            fn default_value(&self, default: &str) -> String {
+                format!("(défaut : {})", default)
+            }
+        }
+
+        let program = Program::new()
+            .with_strings(French)
+            .with_description("Un serveur HTTP")
+            .with_required_flag::<u16>("port", "Numéro de port")
+            .unwrap()
+            .with_optional_flag::<bool>("use-tls", false, "TLS SVP ?")
+            .unwrap();
+
+        assert_eq!(
+            r#"
+Un serveur HTTP
+
+	--port    (obligatoire)   : Numéro de port
+	--use-tls (défaut : false): TLS SVP ?
+"#,
+            program.generate_help_text()
+        );
+    }
+
+    #[test]
+    fn usage_shows_optional_flags_in_brackets() {
+        let program = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<bool>("use-tls", false, "TLS PLS?")
+            .unwrap()
+            .with_optional_flag::<String>("host", "localhost".to_string(), "Host to bind")
+            .unwrap()
+            .with_raw_flag("cmd", "Command to run on startup")
+            .unwrap();
+
+        assert_eq!(
+            "Usage: --port <u16> [--use-tls] [--host <String>] [--cmd <args>...]",
+            program.usage()
+        );
+        assert_eq!("Usage:", Program::new().usage());
+    }
+
+    #[test]
+    fn usage_starts_with_the_name_of_the_program() {
+        let program = Program::new()
+            .with_name("mytool")
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap();
+
+        assert_eq!("Usage: mytool --port <u16>", program.usage());
+    }
+
+    #[test]
+    fn usage_can_be_replaced() {
+        let program = Program::new()
+            .with_optional_flag::<bool>("recursive", false, "Copy directories")
+            .unwrap()
+            .with_usage("mycp [OPTIONS] <SRC>... <DST>");
+
+        assert_eq!("Usage: mycp [OPTIONS] <SRC>... <DST>", program.usage());
+    }
+
+    #[test]
+    fn generate_help_text_with_secret_flag() {
+        let program = Program::new()
+            .with_description("A database client")
+            .with_required_flag::<String>("user", "User to connect as")
+            .unwrap()
+            .with_secret_flag("password", "Password of the user")
+            .unwrap();
+
+        assert_eq!(
+            r#"
+A database client
+
+	--user     (required): User to connect as
+	--password (secret)  : Password of the user
+"#,
+            program.generate_help_text()
+        );
+    }
+
+    #[test]
+    fn generate_help_text_with_subcommands() {
+        let program = Program::new()
+            .with_description("A bunny observing tool!")
+            .with_optional_flag::<bool>("quiet", false, "Log nothing")
+            .unwrap()
+            .with_subcommand(
+                "observe",
+                Program::new().with_description("Observe a rabbit\nFrom a safe distance."),
+            )
+            .unwrap()
+            .with_subcommand("feed", Program::new().with_description("Feed a rabbit"))
+            .unwrap();
+
+        assert_eq!(
+            r#"
+A bunny observing tool!
+
+	--quiet (default: false): Log nothing
+
+Commands:
+	observe  Observe a rabbit
+	feed     Feed a rabbit
+	help     Show the help text of a command
+"#,
+            program.generate_help_text()
+        );
+        assert_eq!("Usage: [--quiet] [command]", program.usage());
+    }
+
     #[test]
     fn generate_help_text_empty_program() {
         let program = Program::new().with_description("A boring tool that does nothing");
@@ -0,0 +1,385 @@
+use std::fmt::{Display, Formatter, Write};
+
+/// A minimal JSON document, just enough to describe a `Program` without pulling in a dependency.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    /// The number as it was written, so integers too large for an `f64`, such as a `u64`
+    /// default, are kept exactly.
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn string(s: impl Into<String>) -> Json {
+        Json::String(s.into())
+    }
+
+    pub fn object<K: Into<String>>(entries: impl IntoIterator<Item = (K, Json)>) -> Json {
+        Json::Object(entries.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    /// Parse a JSON document, giving back a description of what went wrong if it is not valid.
+    pub fn parse(s: &str) -> Result<Json, String> {
+        let mut reader = Reader {
+            chars: s.chars().collect(),
+            pos: 0,
+            depth: 0,
+        };
+        let json = reader.read_value()?;
+        reader.skip_whitespace();
+
+        match reader.peek() {
+            Some(c) => Err(reader.error(&format!("unexpected '{}' after document", c))),
+            None => Ok(json),
+        }
+    }
+
+    /// Look up a key of an object, giving back `None` for anything other than an object.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn write_pretty(&self, f: &mut Formatter<'_>, indent: usize) -> std::fmt::Result {
+        let padding = "  ".repeat(indent + 1);
+        let closing_padding = "  ".repeat(indent);
+
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => write_escaped(f, s),
+            Json::Array(items) if items.is_empty() => write!(f, "[]"),
+            Json::Array(items) => {
+                writeln!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    write!(f, "{}", padding)?;
+                    item.write_pretty(f, indent + 1)?;
+                    writeln!(f, "{}", if i + 1 < items.len() { "," } else { "" })?;
+                }
+                write!(f, "{}]", closing_padding)
+            }
+            Json::Object(entries) if entries.is_empty() => write!(f, "{{}}"),
+            Json::Object(entries) => {
+                writeln!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    write!(f, "{}", padding)?;
+                    write_escaped(f, key)?;
+                    write!(f, ": ")?;
+                    value.write_pretty(f, indent + 1)?;
+                    writeln!(f, "{}", if i + 1 < entries.len() { "," } else { "" })?;
+                }
+                write!(f, "{}}}", closing_padding)
+            }
+        }
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_pretty(f, 0)
+    }
+}
+
+/// How deeply arrays and objects can be nested, so a hostile document can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+struct Reader {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Reader {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    /// Moves past the next character if it is `c`, leaving the position on it otherwise so errors
+    /// point at the offending character.
+    fn consume(&mut self, c: char) -> bool {
+        let is_next = self.peek() == Some(c);
+        if is_next {
+            self.pos += 1;
+        }
+        is_next
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at position {}", message, self.pos)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.consume(expected) {
+            return Ok(());
+        }
+
+        match self.peek() {
+            Some(c) => Err(self.error(&format!("expected '{}' but found '{}'", expected, c))),
+            None => Err(self.error(&format!("expected '{}' but found the end", expected))),
+        }
+    }
+
+    fn expect_word(&mut self, word: &str, json: Json) -> Result<Json, String> {
+        for expected in word.chars() {
+            if self.next() != Some(expected) {
+                return Err(self.error(&format!("expected {}", word)));
+            }
+        }
+        Ok(json)
+    }
+
+    fn read_value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('n') => self.expect_word("null", Json::Null),
+            Some('t') => self.expect_word("true", Json::Bool(true)),
+            Some('f') => self.expect_word("false", Json::Bool(false)),
+            Some('"') => self.read_string().map(Json::String),
+            Some(c @ ('[' | '{')) => {
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("nested too deeply"));
+                }
+                self.depth += 1;
+                let json = match c {
+                    '[' => self.read_array(),
+                    _ => self.read_object(),
+                };
+                self.depth -= 1;
+                json
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => self.read_number(),
+            Some(c) => Err(self.error(&format!("unexpected '{}'", c))),
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    fn read_number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.pos += 1;
+        }
+
+        let number: String = self.chars[start..self.pos].iter().collect();
+        match number.parse::<f64>() {
+            Ok(_) => Ok(Json::Number(number)),
+            Err(_) => Err(self.error(&format!("invalid number {}", number))),
+        }
+    }
+
+    fn read_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.next() {
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('u') => s.push(self.read_unicode_escape()?),
+                    Some(c) => s.push(c),
+                    None => return Err(self.error("unterminated string")),
+                },
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    /// Reads the hex digits of a `\u` escape, joining a high surrogate with the low surrogate
+    /// escape which must follow it, such as `\ud83d\ude00`.
+    fn read_unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.read_code_unit()?;
+        let code_point = match high {
+            0xD800..=0xDBFF => {
+                if !(self.consume('\\') && self.consume('u')) {
+                    return Err(self.error("expected a low surrogate escape"));
+                }
+                match self.read_code_unit()? {
+                    low @ 0xDC00..=0xDFFF => 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00),
+                    low => return Err(self.error(&format!("invalid low surrogate \\u{:04x}", low))),
+                }
+            }
+            code_point => code_point,
+        };
+        char::from_u32(code_point)
+            .ok_or_else(|| self.error(&format!("invalid escape \\u{:04x}", code_point)))
+    }
+
+    fn read_code_unit(&mut self) -> Result<u32, String> {
+        let hex: String = (0..4).filter_map(|_| self.next()).collect();
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .filter(|_| hex.len() == 4)
+            .ok_or_else(|| self.error(&format!("invalid escape \\u{}", hex)))
+    }
+
+    fn read_array(&mut self) -> Result<Json, String> {
+        self.expect('[')?;
+        let mut items = vec![];
+        self.skip_whitespace();
+        if self.consume(']') {
+            return Ok(Json::Array(items));
+        }
+
+        loop {
+            items.push(self.read_value()?);
+            self.skip_whitespace();
+            if self.consume(']') {
+                return Ok(Json::Array(items));
+            }
+            if !self.consume(',') {
+                return Err(self.error("expected ',' or ']'"));
+            }
+        }
+    }
+
+    fn read_object(&mut self) -> Result<Json, String> {
+        self.expect('{')?;
+        let mut entries = vec![];
+        self.skip_whitespace();
+        if self.consume('}') {
+            return Ok(Json::Object(entries));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.read_string()?;
+            self.expect(':')?;
+            entries.push((key, self.read_value()?));
+            self.skip_whitespace();
+            if self.consume('}') {
+                return Ok(Json::Object(entries));
+            }
+            if !self.consume(',') {
+                return Err(self.error("expected ',' or '}'"));
+            }
+        }
+    }
+}
+
+fn write_escaped(f: &mut Formatter<'_>, s: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_write_nested_json_with_indentation() {
+        let json = Json::object([
+            ("name", Json::string("port")),
+            ("required", Json::Bool(true)),
+            ("default", Json::Null),
+            (
+                "values",
+                Json::Array(vec![Json::string("a"), Json::Bool(false)]),
+            ),
+            ("empty", Json::Array(vec![])),
+        ]);
+
+        assert_eq!(
+            r#"{
+  "name": "port",
+  "required": true,
+  "default": null,
+  "values": [
+    "a",
+    false
+  ],
+  "empty": []
+}"#,
+            json.to_string()
+        );
+    }
+
+    #[test]
+    fn should_read_what_was_written() {
+        let json = Json::object([
+            ("name", Json::string("say \"hi\"\n")),
+            ("count", Json::Number("-12.5".to_string())),
+            ("flags", Json::Array(vec![Json::Bool(true), Json::Null])),
+            ("empty", Json::object::<String>([])),
+        ]);
+
+        assert_eq!(Ok(json.clone()), Json::parse(&json.to_string()));
+    }
+
+    #[test]
+    fn should_describe_where_invalid_json_is() {
+        assert_eq!(
+            Err("expected ',' or '}' at position 11".to_string()),
+            Json::parse(r#"{"a": true "b": false}"#)
+        );
+        assert_eq!(
+            Err("unexpected 'x' after document at position 5".to_string()),
+            Json::parse("null x")
+        );
+    }
+
+    #[test]
+    fn should_join_surrogate_pairs() {
+        assert_eq!(
+            Ok(Json::string("\u{1f600} é")),
+            Json::parse(r#""\ud83d\ude00 \u00e9""#)
+        );
+        assert!(Json::parse(r#""\ud83d""#).is_err());
+        assert!(Json::parse(r#""\ude00""#).is_err());
+    }
+
+    #[test]
+    fn should_not_read_documents_nested_too_deeply() {
+        let nested = "[".repeat(100_000);
+
+        assert_eq!(
+            Err("nested too deeply at position 128".to_string()),
+            Json::parse(&nested)
+        );
+        assert!(Json::parse(&format!("{}{}", "[".repeat(64), "]".repeat(64))).is_ok());
+    }
+
+    #[test]
+    fn should_escape_strings() {
+        let json = Json::string("say \"hi\"\n\tto\\me\u{1}");
+
+        assert_eq!(r#""say \"hi\"\n\tto\\me\u0001""#, json.to_string());
+    }
+}
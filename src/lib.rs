@@ -32,10 +32,66 @@
 //! Config::new_from_args().expect("Invalid program args");
 //! ```
 
+pub mod byte_size;
+pub mod completions;
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub mod datetime;
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "miette")]
+mod diagnostic;
+pub mod diff;
+pub mod duration;
 pub mod error;
+pub mod exit;
+mod external;
 pub mod flag;
+mod flag_set;
+mod from_program;
 mod help;
+mod json;
+#[cfg(feature = "log")]
+mod log;
+mod macros;
+mod man;
+pub mod matches;
+mod net;
+mod number;
 pub mod parser;
+pub mod path;
 pub mod program;
+mod prompt;
+pub mod range;
+#[cfg(feature = "semver")]
+mod semver;
+mod session;
+pub mod settings;
+pub mod source;
+mod spec;
+pub mod strings;
+pub mod style;
+mod subcommand;
+pub mod testing;
+mod tokenizer;
+#[cfg(feature = "tracing")]
+mod tracing;
+#[cfg(feature = "url")]
+mod url;
+mod validate;
+pub mod value_enum;
+pub mod verbosity;
 
+#[cfg(feature = "miette")]
+pub use diagnostic::ArgsReport;
+pub use flag_set::FlagSet;
+pub use from_program::{FromParsedProgram, FromProgram, Subcommands};
+pub use matches::Matches;
 pub use program::Program;
+pub use session::Session;
+pub use tokenizer::tokenize;
+
+#[cfg(feature = "derive")]
+pub use commandrs_derive::{FromProgram, Subcommands, ValueEnum};
+
+#[doc(hidden)]
+pub use macros::__private;
@@ -0,0 +1,72 @@
+use std::env;
+
+use ::log::{LevelFilter, SetLoggerError};
+
+use crate::verbosity::LogLevel;
+use crate::Program;
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> LevelFilter {
+        match level {
+            LogLevel::Off => LevelFilter::Off,
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+impl Program<'_> {
+    /// Set up `env_logger` as the logger, at the level given with the flags added by
+    /// `Program::with_verbosity`. `RUST_LOG` is still used for per-module filters, and for the
+    /// level when none of the verbosity flags were given.
+    ///
+    /// This is only available with the `log` feature, and fails if a logger has already been set.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_verbosity()
+    ///     .unwrap()
+    ///     .parse_from_str_arr(&["-v"])
+    ///     .unwrap();
+    ///
+    /// program.init_logger().unwrap();
+    /// assert_eq!(log::LevelFilter::Debug, log::max_level());
+    /// ```
+    pub fn init_logger(&self) -> Result<(), SetLoggerError> {
+        let mut builder = env_logger::Builder::from_default_env();
+        if self.is_verbosity_given() || env::var_os("RUST_LOG").is_none() {
+            builder.filter_level(self.log_level().into());
+        }
+        builder.try_init()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_every_log_level_into_a_level_filter() {
+        assert_eq!(LevelFilter::Off, LevelFilter::from(LogLevel::Off));
+        assert_eq!(LevelFilter::Warn, LevelFilter::from(LogLevel::Warn));
+        assert_eq!(LevelFilter::Trace, LevelFilter::from(LogLevel::Trace));
+    }
+
+    #[test]
+    fn should_only_set_the_logger_once() {
+        let program = Program::new()
+            .with_verbosity()
+            .unwrap()
+            .parse_from_str_arr(&["--log-level", "warn"])
+            .unwrap();
+
+        assert!(program.init_logger().is_ok());
+        assert_eq!(LevelFilter::Warn, ::log::max_level());
+        assert!(program.init_logger().is_err());
+    }
+}
@@ -0,0 +1,207 @@
+/// Build a `Program` declaratively, without chaining each builder call with `?`. This gives back
+/// a `Result<Program, ProgramError>`, just like the builder.
+///
+/// Each flag is either `required`, or has a default after `=`, followed by its description. The
+/// names of flags are their identifier in kebab-case, so `use_tls` is given as `--use-tls`.
+///
+/// ```
+/// use commandrs::program;
+///
+/// let program = program! {
+///     desc: "An HTTP server",
+///     flags: {
+///         port: u16 required "Port number",
+///         use_tls: bool = false "TLS PLS?",
+///         host: String = "localhost" "Host to bind",
+///     }
+/// }
+/// .unwrap()
+/// .parse_from_str_arr(&["--port", "8080", "--use-tls"])
+/// .unwrap();
+///
+/// assert_eq!(8080, program.get::<u16>("port").unwrap());
+/// assert!(program.get::<bool>("use-tls").unwrap());
+/// assert_eq!("localhost", program.get::<String>("host").unwrap());
+/// ```
+///
+/// Types must be a single identifier, such as `u16` or `String`, so a `type` alias is needed for
+/// anything else. Defaults must also be a single token, so something like `-1` has to be wrapped
+/// in parentheses as `(-1)`.
+#[macro_export]
+macro_rules! program {
+    (desc: $desc:expr, flags: { $($flags:tt)* } $(,)?) => {
+        $crate::program!(
+            @flags ::std::result::Result::Ok::<_, $crate::error::ProgramError>(
+                $crate::Program::new().with_description($desc)
+            );
+            $($flags)*
+        )
+    };
+    (flags: { $($flags:tt)* } $(,)?) => {
+        $crate::program!(
+            @flags ::std::result::Result::Ok::<_, $crate::error::ProgramError>(
+                $crate::Program::new()
+            );
+            $($flags)*
+        )
+    };
+    (@flags $program:expr; $(,)?) => {
+        $program
+    };
+    (@flags $program:expr; $name:ident : $ty:ident required $desc:literal $(, $($rest:tt)*)?) => {
+        $crate::program!(
+            @flags $program.and_then(|p| {
+                p.with_required_flag::<$ty>(::std::stringify!($name).replace('_', "-"), $desc)
+            });
+            $($($rest)*)?
+        )
+    };
+    (@flags $program:expr; $name:ident : $ty:ident = $default:tt $desc:literal $(, $($rest:tt)*)?) => {
+        $crate::program!(
+            @flags $program.and_then(|p| {
+                p.with_optional_flag::<$ty>(
+                    ::std::stringify!($name).replace('_', "-"),
+                    $crate::__private::IntoDefault::into_default($default),
+                    $desc,
+                )
+            });
+            $($($rest)*)?
+        )
+    };
+}
+
+/// Assert a `Program` parses the given arguments, and that each of the listed flags has the
+/// expected value, giving back the parsed `Program`. Like `program!`, the names of flags are their
+/// identifier in kebab-case, and types must be a single identifier.
+///
+/// When the arguments can't be parsed, the assertion fails with the error as users would see it.
+///
+/// ```
+/// use commandrs::{assert_parses, program};
+///
+/// let program = program! {
+///     flags: {
+///         port: u16 required "Port number",
+///         use_tls: bool = false "TLS PLS?",
+///     }
+/// }
+/// .unwrap();
+///
+/// assert_parses!(program, ["--port", "80"], port: u16 == 80, use_tls: bool == false);
+/// ```
+#[macro_export]
+macro_rules! assert_parses {
+    ($program:expr, [$($arg:expr),* $(,)?] $(, $name:ident : $ty:ident == $expected:expr)* $(,)?) => {{
+        let parsed = $crate::testing::parse(&$program, [$($arg),*]);
+        $(
+            let name = ::std::stringify!($name).replace('_', "-");
+            match parsed.get::<$ty>(&name) {
+                ::std::result::Result::Ok(value) => ::std::assert_eq!(
+                    value,
+                    $expected,
+                    "flag {} did not have the expected value",
+                    name
+                ),
+                ::std::result::Result::Err(err) => {
+                    ::std::panic!("could not get flag {}: {}", name, err)
+                }
+            }
+        )*
+        parsed
+    }};
+}
+
+/// Used by the macros to convert defaults into the type of their flag, which lets a `String` flag
+/// have a `&str` default while integer literals are still inferred as the type of their flag.
+#[doc(hidden)]
+pub mod __private {
+    pub trait IntoDefault<T> {
+        fn into_default(self) -> T;
+    }
+
+    impl<T> IntoDefault<T> for T {
+        fn into_default(self) -> T {
+            self
+        }
+    }
+
+    impl IntoDefault<String> for &str {
+        fn into_default(self) -> String {
+            self.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ProgramError;
+    use crate::Program;
+
+    #[test]
+    fn should_build_the_same_program_as_the_builder() {
+        let expected = Program::new()
+            .with_description("An HTTP server")
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<bool>("use-tls", false, "TLS PLS?")
+            .unwrap()
+            .with_optional_flag::<i32>("offset", -1, "Offset")
+            .unwrap();
+
+        let program = program! {
+            desc: "An HTTP server",
+            flags: {
+                port: u16 required "Port number",
+                use_tls: bool = false "TLS PLS?",
+                offset: i32 = (-1) "Offset"
+            }
+        };
+
+        assert_eq!(Ok(expected), program);
+    }
+
+    #[test]
+    fn should_build_a_program_without_a_description_or_flags() {
+        assert_eq!(Ok(Program::new()), program! { flags: {} });
+    }
+
+    #[test]
+    fn should_assert_the_values_of_parsed_flags() {
+        let program = program! {
+            flags: {
+                port: u16 required "Port number",
+                use_tls: bool = false "TLS PLS?",
+            }
+        }
+        .unwrap();
+
+        let parsed = assert_parses!(program, ["--port", "80", "--use-tls"], port: u16 == 80, use_tls: bool == true);
+
+        assert!(parsed.is_present("use-tls"));
+    }
+
+    #[test]
+    #[should_panic(expected = "flag port did not have the expected value")]
+    fn should_fail_the_assertion_for_unexpected_values() {
+        let program = program! { flags: { port: u16 required "Port number" } }.unwrap();
+
+        assert_parses!(program, ["--port", "80"], port: u16 == 443);
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_duplicate_flags() {
+        let program = program! {
+            flags: {
+                port: u16 required "Port number",
+                port: u16 = 8080 "Port number",
+            }
+        };
+
+        assert_eq!(
+            Err(ProgramError::FlagAlreadyExistsWithName {
+                name: "port".to_string()
+            }),
+            program
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Man pages, written in roff from the descriptions, flags and subcommands of a `Program`, so they
+//! can be installed along with a tool.
+
+use crate::error::ProgramError;
+use crate::parser::BOOL_TYPE_ID;
+use crate::spec::short_type_name;
+use crate::Program;
+
+/// The hidden flag added by `Program::with_man_flag`.
+pub(crate) const MAN_FLAG: &str = "generate-man";
+
+impl<'a> Program<'a> {
+    /// Accept a hidden `--generate-man` flag, which writes the man page to stdout instead of
+    /// parsing, failing with `ProgramError::ManPageGenerated`.
+    ///
+    /// Like the help flag, `Program::parse_or_exit` and `Program::run` exit successfully after
+    /// writing it, so packaging scripts can run `mytool --generate-man > mytool.1`.
+    pub fn with_man_flag(mut self) -> Program<'a> {
+        self.man_flag = true;
+        self
+    }
+
+    /// The man page of the `Program` in roff, for section 1, with its description, flags and
+    /// subcommands, and its author when it has one.
+    pub fn man_page(&self) -> String {
+        let name = self.full_name().unwrap_or_default().to_string();
+        let title = name.replace(' ', "-").to_uppercase();
+        let footer = match &self.version {
+            Some(version) => format!("{} {}", name, version),
+            None => name.clone(),
+        };
+
+        let mut page = format!(
+            ".TH {} 1 \"\" \"{}\"\n.SH NAME\n{}",
+            roff_escape(&title),
+            roff_escape(&footer),
+            roff_escape(&name)
+        );
+        if let Some(summary) = self.desc.lines().next().filter(|s| !s.trim().is_empty()) {
+            page += &format!(" \\- {}", roff_escape(summary.trim()));
+        }
+
+        page += &format!("\n.SH SYNOPSIS\n\\fB{}\\fR", roff_escape(&name));
+        for flag in &self.flags {
+            let mut arg = format!("\\fB{}\\fR", roff_escape(&self.flag_arg(&flag.name)));
+            if flag.is_raw {
+                arg += " \\fIargs\\fR...";
+            } else if flag.type_id != *BOOL_TYPE_ID {
+                arg += &format!(" \\fI{}\\fR", roff_escape(&short_type_name(flag.type_name)));
+            }
+            page += &match flag.is_required {
+                true => format!(" {}", arg),
+                false => format!(" [{}]", arg),
+            };
+        }
+        if !self.subcommands.is_empty() {
+            page += " [\\fIcommand\\fR]";
+        }
+
+        page += "\n.SH DESCRIPTION\n";
+        page += &roff_paragraphs(&self.desc);
+        if let Some(long_about) = &self.long_about {
+            page += &format!("\n.PP\n{}", roff_paragraphs(long_about));
+        }
+
+        if !self.flags.is_empty() {
+            page += "\n.SH OPTIONS";
+        }
+        for flag in &self.flags {
+            let mut arg = format!("\\fB{}\\fR", roff_escape(&self.flag_arg(&flag.name)));
+            if flag.type_id != *BOOL_TYPE_ID {
+                arg += &format!(" \\fI{}\\fR", roff_escape(&short_type_name(flag.type_name)));
+            }
+            page += &format!("\n.TP\n{}\n{}", arg, roff_paragraphs(&flag.desc));
+
+            let strings = &self.strings.0;
+            let mut notes = vec![];
+            if !flag.possible_values.is_empty() {
+                let values: Vec<&str> = flag.possible_values.iter().map(|v| v.as_ref()).collect();
+                notes.push(strings.possible_values(&values));
+            }
+            if let Some(env_var) = &flag.env_var {
+                notes.push(strings.env_var(env_var));
+            }
+            if !flag.is_required && !flag.is_raw && !flag.is_secret && !flag.hide_default {
+                let default = match &flag.default_display {
+                    Some(display) => Some(display.as_ref()),
+                    None => self
+                        .default_flag(&flag.name)
+                        .ok()
+                        .map(|d| d.str_value.as_str()),
+                };
+                notes.extend(default.map(|d| strings.default_value(d)));
+            }
+            for note in notes {
+                page += &format!("\n.br\n{}", roff_escape(&note));
+            }
+        }
+
+        if !self.subcommands.is_empty() {
+            page += "\n.SH COMMANDS";
+        }
+        for subcommand in &self.subcommands {
+            let summary = subcommand.desc.lines().next().unwrap_or_default();
+            page += &format!(
+                "\n.TP\n\\fB{}\\fR\n{}",
+                roff_escape(subcommand.name().unwrap_or_default()),
+                roff_escape(summary.trim())
+            );
+        }
+
+        if let Some(after_help) = &self.after_help {
+            page += &format!("\n.SH NOTES\n{}", roff_paragraphs(after_help));
+        }
+        if let Some(author) = &self.author {
+            page += &format!("\n.SH AUTHOR\n{}", roff_escape(author));
+        }
+        page + "\n"
+    }
+
+    /// Writes the man page for the man flag.
+    pub(crate) fn show_man_page(&self) -> ProgramError {
+        print!("{}", self.man_page());
+        ProgramError::ManPageGenerated
+    }
+
+    fn flag_arg(&self, name: &str) -> String {
+        format!("{}{}", self.settings.prefix, name)
+    }
+}
+
+/// Escapes text so roff shows it as it is, rather than taking any of it as a request.
+fn roff_escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    match escaped.starts_with(['.', '\'']) {
+        true => format!("\\&{}", escaped),
+        false => escaped,
+    }
+}
+
+/// Escapes each line of the text, separating paragraphs with blank lines in the text.
+fn roff_paragraphs(text: &str) -> String {
+    let lines: Vec<String> = text
+        .trim()
+        .lines()
+        .map(|line| match line.trim().is_empty() {
+            true => ".PP".to_string(),
+            false => roff_escape(line.trim()),
+        })
+        .collect();
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_write_the_man_page_in_roff() {
+        let program = Program::new()
+            .with_name("mytool")
+            .with_version("1.2.0")
+            .with_description("A bunny observing tool!")
+            .with_long_about("Rabbits are observed from a safe distance.\n\n.Never disturbed.")
+            .with_author("Ollie <ollie@example.com>")
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<bool>("use-tls", false, "TLS PLS?")
+            .unwrap()
+            .with_subcommand(
+                "observe",
+                Program::new().with_description("Observe a rabbit"),
+            )
+            .unwrap();
+
+        assert_eq!(
+            r#".TH MYTOOL 1 "" "mytool 1.2.0"
+.SH NAME
+mytool \- A bunny observing tool!
+.SH SYNOPSIS
+\fBmytool\fR \fB\-\-port\fR \fIu16\fR [\fB\-\-use\-tls\fR] [\fIcommand\fR]
+.SH DESCRIPTION
+A bunny observing tool!
+.PP
+Rabbits are observed from a safe distance.
+.PP
+\&.Never disturbed.
+.SH OPTIONS
+.TP
+\fB\-\-port\fR \fIu16\fR
+Port number
+.TP
+\fB\-\-use\-tls\fR
+TLS PLS?
+.br
+(default: false)
+.SH COMMANDS
+.TP
+\fBobserve\fR
+Observe a rabbit
+.SH AUTHOR
+Ollie <ollie@example.com>
+"#,
+            program.man_page()
+        );
+    }
+
+    #[test]
+    fn should_generate_the_man_page_instead_of_parsing() {
+        let err = Program::new()
+            .with_man_flag()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .parse_from(["--generate-man"])
+            .unwrap_err();
+
+        assert_eq!(ProgramError::ManPageGenerated, err);
+    }
+}
@@ -0,0 +1,204 @@
+//! The values parsed by `Program::parse_from`, kept apart from the `Program` which parsed them.
+
+use std::fmt::{Debug, Formatter};
+use std::str::FromStr;
+
+use crate::error::ProgramError;
+use crate::flag::ValueSource;
+use crate::parser::ParsedArgs;
+use crate::Program;
+
+/// The values parsed from one set of arguments by `Program::parse_from`. The `Program` is
+/// borrowed rather than copied, so one definition can cheaply parse many sets of arguments, such
+/// as in tests or servers handling many requests.
+///
+/// Only a subcommand which was given is copied, as it is parsed with the settings and global
+/// flags of the `Program` it was added to. For anything else which reads a parsed `Program`, such
+/// as `Program::get_path`, `Matches::into_program` gives back a copy of it with the values.
+///
+/// ```
+/// use commandrs::Program;
+///
+/// let program = Program::new()
+///     .with_required_flag::<u16>("port", "Port number")
+///     .unwrap();
+///
+/// let first = program.parse_from(["--port", "8080"]).unwrap();
+/// let second = program.parse_from(["--port", "443"]).unwrap();
+///
+/// assert_eq!(8080, first.get::<u16>("port").unwrap());
+/// assert_eq!(443, second.get::<u16>("port").unwrap());
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct Matches<'p, 'a> {
+    program: &'p Program<'a>,
+    parsed: ParsedArgs<'a>,
+    raw_args: Vec<String>,
+}
+
+impl<'p, 'a> Matches<'p, 'a> {
+    pub(crate) fn new(
+        program: &'p Program<'a>,
+        parsed: ParsedArgs<'a>,
+        raw_args: Vec<String>,
+    ) -> Matches<'p, 'a> {
+        Matches {
+            program,
+            parsed,
+            raw_args,
+        }
+    }
+
+    /// The `Program` the values were parsed by.
+    pub fn program(&self) -> &'p Program<'a> {
+        self.program
+    }
+
+    /// A copy of the `Program` holding the values, the same as if it had been parsed with
+    /// `Program::parse_from_strings`.
+    pub fn into_program(self) -> Program<'a> {
+        let mut program = self.program.clone();
+        program.set_parsed(self.parsed);
+        program.raw_args = self.raw_args;
+        program
+    }
+
+    /// Extract the parsed value by its unique name, like `Program::get`.
+    pub fn get<T>(&self, name: &str) -> Result<T, ProgramError>
+    where
+        T: FromStr + Clone + 'static,
+    {
+        self.program
+            .value_in::<T>(&self.parsed.flag_values, name)
+            .cloned()
+    }
+
+    /// A softer `Matches::get`, like `Program::get_or`.
+    pub fn get_or<T>(&self, name: &str, fallback: T) -> T
+    where
+        T: FromStr + Clone + 'static,
+    {
+        self.get::<T>(name).unwrap_or(fallback)
+    }
+
+    /// Borrow the value of a flag as it was given, like `Program::get_str`.
+    pub fn get_str(&self, name: &str) -> Result<&str, ProgramError> {
+        self.program.str_value_in(&self.parsed.flag_values, name)
+    }
+
+    /// The value of a flag as it was given, like `Program::get_string`.
+    pub fn get_string(&self, name: &str) -> Result<String, ProgramError> {
+        self.get_str(name).map(str::to_string)
+    }
+
+    /// Extract every value given to a raw flag, like `Program::get_raw`.
+    pub fn get_raw(&self, name: &str) -> Result<Vec<String>, ProgramError> {
+        self.program
+            .value_in::<Vec<String>>(&self.parsed.flag_values, name)
+            .cloned()
+    }
+
+    /// Whether the flag was explicitly given by the user, like `Program::is_present`.
+    pub fn is_present(&self, name: &str) -> bool {
+        self.parsed
+            .flag_values
+            .get(name)
+            .is_some_and(|v| v.source != ValueSource::Default)
+    }
+
+    /// How many times the flag was given on the command line, like `Program::occurrences_of`.
+    pub fn occurrences_of(&self, name: &str) -> usize {
+        self.parsed
+            .flag_values
+            .get(name)
+            .map(|v| v.occurrences)
+            .unwrap_or(0)
+    }
+
+    /// Iterate over every flag with a value, in the order they were registered, like
+    /// `Program::values`.
+    pub fn values(&self) -> impl Iterator<Item = (&str, &str, ValueSource)> {
+        self.program.flags.iter().filter_map(|f| {
+            self.parsed
+                .flag_values
+                .get(&f.name)
+                .map(|v| (f.name.as_ref(), v.str_value.as_str(), v.source))
+        })
+    }
+
+    /// The exact arguments the values were parsed from, like `Program::raw_args`.
+    pub fn raw_args(&self) -> &[String] {
+        &self.raw_args
+    }
+
+    /// Every argument after the `--` terminator, like `Program::trailing_args`.
+    pub fn trailing_args(&self) -> &[String] {
+        &self.parsed.trailing_args
+    }
+
+    /// The subcommand which was given, parsed with the arguments after it.
+    pub fn subcommand(&self) -> Option<&Program<'a>> {
+        self.parsed.subcommand.as_deref()
+    }
+
+    /// The name of the subcommand which was given, such as `build`.
+    pub fn subcommand_name(&self) -> Option<&str> {
+        self.subcommand().and_then(|s| s.name())
+    }
+}
+
+// Like a `Program`, the values of secret flags are redacted from the raw args.
+impl Debug for Matches<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Matches")
+            .field("flag_values", &self.parsed.flag_values)
+            .field("raw_args", &self.program.redact_args(&self.raw_args))
+            .field("trailing_args", &self.parsed.trailing_args)
+            .field("subcommand", &self.parsed.subcommand)
+            .field("external_subcommand", &self.parsed.external_subcommand)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program() -> Program<'static> {
+        Program::new()
+            .with_secret_flag("token", "API token")
+            .unwrap()
+            .with_optional_flag::<bool>("verbose", false, "Log more")
+            .unwrap()
+            .with_subcommand("build", Program::new())
+            .unwrap()
+    }
+
+    #[test]
+    fn should_hold_the_same_values_as_a_parsed_program() {
+        let program = program();
+        let args = ["--token", "t0ken", "--verbose", "build"];
+
+        let matches = program.parse_from(args).unwrap();
+
+        assert_eq!("t0ken", matches.get_str("token").unwrap());
+        assert!(matches.is_present("verbose"));
+        assert_eq!(Some("build"), matches.subcommand_name());
+        assert_eq!(args, matches.raw_args());
+        assert_eq!(
+            program.clone().parse_from_str_arr(&args).unwrap(),
+            matches.into_program()
+        );
+    }
+
+    #[test]
+    fn should_redact_secret_values_in_debug_output() {
+        let program = program();
+        let matches = program.parse_from(["--token", "t0ken"]).unwrap();
+
+        let debug = format!("{:?}", matches);
+
+        assert!(!debug.contains("t0ken"));
+        assert!(debug.contains(r#"raw_args: ["--token", "****"]"#));
+    }
+}
@@ -0,0 +1,175 @@
+use std::any::{Any, TypeId};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::net::{
+    AddrParseError, IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6,
+};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::error::ValueParseError;
+use crate::flag::{ParsedValue, ValueParser};
+
+/// The parser for flags of an address type, which explains what an address should look like when
+/// one can't be parsed, as `AddrParseError` on its own only says the syntax is invalid.
+pub(crate) fn address_parser<T: Any>() -> Option<ValueParser> {
+    let type_id = TypeId::of::<T>();
+    let parser = if type_id == TypeId::of::<IpAddr>() {
+        ValueParser(|s| parse::<IpAddr>(s, "127.0.0.1 or ::1"))
+    } else if type_id == TypeId::of::<Ipv4Addr>() {
+        ValueParser(|s| parse::<Ipv4Addr>(s, "127.0.0.1"))
+    } else if type_id == TypeId::of::<Ipv6Addr>() {
+        ValueParser(|s| parse::<Ipv6Addr>(s, "::1"))
+    } else if type_id == TypeId::of::<SocketAddr>() {
+        ValueParser(|s| parse::<SocketAddr>(s, "127.0.0.1:8080 or [::1]:8080"))
+    } else if type_id == TypeId::of::<SocketAddrV4>() {
+        ValueParser(|s| parse::<SocketAddrV4>(s, "127.0.0.1:8080"))
+    } else if type_id == TypeId::of::<SocketAddrV6>() {
+        ValueParser(|s| parse::<SocketAddrV6>(s, "[::1]:8080"))
+    } else {
+        return None;
+    };
+    Some(parser)
+}
+
+fn parse<T>(s: &str, example: &'static str) -> Result<ParsedValue, ValueParseError>
+where
+    T: FromStr<Err = AddrParseError> + Send + Sync + 'static,
+{
+    s.parse::<T>()
+        .map(|value| Arc::new(value) as ParsedValue)
+        .map_err(|source| {
+            ValueParseError::new(AddressError {
+                source,
+                example,
+                is_hostname: looks_like_hostname(s),
+            })
+        })
+}
+
+/// Hostnames are a common mistake, as they look like addresses but would need to be resolved.
+fn looks_like_hostname(s: &str) -> bool {
+    let host = s.rsplit_once(':').map_or(s, |(host, _)| host);
+    host.contains(|c: char| c.is_ascii_alphabetic())
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+}
+
+/// An address which could not be parsed, along with an example of a valid one.
+#[derive(Debug)]
+struct AddressError {
+    source: AddrParseError,
+    example: &'static str,
+    is_hostname: bool,
+}
+
+impl Display for AddressError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}, expected something like {}",
+            self.source, self.example
+        )?;
+        if self.is_hostname {
+            write!(f, ", as hostnames are not resolved")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for AddressError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Program;
+
+    use super::*;
+
+    fn parse_error<T>(value: &str) -> String
+    where
+        T: FromStr + Clone + Send + Sync + 'static,
+        T::Err: Error + Send + Sync + 'static,
+    {
+        Program::new()
+            .with_required_flag::<T>("addr", "An address")
+            .unwrap()
+            .parse_from(["--addr", value])
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn should_parse_every_address_type() {
+        let program = Program::new()
+            .with_required_flag::<IpAddr>("ip", "")
+            .unwrap()
+            .with_required_flag::<SocketAddr>("bind", "")
+            .unwrap()
+            .with_required_flag::<Ipv6Addr>("ipv6", "")
+            .unwrap()
+            .parse_from_str_arr(&[
+                "--ip",
+                "10.0.0.1",
+                "--bind",
+                "[::1]:8080",
+                "--ipv6",
+                "fe80::1",
+            ])
+            .unwrap();
+
+        assert_eq!(
+            "10.0.0.1".parse::<IpAddr>().unwrap(),
+            program.get::<IpAddr>("ip").unwrap()
+        );
+        assert_eq!(
+            "[::1]:8080".parse::<SocketAddr>().unwrap(),
+            program.get::<SocketAddr>("bind").unwrap()
+        );
+        assert_eq!(
+            "fe80::1".parse::<Ipv6Addr>().unwrap(),
+            program.get::<Ipv6Addr>("ipv6").unwrap()
+        );
+    }
+
+    #[test]
+    fn should_explain_what_an_address_looks_like() {
+        assert_eq!(
+            "Could not parse addr as type of core::net::socket_addr::SocketAddr, invalid socket \
+            address syntax, expected something like 127.0.0.1:8080 or [::1]:8080",
+            parse_error::<SocketAddr>("8080")
+        );
+        assert_eq!(
+            "Could not parse addr as type of core::net::ip_addr::Ipv4Addr, invalid IPv4 address \
+            syntax, expected something like 127.0.0.1",
+            parse_error::<Ipv4Addr>("10.0.0")
+        );
+    }
+
+    #[test]
+    fn should_point_out_that_hostnames_are_not_resolved() {
+        assert!(parse_error::<SocketAddr>("localhost:8080")
+            .ends_with("[::1]:8080, as hostnames are not resolved"));
+        assert!(parse_error::<IpAddr>("example.com").ends_with("as hostnames are not resolved"));
+        assert!(!parse_error::<IpAddr>("fe80::g").ends_with("as hostnames are not resolved"));
+    }
+
+    #[test]
+    fn should_keep_the_address_error_as_the_source() {
+        let err = Program::new()
+            .with_required_flag::<IpAddr>("ip", "")
+            .unwrap()
+            .parse_from(["--ip", "nope"])
+            .unwrap_err();
+
+        assert_eq!(Some("nope"), err.value());
+        assert!(err
+            .source()
+            .and_then(|source| source.source())
+            .is_some_and(|source| source.is::<AddrParseError>()));
+    }
+}
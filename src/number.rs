@@ -0,0 +1,104 @@
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+
+use crate::error::ValueParseError;
+use crate::flag::{ParsedValue, ValueParser};
+
+macro_rules! int_parser {
+    ($($int:ty),* $(,)?) => {
+        /// The parser for flags of an integer type, which also accepts hex, octal and binary
+        /// values such as `0xFF`, `0o755` and `0b1010`, and underscores between digits such as
+        /// `1_000_000`, as masks, addresses and large counts are easier to give that way.
+        pub(crate) fn int_parser<T: Any>() -> Option<ValueParser> {
+            let type_id = TypeId::of::<T>();
+            $(
+                if type_id == TypeId::of::<$int>() {
+                    return Some(ValueParser(|s| {
+                        let (digits, radix) = split_radix(s);
+                        <$int>::from_str_radix(&digits, radix)
+                            .map(|value| Arc::new(value) as ParsedValue)
+                            .map_err(ValueParseError::new)
+                    }));
+                }
+            )*
+            None
+        }
+    };
+}
+
+int_parser!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Whether the value is an integer in any of the forms integer flags accept.
+pub(crate) fn is_int_literal(s: &str) -> bool {
+    let (digits, radix) = split_radix(s);
+    i128::from_str_radix(&digits, radix).is_ok() || u128::from_str_radix(&digits, radix).is_ok()
+}
+
+/// Splits the value into its digits, still with any sign but without a prefix or underscores, and
+/// the radix they are in.
+fn split_radix(s: &str) -> (String, u32) {
+    let (sign, unsigned) = match s.strip_prefix(['-', '+']) {
+        Some(unsigned) => (&s[..1], unsigned),
+        None => ("", s),
+    };
+    let (radix, digits) = match unsigned.get(..2).map(str::to_ascii_lowercase).as_deref() {
+        Some("0x") => (16, &unsigned[2..]),
+        Some("0o") => (8, &unsigned[2..]),
+        Some("0b") => (2, &unsigned[2..]),
+        _ => (10, unsigned),
+    };
+
+    // Underscores may only separate digits, so `_1`, `1_` and a second sign are still invalid.
+    if digits.starts_with(['_', '-', '+']) || digits.ends_with('_') {
+        return (s.to_string(), 10);
+    }
+    (format!("{}{}", sign, digits.replace('_', "")), radix)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Program;
+
+    fn parse<T>(value: &str) -> Result<T, String>
+    where
+        T: std::fmt::Display + std::str::FromStr + Clone + Send + Sync + 'static,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        Program::new()
+            .with_required_flag::<T>("n", "A number")
+            .unwrap()
+            .parse_from(["--n", value])
+            .and_then(|program| program.get::<T>("n"))
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn should_parse_hex_octal_and_binary_integers() {
+        assert_eq!(Ok(255), parse::<u8>("0xFF"));
+        assert_eq!(Ok(255), parse::<u32>("0xff"));
+        assert_eq!(Ok(493), parse::<u16>("0o755"));
+        assert_eq!(Ok(10), parse::<i64>("0b1010"));
+        assert_eq!(Ok(-16), parse::<i32>("-0x10"));
+    }
+
+    #[test]
+    fn should_parse_integers_with_underscores_between_digits() {
+        assert_eq!(Ok(1_000_000), parse::<u64>("1_000_000"));
+        assert_eq!(Ok(0xdead_beef), parse::<u32>("0xdead_beef"));
+        assert!(parse::<u64>("_1").is_err());
+        assert!(parse::<u64>("1_").is_err());
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_integers_which_do_not_fit() {
+        assert_eq!(
+            Err(
+                "Could not parse n as type of u8, number too large to fit in target type"
+                    .to_string()
+            ),
+            parse::<u8>("0x100")
+        );
+        assert!(parse::<u32>("-1").is_err());
+        assert!(parse::<i32>("0x-1").is_err());
+    }
+}
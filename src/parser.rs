@@ -1,188 +1,1617 @@
 use std::any::TypeId;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::env;
+use std::ffi::OsString;
+use std::iter::Peekable;
+use std::path::Path;
 use std::string::ToString;
 
 use lazy_static::lazy_static;
 
+use crate::completions::COMPLETIONS_FLAG;
 use crate::error::ProgramError;
-use crate::error::ProgramError::HelpFlagGiven;
-use crate::flag::{Flag, FlagValue};
+use crate::error::ProgramError::{HelpFlagGiven, NoArgsGiven, VersionFlagGiven};
+use crate::external::ExternalSubcommand;
+use crate::flag::{parse_bool, Flag, FlagValue, FlagValues, ValueSource, REDACTED};
+use crate::man::MAN_FLAG;
+use crate::matches::Matches;
+use crate::number::is_int_literal;
+use crate::source::ProcessArgs;
+use crate::spec::DUMP_SPEC_FLAG;
+use crate::subcommand::HELP_SUBCOMMAND;
+use crate::tokenizer::tokenize;
 use crate::Program;
 
-const ARG_PREFIX: &str = "--";
-const HELP_FLAG: &str = "help";
+pub(crate) const ARG_PREFIX: &str = "--";
+const SINGLE_DASH_PREFIX: &str = "-";
+/// Every argument after this is a trailing argument, rather than a flag or value.
+const TERMINATOR: &str = "--";
+pub(crate) const HELP_FLAG: &str = "help";
+const VERSION_FLAG: &str = "version";
 
 lazy_static! {
-    static ref BOOL_TYPE_ID: TypeId = TypeId::of::<bool>();
+    pub(crate) static ref BOOL_TYPE_ID: TypeId = TypeId::of::<bool>();
+}
+
+/// Everything parsed from the arguments, apart from the arguments themselves.
+#[derive(Clone, PartialEq)]
+pub(crate) struct ParsedArgs<'a> {
+    pub flag_values: FlagValues<'a>,
+    pub trailing_args: Vec<String>,
+    pub subcommand: Option<Box<Program<'a>>>,
+    pub external_subcommand: Option<ExternalSubcommand>,
+}
+
+/// The arguments being parsed, which are taken one at a time so they never have to be collected
+/// up front. When the raw args are kept, each argument is kept as it is taken.
+///
+/// An argument which could not be read, such as one which is not valid Unicode, is given as the
+/// error, which is only reported once parsing reaches it.
+pub(crate) struct ArgStream<'i, S> {
+    args: Peekable<&'i mut dyn Iterator<Item = Result<S, String>>>,
+    taken: usize,
+    pub kept: Option<Vec<S>>,
+}
+
+impl<'i, S> ArgStream<'i, S>
+where
+    S: AsRef<str> + Into<String> + Clone,
+{
+    pub fn new(
+        args: &'i mut dyn Iterator<Item = Result<S, String>>,
+        keep: bool,
+    ) -> ArgStream<'i, S> {
+        ArgStream {
+            args: args.peekable(),
+            taken: 0,
+            kept: keep.then(Vec::new),
+        }
+    }
+
+    /// Takes the next argument, along with its index.
+    fn next(&mut self) -> Result<Option<(usize, S)>, ProgramError> {
+        let arg = match self.args.next() {
+            Some(Ok(arg)) => arg,
+            Some(Err(arg)) => {
+                return Err(ProgramError::ArgNotUnicode {
+                    arg,
+                    index: Some(self.taken),
+                })
+            }
+            None => return Ok(None),
+        };
+        if let Some(kept) = &mut self.kept {
+            kept.push(arg.clone());
+        }
+        self.taken += 1;
+        Ok(Some((self.taken - 1, arg)))
+    }
+
+    /// Looks at the next argument without taking it.
+    fn peek(&mut self) -> Result<Option<&str>, ProgramError> {
+        let index = self.taken;
+        match self.args.peek() {
+            Some(Ok(arg)) => Ok(Some(arg.as_ref())),
+            Some(Err(arg)) => Err(ProgramError::ArgNotUnicode {
+                arg: arg.clone(),
+                index: Some(index),
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// Takes every argument which is left.
+    fn rest(&mut self) -> Result<Vec<String>, ProgramError> {
+        let mut rest = vec![];
+        while let Some((_, arg)) = self.next()? {
+            rest.push(arg.into());
+        }
+        Ok(rest)
+    }
+
+    /// The arguments which are left, as a stream of their own, such as for a subcommand. They are
+    /// counted from zero, and kept apart from these.
+    pub fn remaining(&mut self) -> ArgStream<'_, S> {
+        let keep = self.kept.is_some();
+        ArgStream::new(&mut self.args, keep)
+    }
 }
 
 impl<'a> Program<'a> {
     /// Parse command line arguments and store their values against the flags configured on
-    /// `Program`. These values are stored in their string representation until later fetched.
+    /// `Program`. Each value is parsed as the type of its flag once, here, so fetching it later
+    /// never fails to parse.
+    ///
+    /// The first argument is the executable, which is not parsed but used as the name of the
+    /// `Program`, unless it was given one with `Program::with_name`.
     pub fn parse(self) -> Result<Program<'a>, ProgramError> {
-        self.parse_from_strings(env::args().collect())
+        self.parse_from_source(ProcessArgs)
+    }
+
+    /// The same as `Program::parse_from_strings`, but instead accepts a `&[&str]`. The arguments
+    /// are parsed while borrowed, so only the values which are stored are allocated, and nothing
+    /// is allocated for them when parsing fails.
+    pub fn parse_from_str_arr(self, arr: &[&str]) -> Result<Program<'a>, ProgramError> {
+        self.parse_borrowed(arr)
+    }
+
+    /// Parse the given `args` without consuming the `Program`, giving back `Matches` which borrow
+    /// it and hold the values. This lets one definition parse many sets of arguments, such as in
+    /// tests or servers handling many requests, without copying it each time.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_required_flag::<u16>("port", "Port number")
+    ///     .unwrap();
+    ///
+    /// let first = program.parse_from(["--port", "8080"]).unwrap();
+    /// let second = program.parse_from(["--port", "443"]).unwrap();
+    ///
+    /// assert_eq!(8080, first.get::<u16>("port").unwrap());
+    /// assert_eq!(443, second.get::<u16>("port").unwrap());
+    /// ```
+    pub fn parse_from<I>(&self, args: I) -> Result<Matches<'_, 'a>, ProgramError>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let mut args = args.into_iter().map(|arg| Ok(arg.into()));
+        let mut args = ArgStream::new(&mut args, self.settings.keep_raw_args);
+        let parsed = self.parse_values(&mut args)?;
+        Ok(Matches::new(self, parsed, args.kept.unwrap_or_default()))
+    }
+
+    /// Parse arguments from an iterator of `OsString`s, such as `env::args_os().skip(1)`. Each
+    /// argument is taken from the iterator as it is parsed, so they are never all collected up
+    /// front, and nothing more is taken once an argument is found which is not valid Unicode.
+    ///
+    /// Every argument is still copied for `Program::raw_args`, unless that is turned off with
+    /// `Program::with_raw_args`, in which case only the values are held on to.
+    ///
+    /// ```
+    /// use std::ffi::OsString;
+    ///
+    /// use commandrs::Program;
+    ///
+    /// let args = ["--port", "8080"].into_iter().map(OsString::from);
+    /// let program = Program::new()
+    ///     .with_required_flag::<u16>("port", "Port number")
+    ///     .unwrap()
+    ///     .parse_from_iter(args)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(8080, program.get::<u16>("port").unwrap());
+    /// ```
+    pub fn parse_from_iter(
+        self,
+        args: impl Iterator<Item = OsString>,
+    ) -> Result<Program<'a>, ProgramError> {
+        let mut args = args.map(|arg| {
+            arg.into_string()
+                .map_err(|arg| arg.to_string_lossy().into_owned())
+        });
+        let (mut program, kept) = self.parse_args(&mut args)?;
+        program.raw_args = kept;
+        Ok(program)
+    }
+
+    /// Splits a whole command line with `tokenize` before parsing it, so REPL-style tools and
+    /// tests can give commands as a single string, such as `--name 'Ollie Smith'`.
+    pub fn parse_from_line(self, line: &str) -> Result<Program<'a>, ProgramError> {
+        self.parse_from_strings(tokenize(line))
+    }
+
+    /// Parse the given `args` parameters and store their values against the flags configured on
+    /// `Program`. Like `Program::parse`, each value is parsed as the type of its flag once, here.
+    ///
+    /// The arguments are kept whole for `Program::raw_args`, so the values are copied out of them.
+    /// Without `Program::with_raw_args`, the values are moved into their flags instead.
+    ///
+    /// Generally, this function will not be used, and instead you will want the `Program::parse`
+    /// function for most programs.
+    pub fn parse_from_strings(self, args: Vec<String>) -> Result<Program<'a>, ProgramError> {
+        if !self.settings.keep_raw_args {
+            return self
+                .parse_args(&mut args.into_iter().map(Ok))
+                .map(|(program, _)| program);
+        }
+
+        let (mut program, _) = self.parse_args(&mut args.iter().map(|arg| Ok(arg.as_str())))?;
+        program.raw_args = args;
+        Ok(program)
+    }
+
+    /// Parses borrowed arguments, only copying them into the raw args once parsing succeeds.
+    pub(crate) fn parse_borrowed<S: AsRef<str>>(
+        self,
+        args: &[S],
+    ) -> Result<Program<'a>, ProgramError> {
+        let (mut program, kept) = self.parse_args(&mut args.iter().map(|arg| Ok(arg.as_ref())))?;
+        program.raw_args = kept.into_iter().map(str::to_string).collect();
+        Ok(program)
+    }
+
+    /// Parses the arguments as they are taken from `args`, giving back the parsed `Program` along
+    /// with the arguments which were kept for the raw args, leaving the caller to set them.
+    fn parse_args<S>(
+        mut self,
+        args: &mut dyn Iterator<Item = Result<S, String>>,
+    ) -> Result<(Program<'a>, Vec<S>), ProgramError>
+    where
+        S: AsRef<str> + Into<String> + Clone,
+    {
+        let mut args = ArgStream::new(args, self.settings.keep_raw_args);
+        let parsed = self.parse_values(&mut args)?;
+        self.set_parsed(parsed);
+        Ok((self, args.kept.unwrap_or_default()))
+    }
+
+    /// Stores the values parsed from the arguments.
+    pub(crate) fn set_parsed(&mut self, parsed: ParsedArgs<'a>) {
+        self.flag_values = parsed.flag_values;
+        self.trailing_args = parsed.trailing_args;
+        self.subcommand = parsed.subcommand;
+        self.external_subcommand = parsed.external_subcommand;
+    }
+
+    /// Parses the arguments into the values of every flag, taking them one at a time. Values are
+    /// moved out of the arguments into their flags, so owned arguments are never copied.
+    pub(crate) fn parse_values<S>(
+        &self,
+        args: &mut ArgStream<S>,
+    ) -> Result<ParsedArgs<'a>, ProgramError>
+    where
+        S: AsRef<str> + Into<String> + Clone,
+    {
+        if args.peek()?.is_none() && self.settings.args_required_else_help {
+            self.print_help(true);
+            return Err(NoArgsGiven);
+        }
+
+        // The index of the last time each flag was given, along with its value.
+        let mut given_flag_args: HashMap<&str, (usize, Option<S>)> =
+            HashMap::with_capacity(self.flags.len());
+        let mut given_raw_args: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut occurrences: HashMap<&str, usize> = HashMap::with_capacity(self.flags.len());
+        let mut trailing_args = vec![];
+        // The index of the argument the subcommand was given at, along with the subcommand.
+        let mut given_subcommand: Option<(usize, usize)> = None;
+        let mut external_subcommand = None;
+        let mut help_given = false;
+        // The help flag shows the long help text, while its aliases, such as `-h`, show the short.
+        let mut long_help = false;
+        let mut version_given = false;
+        while let Some((i, arg)) = args.next()? {
+            let arg = arg.as_ref();
+            if arg == TERMINATOR {
+                trailing_args = args.rest()?;
+                break;
+            }
+
+            if self.settings.help_aliases.iter().any(|alias| alias == arg) {
+                help_given = true;
+                continue;
+            }
+
+            if let Some((name, count)) = self.short_verbosity_flag(arg) {
+                given_flag_args.insert(name, (i, None));
+                *occurrences.entry(name).or_default() += count;
+                continue;
+            }
+
+            let arg_name = match self.strip_arg_prefix(arg) {
+                Some(arg_name) => arg_name,
+                None => {
+                    if arg == HELP_SUBCOMMAND && self.has_help_subcommand() {
+                        return Err(self.show_subcommand_help(args.peek()?));
+                    }
+                    // Every argument after a subcommand belongs to the subcommand.
+                    if let Some(n) = self.subcommands.iter().position(|s| s.name() == Some(arg)) {
+                        given_subcommand = Some((i, n));
+                        break;
+                    }
+                    if let Some(path) = self.find_external_subcommand(arg) {
+                        external_subcommand = Some(ExternalSubcommand {
+                            name: arg.to_string(),
+                            path,
+                            args: args.rest()?,
+                        });
+                        break;
+                    }
+                    if !self.subcommands.is_empty() {
+                        return Err(self.unknown_subcommand(arg).at_index(i));
+                    }
+                    continue;
+                }
+            };
+
+            // The help flag is never treated as an abbreviation of another flag, so it can't be
+            // ambiguous, unless a flag has been registered with the same name.
+            if self.settings.help_flag.as_deref() == Some(arg_name) && self.flag(arg_name).is_none()
+            {
+                help_given = true;
+                long_help = true;
+                continue;
+            }
+
+            // Like the help flag, the version flag gives way to a flag registered with its name.
+            if self.version.is_some() && arg_name == VERSION_FLAG && self.flag(arg_name).is_none() {
+                version_given = true;
+                continue;
+            }
+
+            if self.completions_flag
+                && arg_name == COMPLETIONS_FLAG
+                && self.flag(arg_name).is_none()
+            {
+                return Err(self.show_completions(args.peek()?, i));
+            }
+            if self.man_flag && arg_name == MAN_FLAG && self.flag(arg_name).is_none() {
+                return Err(self.show_man_page());
+            }
+            if self.dump_spec_flag && arg_name == DUMP_SPEC_FLAG && self.flag(arg_name).is_none() {
+                return Err(self.dump_spec());
+            }
+
+            let flag = self.find_flag(arg_name).map_err(|err| err.at_index(i))?;
+            if flag.is_none() && self.settings.strict {
+                return Err(ProgramError::UnknownFlag {
+                    name: arg_name.to_string(),
+                    prefix: self.settings.prefix.to_string(),
+                    index: Some(i),
+                });
+            }
+            if let Some(raw_flag) = flag.filter(|f| f.is_raw) {
+                // Nothing after a raw flag is looked at, it all belongs to the raw flag.
+                given_raw_args.insert(&raw_flag.name, args.rest()?);
+                *occurrences.entry(&raw_flag.name).or_default() += 1;
+                break;
+            }
+
+            // A value which was used by the flag is taken, so it isn't looked at again, as it
+            // might look like a flag itself when hyphen values are allowed.
+            let takes_value = self.value_of_flag(flag, args.peek()?).is_some();
+            let arg_value = if takes_value {
+                args.next()?.map(|(_, value)| value)
+            } else {
+                None
+            };
+            if let Some(flag) = flag {
+                given_flag_args.insert(&flag.name, (i, arg_value));
+                *occurrences.entry(&flag.name).or_default() += 1;
+            }
+        }
+
+        let help_flag = self.settings.help_flag.as_deref();
+        long_help |= help_flag.is_some_and(|h| given_flag_args.contains_key(h));
+        help_given |= long_help;
+        if help_given && !self.settings.errors_before_help {
+            return Err(self.show_help(long_help));
+        }
+        if version_given {
+            return Err(self.show_version());
+        }
+
+        // Every flag is resolved in one pass, keeping the first error along with the name of every
+        // required flag which was not given at all, so they can be reported together.
+        let mut flag_values = HashMap::with_capacity(self.flags.len());
+        let mut first_err = None;
+        let mut missing = vec![];
+        let mut unprompted = vec![];
+        for flag in &self.flags {
+            let name = flag.name.as_ref();
+            let given = given_flag_args.remove(name);
+            // Errors in a value which was given point at the value.
+            let value_index = match &given {
+                Some((i, Some(_))) => Some(i + 1),
+                _ => None,
+            };
+            let flag_value = match (given_raw_args.remove(name), given) {
+                (Some(raw_args), _) => Ok(FlagValue::raw(flag.name.clone(), raw_args)
+                    .with_source(ValueSource::CommandLine)),
+                (None, Some((_, Some(given_arg)))) => FlagValue::parse(flag, given_arg.into())
+                    .map(|v| v.with_source(ValueSource::CommandLine)),
+                (None, Some(_)) if flag.type_id == *BOOL_TYPE_ID => {
+                    Ok(FlagValue::new(flag.name.clone(), true.to_string(), true)
+                        .with_source(ValueSource::CommandLine))
+                }
+                (None, Some((i, None))) => Err(ProgramError::RequiredArgWasNotGiven {
+                    name: name.to_string(),
+                    prefix: self.settings.prefix.to_string(),
+                    index: Some(i),
+                }),
+                (None, None) => match self.env_var_flag_value(flag) {
+                    Some(env_value) => env_value,
+                    None if flag.is_required => {
+                        unprompted.push(flag);
+                        continue;
+                    }
+                    None => self.default_flag(name).cloned(),
+                },
+            };
+
+            let flag_value = flag_value
+                .map(|fv| fv.with_secret(flag.is_secret))
+                .and_then(|fv| self.check_value(flag, fv))
+                .map_err(|err| match value_index {
+                    Some(i) => err.at_index(i),
+                    None => err,
+                });
+
+            match flag_value {
+                Ok(mut fv) => {
+                    fv.occurrences = occurrences.get(name).copied().unwrap_or(0);
+                    flag_values.insert(flag.name.clone(), fv);
+                }
+                Err(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
+
+        // Missing flags are only prompted for once every other flag has been resolved without an
+        // error, so the user is never asked for a value, or a secret, which is then thrown away.
+        for flag in unprompted {
+            let prompted = match first_err {
+                None => self.prompt_for_flag(flag),
+                Some(_) => None,
+            };
+            let prompted = prompted.map(|flag_value| {
+                flag_value
+                    .map(|fv| fv.with_secret(flag.is_secret))
+                    .and_then(|fv| self.check_value(flag, fv))
+            });
+            match prompted {
+                Some(Ok(fv)) => {
+                    flag_values.insert(flag.name.clone(), fv);
+                }
+                Some(Err(err)) => {
+                    first_err.get_or_insert(err);
+                }
+                None => {
+                    missing.push(flag.name.to_string());
+                    first_err.get_or_insert(ProgramError::RequiredArgWasNotGiven {
+                        name: flag.name.to_string(),
+                        prefix: self.settings.prefix.to_string(),
+                        index: None,
+                    });
+                }
+            }
+        }
+
+        if let Some(err) = first_err {
+            return Err(self.with_all_missing_flags(err, missing));
+        }
+
+        if help_given {
+            return Err(self.show_help(long_help));
+        }
+
+        let mut subcommand = None;
+        if let Some((at, n)) = given_subcommand {
+            let given = self.subcommands.get(n).cloned().unwrap_or_default();
+            let given = self.parse_subcommand(given, &flag_values, args, at)?;
+            self.take_global_values(&mut flag_values, &given);
+            subcommand = Some(Box::new(given));
+        }
+
+        Ok(ParsedArgs {
+            flag_values,
+            trailing_args,
+            subcommand,
+            external_subcommand,
+        })
+    }
+
+    pub(crate) fn show_help(&self, long: bool) -> ProgramError {
+        self.print_help(long);
+        HelpFlagGiven
+    }
+
+    fn show_version(&self) -> ProgramError {
+        match (&self.name, &self.version) {
+            (Some(name), Some(version)) => println!("{} {}", name, version),
+            (None, Some(version)) => println!("{}", version),
+            _ => {}
+        }
+        VersionFlagGiven
+    }
+
+    /// Gathers every required flag which was not given at all into one error when more than one
+    /// is missing, so users can fix them in one go rather than one run at a time.
+    fn with_all_missing_flags(&self, err: ProgramError, names: Vec<String>) -> ProgramError {
+        match err {
+            ProgramError::RequiredArgWasNotGiven { index: None, .. } if names.len() > 1 => {
+                ProgramError::RequiredArgsWereNotGiven {
+                    names,
+                    prefix: self.settings.prefix.to_string(),
+                }
+            }
+            _ => err,
+        }
+    }
+
+    /// Makes sure a value which was given is one of the flag's possible values, if it has any, and
+    /// passes every one of the flag's validators. Defaults are not checked, as they are up to the
+    /// program rather than the user.
+    fn check_value(
+        &self,
+        flag: &Flag<'a>,
+        flag_value: FlagValue<'a>,
+    ) -> Result<FlagValue<'a>, ProgramError> {
+        if flag_value.source == ValueSource::Default {
+            return Ok(flag_value);
+        }
+
+        let is_possible = flag.possible_values.is_empty()
+            || flag
+                .possible_values
+                .iter()
+                .any(|v| v.as_ref() == flag_value.str_value);
+        if !is_possible {
+            return Err(ProgramError::FlagValueNotPossible {
+                name: flag.name.to_string(),
+                prefix: self.settings.prefix.to_string(),
+                value: flag_value.shown_value(),
+                possible_values: flag.possible_values.iter().map(|v| v.to_string()).collect(),
+                index: None,
+            });
+        }
+
+        for validator in &flag.validators {
+            if let Err(reason) = (validator.0)(flag_value.value.as_ref()) {
+                return Err(ProgramError::InvalidFlagValue {
+                    name: flag.name.to_string(),
+                    prefix: self.settings.prefix.to_string(),
+                    value: flag_value.shown_value(),
+                    reason,
+                    index: None,
+                });
+            }
+        }
+
+        Ok(flag_value)
+    }
+
+    /// Parses the value of the flag's environment variable, if it has one and it is set.
+    fn env_var_flag_value(&self, flag: &Flag<'a>) -> Option<Result<FlagValue<'a>, ProgramError>> {
+        let env_value = env::var(flag.env_var.as_ref()?.as_ref()).ok()?;
+        Some(FlagValue::parse(flag, env_value).map(|v| v.with_source(ValueSource::Environment)))
+    }
+
+    /// Reconstruct the arguments which would parse to the same values as this parsed `Program`,
+    /// in the order the flags were registered. This is useful for logging the effective
+    /// invocation, or re-spawning workers with the same config.
+    ///
+    /// Secret flags are always left out, so they are never logged by accident.
+    pub fn to_args(&self) -> Vec<String> {
+        self.args_where(&|_| true)
+    }
+
+    /// The same as `Program::to_args`, but only with the flags which were explicitly given,
+    /// leaving out any which used their defaults.
+    pub fn to_explicit_args(&self) -> Vec<String> {
+        self.args_where(&|value| value.source != ValueSource::Default)
+    }
+
+    fn args_where(&self, include: &dyn Fn(&FlagValue) -> bool) -> Vec<String> {
+        let mut args = vec![];
+        // Raw flags take every argument after them, so they have to come last.
+        let mut raw_args = vec![];
+
+        for flag in self.flags.iter().filter(|f| !f.is_secret) {
+            let value = match self.flag_values.get(&flag.name) {
+                Some(value) if include(value) => value,
+                _ => continue,
+            };
+            let arg = format!("{}{}", self.settings.prefix, flag.name);
+
+            if flag.is_raw {
+                let raw_values = value.value.downcast_ref::<Vec<String>>();
+                if let Some(raw_values) = raw_values.filter(|v| !v.is_empty()) {
+                    raw_args.push(arg);
+                    raw_args.extend(raw_values.iter().cloned());
+                }
+            } else if value.value.downcast_ref::<bool>() == Some(&true) {
+                args.push(arg);
+            } else {
+                args.push(arg);
+                args.push(value.str_value.clone());
+            }
+        }
+
+        args.extend(raw_args);
+        if let Some(subcommand) = &self.subcommand {
+            args.extend(subcommand.name.iter().map(|n| n.to_string()));
+            // Global flags are only given once, before the subcommand.
+            args.extend(
+                subcommand
+                    .args_where(&|v| include(v) && !self.is_propagated_to(subcommand, &v.name)),
+            );
+        }
+        if let Some(external) = &self.external_subcommand {
+            args.push(external.name.clone());
+            args.extend(external.args.iter().cloned());
+        }
+        if !self.trailing_args().is_empty() {
+            args.push(TERMINATOR.to_string());
+            args.extend(self.trailing_args().iter().cloned());
+        }
+        args
+    }
+
+    /// Finds the flag an argument name refers to. Usually this must be an exact match, but when
+    /// abbreviations are enabled an unambiguous prefix of a flag name is also accepted.
+    ///
+    /// With case insensitive flags, both the exact and abbreviated matches ignore case, though a
+    /// flag matching with the exact same case is always preferred.
+    pub(crate) fn find_flag(&self, arg_name: &str) -> Result<Option<&Flag<'a>>, ProgramError> {
+        if let Some(flag) = self.flag(arg_name) {
+            return Ok(Some(flag));
+        }
+
+        let normalised_arg_name = self.normalise_name(arg_name);
+        if self.settings.case_insensitive_flags {
+            if let Some(flag) = self
+                .flags
+                .iter()
+                .find(|f| self.normalise_name(&f.name) == normalised_arg_name)
+            {
+                return Ok(Some(flag));
+            }
+        }
+
+        if !self.settings.abbreviations || arg_name.is_empty() {
+            return Ok(None);
+        }
+
+        let candidates: Vec<&Flag> = self
+            .flags
+            .iter()
+            .filter(|f| {
+                self.normalise_name(&f.name)
+                    .starts_with(normalised_arg_name.as_ref())
+            })
+            .collect();
+
+        match candidates.as_slice() {
+            [] => Ok(None),
+            [flag] => Ok(Some(flag)),
+            _ => Err(ProgramError::AmbiguousFlagAbbreviation {
+                name: arg_name.to_string(),
+                prefix: self.settings.prefix.to_string(),
+                candidates: candidates.iter().map(|f| f.name.to_string()).collect(),
+                index: None,
+            }),
+        }
+    }
+
+    fn normalise_name<'s>(&self, name: &'s str) -> Cow<'s, str> {
+        if self.settings.case_insensitive_flags {
+            Cow::Owned(name.to_lowercase())
+        } else {
+            Cow::Borrowed(name)
+        }
+    }
+
+    /// The argument after a flag, if the flag takes it as its value.
+    fn value_of_flag<'s>(&self, flag: Option<&Flag<'a>>, next: Option<&'s str>) -> Option<&'s str> {
+        let requires_value = flag.map(|f| f.type_id != *BOOL_TYPE_ID).unwrap_or(false);
+        let allow_hyphen_values = flag
+            .map(|f| requires_value && f.allow_hyphen_values)
+            .unwrap_or(false);
+
+        // With subcommands, a bool flag only takes the next argument as its value if it is a
+        // bool, as otherwise it is the subcommand, or a typo of one.
+        next.filter(|s| allow_hyphen_values || !self.is_in_arg_format(s))
+            .filter(|s| requires_value || self.subcommands.is_empty() || parse_bool(s).is_ok())
+    }
+
+    /// The arguments with the value of every secret flag replaced, such as `--token ****` or
+    /// `--token=****`. Values are found by their position, as they are when parsing, walking into
+    /// the subcommand for the arguments after it, so other arguments are never touched.
+    pub(crate) fn redact_args<'s, S: AsRef<str>>(&self, args: &'s [S]) -> Vec<Cow<'s, str>> {
+        let mut program = self;
+        let mut redacted = Vec::with_capacity(args.len());
+        let mut i = 0;
+        while i < args.len() {
+            let arg = args[i].as_ref();
+            if arg == TERMINATOR {
+                break;
+            }
+
+            let arg_name = match program.strip_arg_prefix(arg) {
+                Some(arg_name) => arg_name,
+                None => {
+                    let subcommand = program
+                        .subcommand
+                        .as_deref()
+                        .filter(|s| s.name() == Some(arg))
+                        .or_else(|| program.subcommands.iter().find(|s| s.name() == Some(arg)));
+                    if let Some(subcommand) = subcommand {
+                        program = subcommand;
+                    }
+                    redacted.push(Cow::Borrowed(arg));
+                    i += 1;
+                    continue;
+                }
+            };
+
+            let is_secret = |name: &str| {
+                program
+                    .find_flag(name)
+                    .ok()
+                    .flatten()
+                    .is_some_and(|f| f.is_secret)
+            };
+            match arg_name.split_once('=') {
+                Some((name, _)) if is_secret(name) => {
+                    let flag_arg = &arg[..arg.len() - arg_name.len() + name.len()];
+                    redacted.push(Cow::Owned(format!("{}={}", flag_arg, REDACTED)));
+                    i += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let flag = program.find_flag(arg_name).ok().flatten();
+            let shown = |value: &'s str| match flag.is_some_and(|f| f.is_secret) {
+                true => Cow::Borrowed(REDACTED),
+                false => Cow::Borrowed(value),
+            };
+            redacted.push(Cow::Borrowed(arg));
+            if flag.is_some_and(|f| f.is_raw) {
+                // Every argument after a raw flag is one of its values.
+                redacted.extend(args[i + 1..].iter().map(|arg| shown(arg.as_ref())));
+                return redacted;
+            }
+            match program.value_of_flag(flag, args.get(i + 1).map(AsRef::as_ref)) {
+                Some(value) => {
+                    redacted.push(shown(value));
+                    i += 2;
+                }
+                None => i += 1,
+            }
+        }
+        redacted.extend(args[i..].iter().map(|arg| Cow::Borrowed(arg.as_ref())));
+        redacted
+    }
+
+    pub(crate) fn is_in_arg_format(&self, s: &str) -> bool {
+        self.strip_arg_prefix(s).is_some()
+    }
+
+    /// Strips the configured prefix from an argument, giving back the name of the flag. In single
+    /// dash mode, `-name` is also accepted alongside the configured prefix.
+    ///
+    /// Negative numbers are never considered flags, so `-5` can always be given as a value, even
+    /// when the prefix is `-`. Neither is a lone `-`, as it usually stands for stdin.
+    pub(crate) fn strip_arg_prefix<'s>(&self, s: &'s str) -> Option<&'s str> {
+        if s == "-" || is_negative_number(s) {
+            return None;
+        }
+
+        s.strip_prefix(self.settings.prefix.as_ref()).or_else(|| {
+            if self.settings.single_dash_long_flags {
+                s.strip_prefix(SINGLE_DASH_PREFIX)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// The file name of an executable, without any extension it must have on this platform, such as
+/// `mytool` for `/usr/bin/mytool` or `C:\bin\mytool.exe` on Windows.
+pub(crate) fn executable_name(executable: &str) -> String {
+    let name = Path::new(executable)
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or(Cow::Borrowed(executable));
+    name.strip_suffix(env::consts::EXE_SUFFIX)
+        .filter(|name| !name.is_empty())
+        .unwrap_or(&name)
+        .to_string()
+}
+
+fn is_negative_number(s: &str) -> bool {
+    match s.strip_prefix('-') {
+        Some(rest) => {
+            rest.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+                && (s.parse::<f64>().is_ok() || is_int_literal(s))
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::{Display, Formatter};
+    use std::iter;
+    use std::num::ParseIntError;
+    use std::str::FromStr;
+
+    use crate::error::ValueParseError;
+
+    use super::*;
+
+    #[test]
+    fn should_have_values_for_given_args_when_parsed() {
+        let name_value = Program::new()
+            .with_required_flag::<String>("name", "Your name")
+            .unwrap()
+            .parse_from_str_arr(&["--name", "Ollie"])
+            .unwrap()
+            .get_string("name")
+            .unwrap();
+
+        assert_eq!("Ollie", name_value);
+    }
+
+    #[test]
+    fn should_have_values_for_given_args_when_parsed_from_a_line() {
+        let name_value = Program::new()
+            .with_required_flag::<String>("name", "Your name")
+            .unwrap()
+            .parse_from_line("--name 'Ollie Smith'")
+            .unwrap()
+            .get_string("name")
+            .unwrap();
+
+        assert_eq!("Ollie Smith", name_value);
+    }
+
+    #[test]
+    fn should_reconstruct_args_which_parse_to_the_same_values() {
+        let program = || {
+            Program::new()
+                .with_required_flag::<u16>("port", "Port number")
+                .unwrap()
+                .with_optional_flag::<String>("host", "localhost".to_string(), "Host to bind")
+                .unwrap()
+                .with_optional_flag::<bool>("use-tls", false, "TLS PLS?")
+                .unwrap()
+                .with_optional_flag::<bool>("verbose", false, "Log everything")
+                .unwrap()
+                .with_secret_flag("token", "API token")
+                .unwrap()
+                .with_raw_flag("cmd", "Command to run on startup")
+                .unwrap()
+        };
+
+        let parsed = program()
+            .parse_from_str_arr(&[
+                "--port",
+                "8080",
+                "--verbose",
+                "--token",
+                "hunter2",
+                "--cmd",
+                "ls",
+                "-la",
+            ])
+            .unwrap();
+
+        assert_eq!(
+            vec!["--port", "8080", "--verbose", "--cmd", "ls", "-la"],
+            parsed.to_explicit_args()
+        );
+        assert_eq!(
+            vec![
+                "--port",
+                "8080",
+                "--host",
+                "localhost",
+                "--use-tls",
+                "false",
+                "--verbose",
+                "--cmd",
+                "ls",
+                "-la"
+            ],
+            parsed.to_args()
+        );
+
+        let mut args = parsed.to_args();
+        args.splice(0..0, ["--token".to_string(), "hunter2".to_string()]);
+        let reparsed = program().parse_from_strings(args).unwrap();
+        assert_eq!(parsed.to_args(), reparsed.to_args());
+    }
+
+    #[test]
+    fn should_keep_trailing_args_after_the_terminator() {
+        let program = Program::new()
+            .with_strict(true)
+            .with_optional_flag::<bool>("watch", false, "Run again when files change")
+            .unwrap()
+            .parse_from_str_arr(&["--", "cargo", "--watch", "--", "x"])
+            .unwrap();
+
+        assert_eq!(["cargo", "--watch", "--", "x"], program.trailing_args());
+        assert!(!program.get::<bool>("watch").unwrap());
+        assert_eq!(
+            vec!["--", "cargo", "--watch", "--", "x"],
+            program.to_explicit_args()
+        );
+        assert!(program.reset().trailing_args().is_empty());
+    }
+
+    #[test]
+    fn should_parse_the_same_values_without_keeping_raw_args() {
+        let program = |keep_raw_args| {
+            Program::new()
+                .with_raw_args(keep_raw_args)
+                .with_required_flag::<String>("name", "Your name")
+                .unwrap()
+                .with_subcommand(
+                    "run",
+                    Program::new()
+                        .with_raw_flag("cmd", "Command to run")
+                        .unwrap(),
+                )
+                .unwrap()
+        };
+        let args = ["--name", "Ollie", "run", "--cmd", "ls", "-la"];
+        let args = || args.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+
+        let kept = program(true).parse_from_strings(args()).unwrap();
+        let moved = program(false).parse_from_strings(args()).unwrap();
+
+        assert_eq!(args(), kept.raw_args());
+        assert_eq!(
+            ["--cmd", "ls", "-la"],
+            kept.subcommand().unwrap().raw_args()
+        );
+        assert!(moved.raw_args().is_empty());
+        assert!(moved.subcommand().unwrap().raw_args().is_empty());
+        assert_eq!(kept.to_args(), moved.to_args());
+        assert_eq!("Ollie", moved.get_str("name").unwrap());
+    }
+
+    #[test]
+    fn should_parse_borrowed_args_the_same_as_owned_ones() {
+        let program = || {
+            Program::new()
+                .with_required_flag::<u16>("port", "Port number")
+                .unwrap()
+                .with_raw_flag("cmd", "Command to run on startup")
+                .unwrap()
+        };
+        let args = ["--port", "8080", "--cmd", "ls", "--", "-la"];
+
+        let borrowed = program().parse_from_str_arr(&args).unwrap();
+        let owned = program()
+            .parse_from_strings(args.iter().map(|s| s.to_string()).collect())
+            .unwrap();
+
+        assert_eq!(owned, borrowed);
+        assert_eq!(args, borrowed.raw_args());
+        assert_eq!(vec!["ls", "--", "-la"], borrowed.get_raw("cmd").unwrap());
+    }
+
+    #[test]
+    fn should_take_args_from_an_iterator_only_as_they_are_parsed() {
+        // The iterator never ends, so it can only be parsed if it is never collected.
+        let args = iter::once("biuld")
+            .chain(iter::repeat("--verbose"))
+            .map(OsString::from);
+
+        let err = Program::new()
+            .with_subcommand("build", Program::new())
+            .unwrap()
+            .parse_from_iter(args)
+            .unwrap_err();
+
+        assert_eq!(Some(0), err.index());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_stop_taking_args_at_the_first_which_is_not_unicode() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let mut taken = 0;
+        let args = [
+            OsString::from("--name"),
+            OsString::from_vec(vec![b'O', 0xff]),
+            OsString::from("--age"),
+        ]
+        .into_iter()
+        .inspect(|_| taken += 1);
+
+        let err = Program::new()
+            .with_required_flag::<String>("name", "Your name")
+            .unwrap()
+            .parse_from_iter(args)
+            .unwrap_err();
+
+        assert_eq!(2, taken);
+        assert_eq!(
+            ProgramError::ArgNotUnicode {
+                arg: "O\u{fffd}".to_string(),
+                index: Some(1),
+            },
+            err
+        );
+        assert_eq!("Argument 'O\u{fffd}' is not valid Unicode", err.to_string());
+    }
+
+    #[test]
+    fn should_count_occurrences_of_flags_when_parsed() {
+        let program = Program::new()
+            .with_optional_flag::<bool>("verbose", false, "Log more")
+            .unwrap()
+            .with_optional_flag::<String>("name", "Ollie".to_string(), "Your name")
+            .unwrap()
+            .with_optional_flag::<bool>("quiet", false, "Log less")
+            .unwrap()
+            .parse_from_str_arr(&["--verbose", "--name", "Ollie", "--verbose", "--verbose"])
+            .unwrap();
+
+        assert_eq!(3, program.occurrences_of("verbose"));
+        assert_eq!(1, program.occurrences_of("name"));
+        assert_eq!(0, program.occurrences_of("quiet"));
+        assert_eq!(0, program.occurrences_of("unknown"));
+    }
+
+    #[test]
+    fn should_keep_the_exact_args_when_parsed() {
+        let program = Program::new()
+            .with_optional_flag::<bool>("verbose", false, "Log everything")
+            .unwrap()
+            .parse_from_str_arr(&["mytool", "--verbose", "true", "extra"])
+            .unwrap();
+
+        assert_eq!(["mytool", "--verbose", "true", "extra"], program.raw_args());
+    }
+
+    #[test]
+    fn should_fall_back_to_env_vars_when_flags_are_not_given() {
+        env::set_var("COMMANDRS_TEST_ENV_PORT", "8080");
+        env::set_var("COMMANDRS_TEST_ENV_HOST", "example.com");
+
+        let program = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_env_var("port", "COMMANDRS_TEST_ENV_PORT")
+            .unwrap()
+            .with_optional_flag::<String>("host", "localhost".to_string(), "Host to bind")
+            .unwrap()
+            .with_env_var("host", "COMMANDRS_TEST_ENV_HOST")
+            .unwrap()
+            .parse_from_str_arr(&["--host", "0.0.0.0"])
+            .unwrap();
+
+        assert_eq!(8080, program.get::<u16>("port").unwrap());
+        assert_eq!("0.0.0.0", program.get::<String>("host").unwrap());
+        assert!(program.is_present("port"));
+    }
+
+    #[test]
+    fn should_only_accept_possible_values_when_flag_has_them() {
+        let program = Program::new()
+            .with_optional_flag::<String>("speed", "auto".to_string(), "How fast to go")
+            .unwrap()
+            .with_possible_values("speed", ["fast", "slow", "auto"])
+            .unwrap();
+
+        assert_eq!(
+            "slow",
+            program
+                .parse_from(["--speed", "slow"])
+                .unwrap()
+                .get::<String>("speed")
+                .unwrap()
+        );
+        assert_eq!(
+            ProgramError::FlagValueNotPossible {
+                name: "speed".to_string(),
+                prefix: "--".to_string(),
+                value: "ludicrous".to_string(),
+                possible_values: vec!["fast".to_string(), "slow".to_string(), "auto".to_string()],
+                index: Some(1),
+            },
+            program.parse_from(["--speed", "ludicrous"]).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn should_have_values_for_given_args_when_parsed_and_convert_them() {
+        let name_value = Program::new()
+            .with_required_flag::<usize>("cranberries", "Number of cranberries")
+            .unwrap()
+            .parse_from_str_arr(&["--cranberries", "314159265358979"])
+            .unwrap()
+            .get::<usize>("cranberries")
+            .unwrap();
+
+        assert_eq!(314159265358979, name_value);
+    }
+
+    #[test]
+    fn should_result_in_an_error_when_required_arg_is_not_given() {
+        let err = Program::new()
+            .with_required_flag::<String>("required-flag", "A required flag, wow")
+            .unwrap()
+            .parse_from_str_arr(&[])
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::RequiredArgWasNotGiven {
+                name: "required-flag".to_string(),
+                prefix: "--".to_string(),
+                index: None,
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn should_result_in_one_error_for_every_required_arg_which_is_not_given() {
+        let err = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<bool>("use-tls", false, "TLS PLS?")
+            .unwrap()
+            .with_required_flag::<String>("host", "Host to bind")
+            .unwrap()
+            .parse_from_str_arr(&["--use-tls"])
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::RequiredArgsWereNotGiven {
+                names: vec!["port".to_string(), "host".to_string()],
+                prefix: "--".to_string(),
+            },
+            err
+        );
+        assert_eq!(
+            "Required args were not given with names --port, --host",
+            err.to_string()
+        );
     }
 
-    /// Just wraps `Program::parse_from_strings`, but instead accepts a `&[&str]`.
-    pub fn parse_from_str_arr(self, arr: &[&str]) -> Result<Program<'a>, ProgramError> {
-        self.parse_from_strings(arr.iter().map(|s| s.to_string()).collect())
+    #[test]
+    fn should_show_help_for_the_help_flag_and_its_aliases() {
+        let program = Program::new()
+            .with_help_pager(false)
+            .with_help_flag("usage")
+            .with_help_alias("-h")
+            .with_optional_flag::<String>("name", "Ollie".to_string(), "Your name")
+            .unwrap();
+
+        assert_eq!(Err(HelpFlagGiven), program.parse_from(["--usage"]));
+        assert_eq!(Err(HelpFlagGiven), program.parse_from(["-h"]));
+        assert!(program.parse_from(["--help"]).is_ok());
+        assert_eq!(
+            "-h",
+            program
+                .parse_from(["--name", "-h"])
+                .unwrap()
+                .get::<String>("name")
+                .unwrap()
+        );
     }
 
-    /// Parse the given `args` parameters and store their values against the flags configured on
-    /// `Program`. These values are stored in their string representation until later fetched.
-    ///
-    /// Generally, this function will not be used, and instead you will want the `Program::parse`
-    /// function for most programs.
-    pub fn parse_from_strings(mut self, args: Vec<String>) -> Result<Program<'a>, ProgramError> {
-        let given_flag_args: HashMap<&str, Option<&String>> = args
-            .iter()
-            .enumerate()
-            .filter(|(_, a)| is_in_arg_format(a))
-            .map(|(i, a)| {
-                let arg_name = a.strip_prefix(ARG_PREFIX).unwrap_or(a);
-                let requires_value = self
-                    .flags
-                    .iter()
-                    .find(|f| f.name == arg_name)
-                    .map(|f| f.type_id != *BOOL_TYPE_ID)
-                    .unwrap_or(false);
-
-                let arg_value = args
-                    .get(i + 1)
-                    .map(|b| b)
-                    .filter(|s| requires_value || !is_in_arg_format(s));
-                (arg_name, arg_value)
-            })
-            .collect();
+    #[test]
+    fn should_show_the_version_for_the_version_flag_when_there_is_one() {
+        let program = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap();
 
-        let flag_value_mutations: Vec<Result<FlagValue, ProgramError>> = self
-            .flags
-            .iter()
-            .map(
-                |&Flag {
-                     name,
-                     type_id,
-                     is_required,
-                     ..
-                 }| match given_flag_args.get(name) {
-                    Some(Some(given_arg)) => Ok(FlagValue {
-                        name,
-                        str_value: given_arg.to_string(),
-                    }),
-                    Some(_) if type_id == *BOOL_TYPE_ID => Ok(FlagValue {
-                        name,
-                        str_value: true.to_string(),
-                    }),
-                    Some(None) => Err(ProgramError::RequiredArgWasNotGiven {
-                        name: name.to_string(),
-                    }),
-                    None if is_required => Err(ProgramError::RequiredArgWasNotGiven {
-                        name: name.to_string(),
-                    }),
-                    None => {
-                        let flag_value = self.unwrap_default_flag_value(name);
-                        Ok(FlagValue {
-                            name,
-                            str_value: flag_value.to_string(),
-                        })
-                    }
-                },
-            )
-            .collect();
+        assert_eq!(
+            Err(VersionFlagGiven),
+            program
+                .clone()
+                .with_version("1.2.3")
+                .parse_from(["--version"])
+        );
+        assert!(matches!(
+            program.parse_from(["--version"]),
+            Err(ProgramError::RequiredArgWasNotGiven { .. })
+        ));
+    }
 
-        if let Some(Err(err)) = flag_value_mutations.iter().find(|r| r.is_err()) {
-            return Err(err.clone());
-        }
+    #[test]
+    fn should_only_reject_unknown_flags_in_strict_mode() {
+        let program = Program::new()
+            .with_optional_flag::<bool>("verbose", false, "Log everything")
+            .unwrap();
 
-        if given_flag_args.contains_key(HELP_FLAG) {
-            println!("{}", self.generate_help_text());
+        assert!(program.parse_from(["--quiet"]).is_ok());
+        assert_eq!(
+            Err(ProgramError::UnknownFlag {
+                name: "quiet".to_string(),
+                prefix: "--".to_string(),
+                index: Some(1),
+            }),
+            program
+                .with_strict(true)
+                .parse_from(["--verbose", "--quiet", "extra"])
+        );
+    }
 
-            return Err(HelpFlagGiven);
-        }
+    #[test]
+    fn should_name_the_program_after_the_executable() {
+        assert_eq!("mytool", executable_name("/usr/local/bin/mytool"));
+        assert_eq!("mytool", executable_name("mytool"));
+        assert_eq!("", executable_name(""));
+    }
 
-        self.flag_values = flag_value_mutations
-            .into_iter()
-            .filter_map(|r| r.ok())
-            .collect();
+    #[test]
+    fn should_show_help_before_reporting_missing_flags() {
+        let program = Program::new()
+            .with_help_pager(false)
+            .with_abbreviations(true)
+            .with_required_flag::<u16>("helper-port", "Port of the helper")
+            .unwrap()
+            .with_optional_flag::<bool>("helpful", false, "Be helpful")
+            .unwrap();
 
-        Ok(self)
+        assert_eq!(Err(HelpFlagGiven), program.parse_from(["--help"]));
+        assert_eq!(
+            ProgramError::RequiredArgWasNotGiven {
+                name: "helper-port".to_string(),
+                prefix: "--".to_string(),
+                index: None,
+            },
+            program
+                .with_errors_before_help(true)
+                .parse_from(["--help"])
+                .unwrap_err()
+        );
     }
-}
 
-fn is_in_arg_format(s: &str) -> bool {
-    s.starts_with(ARG_PREFIX)
-}
+    #[test]
+    fn should_leave_the_help_flag_alone_when_it_is_disabled() {
+        let program = Program::new()
+            .without_help_flag()
+            .with_optional_flag::<bool>("help", false, "Handled elsewhere")
+            .unwrap()
+            .parse_from_str_arr(&["--help"])
+            .unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        assert!(program.get::<bool>("help").unwrap());
+    }
 
     #[test]
-    fn should_have_values_for_given_args_when_parsed() {
-        let name_value = Program::new()
-            .with_required_flag::<&str>("name", "Your name")
+    fn should_parse_flags_using_a_custom_prefix() {
+        let program = Program::new()
+            .with_prefix("+")
+            .with_optional_flag::<bool>("feature", false, "Enable the feature")
             .unwrap()
-            .parse_from_str_arr(&["--name", "Ollie"])
+            .with_required_flag::<String>("name", "Your name")
             .unwrap()
-            .get_string("name")
+            .parse_from_str_arr(&["+feature", "+name", "Ollie"])
             .unwrap();
 
-        assert_eq!("Ollie", name_value);
+        assert!(program.get::<bool>("feature").unwrap());
+        assert_eq!("Ollie", program.get_string("name").unwrap());
     }
 
     #[test]
-    fn should_have_values_for_given_args_when_parsed_and_convert_them() {
-        let name_value = Program::new()
-            .with_required_flag::<usize>("cranberries", "Number of cranberries")
+    fn should_accept_single_dash_long_flags_when_enabled() {
+        let program = Program::new()
+            .with_single_dash_long_flags(true)
+            .with_optional_flag::<bool>("verbose", false, "Talk a lot")
             .unwrap()
-            .parse_from_str_arr(&["--cranberries", "314159265358979"])
+            .with_required_flag::<u16>("port", "Port number")
             .unwrap()
-            .get::<usize>("cranberries")
+            .parse_from_str_arr(&["-verbose", "--port", "8080"])
             .unwrap();
 
-        assert_eq!(314159265358979, name_value);
+        assert!(program.get::<bool>("verbose").unwrap());
+        assert_eq!(8080, program.get::<u16>("port").unwrap());
     }
 
     #[test]
-    fn should_result_in_an_error_when_required_arg_is_not_given() {
+    fn should_ignore_single_dash_long_flags_by_default() {
+        let program = Program::new()
+            .with_optional_flag::<bool>("verbose", false, "Talk a lot")
+            .unwrap()
+            .parse_from_str_arr(&["-verbose"])
+            .unwrap();
+
+        assert!(!program.get::<bool>("verbose").unwrap());
+    }
+
+    #[test]
+    fn should_match_unambiguous_abbreviations_when_enabled() {
+        let program = Program::new()
+            .with_abbreviations(true)
+            .with_optional_flag::<bool>("verbose", false, "Talk a lot")
+            .unwrap()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .parse_from_str_arr(&["--verb", "--po", "8080"])
+            .unwrap();
+
+        assert!(program.get::<bool>("verbose").unwrap());
+        assert_eq!(8080, program.get::<u16>("port").unwrap());
+    }
+
+    #[test]
+    fn should_prefer_exact_matches_over_abbreviations() {
+        let program = Program::new()
+            .with_abbreviations(true)
+            .with_optional_flag::<bool>("all", false, "Everything")
+            .unwrap()
+            .with_optional_flag::<bool>("all-the-things", false, "Everything, really")
+            .unwrap()
+            .parse_from_str_arr(&["--all"])
+            .unwrap();
+
+        assert!(program.get::<bool>("all").unwrap());
+        assert!(!program.get::<bool>("all-the-things").unwrap());
+    }
+
+    #[test]
+    fn should_result_in_an_error_when_abbreviation_is_ambiguous() {
+        let err = Program::new()
+            .with_abbreviations(true)
+            .with_optional_flag::<bool>("verbose", false, "Talk a lot")
+            .unwrap()
+            .with_optional_flag::<bool>("version", false, "Print the version")
+            .unwrap()
+            .parse_from_str_arr(&["--ver"])
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::AmbiguousFlagAbbreviation {
+                name: "ver".to_string(),
+                prefix: "--".to_string(),
+                candidates: vec!["verbose".to_string(), "version".to_string()],
+                index: Some(0),
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn should_match_flags_ignoring_case_when_enabled() {
+        let program = Program::new()
+            .with_case_insensitive_flags(true)
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<bool>("use-tls", false, "TLS PLS?")
+            .unwrap()
+            .parse_from_str_arr(&["--PORT", "8080", "--Use-TLS"])
+            .unwrap();
+
+        assert_eq!(8080, program.get::<u16>("port").unwrap());
+        assert!(program.get::<bool>("use-tls").unwrap());
+    }
+
+    #[test]
+    fn should_match_flags_exactly_by_default() {
+        let err = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .parse_from_str_arr(&["--Port", "8080"])
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::RequiredArgWasNotGiven {
+                name: "port".to_string(),
+                prefix: "--".to_string(),
+                index: None,
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn should_accept_negative_numbers_as_values_with_a_single_dash_prefix() {
+        let program = Program::new()
+            .with_prefix("-")
+            .with_required_flag::<i32>("offset", "Offset to apply")
+            .unwrap()
+            .with_optional_flag::<f64>("scale", 1.0, "Scale to apply")
+            .unwrap()
+            .with_optional_flag::<bool>("verbose", false, "Talk a lot")
+            .unwrap()
+            .parse_from_str_arr(&["-offset", "-5", "-verbose", "-scale", "-.5"])
+            .unwrap();
+
+        assert_eq!(-5, program.get::<i32>("offset").unwrap());
+        assert_eq!(-0.5, program.get::<f64>("scale").unwrap());
+        assert!(program.get::<bool>("verbose").unwrap());
+    }
+
+    #[test]
+    fn should_not_consider_negative_numbers_to_be_flags() {
+        let program = Program::new().with_single_dash_long_flags(true);
+
+        assert!(!program.is_in_arg_format("-5"));
+        assert!(!program.is_in_arg_format("-3.14"));
+        assert!(!program.is_in_arg_format("-1e10"));
+        assert!(program.is_in_arg_format("-inf"));
+        assert!(program.is_in_arg_format("-verbose"));
+    }
+
+    #[test]
+    fn should_not_use_flag_like_values_for_flags() {
         let err = Program::new()
-            .with_required_flag::<&str>("required-flag", "A required flag, wow")
+            .with_required_flag::<String>("pattern", "Pattern to search for")
+            .unwrap()
+            .with_optional_flag::<bool>("ignore-case", false, "Ignore case")
+            .unwrap()
+            .parse_from_str_arr(&["--pattern", "--ignore-case"])
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::RequiredArgWasNotGiven {
+                name: "pattern".to_string(),
+                prefix: "--".to_string(),
+                index: Some(0),
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn should_use_flag_like_values_when_hyphen_values_are_allowed() {
+        let program = Program::new()
+            .with_required_flag::<String>("pattern", "Pattern to search for")
+            .unwrap()
+            .with_hyphen_values_allowed("pattern")
+            .unwrap()
+            .with_optional_flag::<bool>("ignore-case", false, "Ignore case")
+            .unwrap()
+            .parse_from_str_arr(&["--pattern", "--ignore-case"])
+            .unwrap();
+
+        assert_eq!("--ignore-case", program.get_string("pattern").unwrap());
+        assert!(!program.get::<bool>("ignore-case").unwrap());
+    }
+
+    #[test]
+    fn should_take_every_remaining_arg_for_raw_flags() {
+        let program = Program::new()
+            .with_optional_flag::<bool>("verbose", false, "Talk a lot")
+            .unwrap()
+            .with_raw_flag("cmd", "Command to execute")
+            .unwrap()
+            .parse_from_str_arr(&["--cmd", "ls", "-la", "--verbose", "/tmp"])
+            .unwrap();
+
+        assert_eq!(
+            vec!["ls", "-la", "--verbose", "/tmp"],
+            program.get_raw("cmd").unwrap()
+        );
+        assert!(!program.get::<bool>("verbose").unwrap());
+    }
+
+    #[test]
+    fn should_have_no_values_for_raw_flags_which_are_not_given() {
+        let program = Program::new()
+            .with_raw_flag("cmd", "Command to execute")
             .unwrap()
             .parse_from_str_arr(&[])
+            .unwrap();
+
+        assert!(program.get_raw("cmd").unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_not_treat_default_prefix_as_a_flag_when_using_a_custom_prefix() {
+        let err = Program::new()
+            .with_prefix("-")
+            .with_required_flag::<String>("name", "Your name")
+            .unwrap()
+            .parse_from_str_arr(&["--name", "Ollie"])
             .unwrap_err();
 
         assert_eq!(
             ProgramError::RequiredArgWasNotGiven {
-                name: "required-flag".to_string()
+                name: "name".to_string(),
+                prefix: "-".to_string(),
+                index: None,
             },
             err
         );
+        assert_eq!(
+            "Required args was not given with name -name",
+            err.to_string()
+        );
     }
 
     #[test]
     fn should_result_in_an_error_when_parsing_fails_for_type() {
-        let program = Program::new()
+        let err = Program::new()
             .with_required_flag::<u8>("age", "Your age")
             .unwrap()
             .parse_from_str_arr(&["--age", "who?"])
-            .unwrap();
-
-        let err = program.get::<u8>("age").unwrap_err();
+            .unwrap_err();
 
         assert_eq!(
             ProgramError::FailedToParseFlagValue {
                 name: "age".to_string(),
-                type_name: "u8".to_string()
+                type_name: "u8".to_string(),
+                value: "who?".to_string(),
+                index: Some(1),
+                source: ValueParseError::new("who?".parse::<u8>().unwrap_err()),
             },
             err
         );
     }
 
+    #[test]
+    fn should_point_errors_at_the_argument_which_caused_them() {
+        let err = Program::new()
+            .with_required_flag::<u8>("age", "Your age")
+            .unwrap()
+            .parse_from_str_arr(&["--age", "30", "--age", "who?"])
+            .unwrap_err();
+
+        assert_eq!(Some(3), err.index());
+        assert_eq!(Some("who?"), err.value());
+    }
+
+    #[test]
+    fn should_only_parse_values_once_when_parsed() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static PARSE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Clone, Debug, PartialEq)]
+        struct Counted(u8);
+
+        impl FromStr for Counted {
+            type Err = ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                PARSE_COUNT.fetch_add(1, Ordering::SeqCst);
+                s.parse().map(Counted)
+            }
+        }
+
+        impl Display for Counted {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        let program = Program::new()
+            .with_required_flag::<Counted>("count", "A counted value")
+            .unwrap()
+            .parse_from_str_arr(&["--count", "3"])
+            .unwrap();
+
+        assert_eq!(Counted(3), program.get::<Counted>("count").unwrap());
+        assert_eq!(Counted(3), program.get::<Counted>("count").unwrap());
+        assert_eq!(1, PARSE_COUNT.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn should_have_values_for_every_flag_when_parsing_many_flags() {
+        let names: Vec<String> = (0..500).map(|i| format!("flag-{}", i)).collect();
+        let args: Vec<String> = names
+            .iter()
+            .enumerate()
+            .flat_map(|(i, name)| vec![format!("--{}", name), i.to_string()])
+            .collect();
+
+        let program = names
+            .iter()
+            .fold(Program::new(), |program, name| {
+                program.with_required_flag::<usize>(name, "A flag").unwrap()
+            })
+            .parse_from_strings(args)
+            .unwrap();
+
+        for (i, name) in names.iter().enumerate() {
+            assert_eq!(i, program.get::<usize>(name).unwrap());
+        }
+    }
+
     #[test]
     fn should_use_default_values_for_optional_args_when_parsed() {
         let name_value = Program::new()
-            .with_optional_flag::<&str>("name", "Mr. Ollie", "Your name")
+            .with_optional_flag::<String>("name", "Mr. Ollie".to_string(), "Your name")
             .unwrap()
             .parse_from_str_arr(&["--something", "else"])
             .unwrap()
@@ -197,7 +1626,7 @@ mod tests {
         let program = Program::new()
             .with_optional_flag::<bool>("is-wonderful", false, "Is it wonderful?")
             .unwrap()
-            .with_required_flag::<&str>("name", "Your name")
+            .with_required_flag::<String>("name", "Your name")
             .unwrap()
             .parse_from_str_arr(&["--is-wonderful", "--name", "Dr. Ollie"])
             .unwrap();
@@ -214,7 +1643,7 @@ mod tests {
         let program = Program::new()
             .with_required_flag::<bool>("is-great", "Is it great?")
             .unwrap()
-            .with_required_flag::<&str>("name", "Your name")
+            .with_required_flag::<String>("name", "Your name")
             .unwrap()
             .parse_from_str_arr(&["--is-great", "true", "--name", "Dr. Ollie"])
             .unwrap();
@@ -225,4 +1654,89 @@ mod tests {
         assert!(is_great);
         assert_eq!("Dr. Ollie", name);
     }
+
+    #[test]
+    fn should_accept_booleans_given_as_yes_no_on_off_or_numbers() {
+        let program = Program::new()
+            .with_required_flag::<bool>("enabled", "Is it enabled?")
+            .unwrap();
+
+        for (value, expected) in [
+            ("yes", true),
+            ("ON", true),
+            ("1", true),
+            ("True", true),
+            ("no", false),
+            ("Off", false),
+            ("0", false),
+            ("FALSE", false),
+        ] {
+            let parsed = program.parse_from(["--enabled", value]).unwrap();
+            assert_eq!(
+                expected,
+                parsed.get::<bool>("enabled").unwrap(),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn should_list_the_accepted_booleans_when_one_can_not_be_parsed() {
+        let err = Program::new()
+            .with_required_flag::<bool>("enabled", "Is it enabled?")
+            .unwrap()
+            .parse_from_str_arr(&["--enabled", "maybe"])
+            .unwrap_err();
+
+        assert_eq!(
+            "Could not parse enabled as type of bool, expected one of true, false, yes, no, on, off, \
+            1 or 0",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn should_give_back_true_booleans_as_just_the_flag() {
+        let program = Program::new()
+            .with_optional_flag::<bool>("verbose", false, "Talk a lot")
+            .unwrap()
+            .parse_from_str_arr(&["--verbose", "yes"])
+            .unwrap();
+
+        assert_eq!(vec!["--verbose"], program.to_args());
+    }
+
+    #[test]
+    fn should_give_back_errors_instead_of_panicking_on_malformed_args() {
+        let program = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<bool>("verbose", false, "Log more")
+            .unwrap()
+            .with_raw_flag("cmd", "Command to run")
+            .unwrap()
+            .with_subcommand("build", Program::new())
+            .unwrap();
+        let malformed: &[&[&str]] = &[
+            &["--"],
+            &["-"],
+            &["--="],
+            &["--port="],
+            &["--port", "--port"],
+            &["--port", "99999999999"],
+            &["--cmd"],
+            &["--verbose=maybe"],
+            &["help", "nope"],
+            &["-vvv", "\u{0}"],
+            &["buil"],
+            &["build", "--port"],
+            &["\u{feff}--port", "1"],
+        ];
+
+        for args in malformed {
+            let attempt = program.clone();
+            let _ = attempt.parse_from_str_arr(args);
+        }
+    }
 }
@@ -0,0 +1,285 @@
+//! Checks for `PathBuf` flags, so tools don't each have to look at the filesystem themselves
+//! before using a path they were given, and `Input` for paths which can be `-` for stdin.
+
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::fmt::{Display, Formatter};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::error::ProgramError;
+use crate::flag::FlagValue;
+use crate::Program;
+
+/// Something which must be true of a path given for a flag, checked when the `Program` is parsed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PathCheck {
+    /// The path must exist.
+    Exists,
+    /// The path must exist and be a file.
+    IsFile,
+    /// The path must exist and be a directory.
+    IsDir,
+    /// The path must be writable, or be in a writable directory when it does not exist yet.
+    Writable,
+}
+
+impl PathCheck {
+    fn check(self, path: &Path) -> Result<(), String> {
+        let is_valid = match self {
+            PathCheck::Exists => path.exists(),
+            PathCheck::IsFile => path.is_file(),
+            PathCheck::IsDir => path.is_dir(),
+            PathCheck::Writable => is_writable(path),
+        };
+        if is_valid {
+            return Ok(());
+        }
+
+        Err(match self {
+            PathCheck::Exists => "the path does not exist",
+            PathCheck::IsFile => "the path is not a file",
+            PathCheck::IsDir => "the path is not a directory",
+            PathCheck::Writable => "the path is not writable",
+        }
+        .to_string())
+    }
+}
+
+fn is_writable(path: &Path) -> bool {
+    if let Ok(metadata) = fs::metadata(path) {
+        return !metadata.permissions().readonly();
+    }
+
+    // A path which doesn't exist yet can be written if it can be created in its directory.
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::metadata(dir).is_ok_and(|metadata| metadata.is_dir() && !metadata.permissions().readonly())
+}
+
+/// A file to read from, or stdin when it is given as `-`, following the usual Unix convention.
+///
+/// ```
+/// use std::io::Read;
+///
+/// use commandrs::path::Input;
+/// use commandrs::Program;
+///
+/// let program = Program::new()
+///     .with_optional_flag("input", Input::Stdin, "File to read, or - for stdin")
+///     .unwrap()
+///     .parse_from_str_arr(&["--input", "Cargo.toml"])
+///     .unwrap();
+///
+/// let mut contents = String::new();
+/// program
+///     .get::<Input>("input")
+///     .unwrap()
+///     .open()
+///     .unwrap()
+///     .read_to_string(&mut contents)
+///     .unwrap();
+/// assert!(contents.contains("commandrs"));
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Input {
+    /// Stdin, given as `-`.
+    Stdin,
+    /// A file, given as any other path.
+    Path(PathBuf),
+}
+
+impl Input {
+    /// Open the file, or stdin, for reading.
+    pub fn open(&self) -> io::Result<Box<dyn Read>> {
+        match self {
+            Input::Stdin => Ok(Box::new(io::stdin())),
+            Input::Path(path) => Ok(Box::new(File::open(path)?)),
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Input, Infallible> {
+        Ok(match s {
+            "-" => Input::Stdin,
+            _ => Input::Path(PathBuf::from(s)),
+        })
+    }
+}
+
+impl Display for Input {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Input::Stdin => write!(f, "-"),
+            Input::Path(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+impl<'a> Program<'a> {
+    /// Add an optional `PathBuf` flag, which `Program::with_optional_flag` can't do as paths are
+    /// not `Display`. The default is shown in the help text as it would be printed by
+    /// `Path::display`.
+    pub fn with_optional_path_flag(
+        mut self,
+        name: impl Into<Cow<'a, str>>,
+        default: impl Into<PathBuf>,
+        desc: impl Into<Cow<'a, str>>,
+    ) -> Result<Program<'a>, ProgramError> {
+        let name = name.into();
+        let default = default.into();
+        self = self.with_required_flag::<PathBuf>(name.clone(), desc)?;
+        self.flag_mut(&name)?.is_required = false;
+        self.flag_defaults.insert(
+            name.clone(),
+            FlagValue::new(name, default.display().to_string(), default),
+        );
+        Ok(self)
+    }
+
+    /// Extract the value of a `PathBuf` flag. This is the same as `Program::get::<PathBuf>`,
+    /// without having to name the type.
+    pub fn get_path(&self, name: &str) -> Result<PathBuf, ProgramError> {
+        self.get_value::<PathBuf>(name).cloned()
+    }
+
+    /// Check the path given for a `PathBuf` flag when the `Program` is parsed, so a missing
+    /// config file is reported like any other invalid value.
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    ///
+    /// use commandrs::path::PathCheck;
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_required_flag::<PathBuf>("config", "Config file")
+    ///     .unwrap()
+    ///     .with_path_checks("config", [PathCheck::IsFile])
+    ///     .unwrap();
+    ///
+    /// assert!(program.parse_from(["--config", "Cargo.toml"]).is_ok());
+    /// assert!(program.parse_from(["--config", "src"]).is_err());
+    /// ```
+    pub fn with_path_checks(
+        self,
+        name: &str,
+        checks: impl IntoIterator<Item = PathCheck>,
+    ) -> Result<Program<'a>, ProgramError> {
+        let checks: Vec<PathCheck> = checks.into_iter().collect();
+        self.with_validator::<PathBuf, _>(name, move |path| {
+            checks.iter().try_for_each(|check| check.check(path))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn program(checks: &[PathCheck]) -> Program<'static> {
+        Program::new()
+            .with_required_flag::<PathBuf>("path", "Some path")
+            .unwrap()
+            .with_path_checks("path", checks.to_vec())
+            .unwrap()
+    }
+
+    #[test]
+    fn should_accept_paths_which_pass_every_check() {
+        let program = program(&[PathCheck::Exists, PathCheck::IsDir, PathCheck::Writable]);
+
+        let parsed = program.parse_from_str_arr(&["--path", "src"]).unwrap();
+
+        assert_eq!(PathBuf::from("src"), parsed.get_path("path").unwrap());
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_paths_which_fail_a_check() {
+        let err = program(&[PathCheck::IsFile])
+            .parse_from(["--path", "src"])
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::InvalidFlagValue {
+                name: "path".to_string(),
+                prefix: "--".to_string(),
+                value: "src".to_string(),
+                reason: "the path is not a file".to_string(),
+                index: Some(1),
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn should_consider_new_files_in_writable_directories_writable() {
+        let program = program(&[PathCheck::Writable]);
+        let new_file = env::temp_dir().join("new-file");
+
+        assert!(program
+            .parse_from(["--path", &new_file.to_string_lossy()])
+            .is_ok());
+        assert!(program
+            .parse_from(["--path", "no-such-dir/new-file"])
+            .is_err());
+    }
+
+    #[test]
+    fn should_use_the_default_path_when_not_given() {
+        let program = Program::new()
+            .with_optional_path_flag("config", "/etc/app.toml", "Config file")
+            .unwrap()
+            .parse_from_strings(vec![])
+            .unwrap();
+
+        assert_eq!(
+            PathBuf::from("/etc/app.toml"),
+            program.get_path("config").unwrap()
+        );
+    }
+
+    #[test]
+    fn should_read_from_stdin_when_the_input_is_a_dash() {
+        let program = Program::new()
+            .with_required_flag::<Input>("input", "File to read")
+            .unwrap()
+            .with_prefix("-");
+
+        let stdin = program.parse_from(["-input", "-"]).unwrap();
+        let file = program.parse_from(["-input", "Cargo.toml"]).unwrap();
+
+        assert_eq!(Input::Stdin, stdin.get::<Input>("input").unwrap());
+        assert_eq!(
+            Input::Path(PathBuf::from("Cargo.toml")),
+            file.get::<Input>("input").unwrap()
+        );
+    }
+
+    #[test]
+    fn should_only_check_paths_of_path_flags() {
+        let err = Program::new()
+            .with_required_flag::<String>("path", "Some path")
+            .unwrap()
+            .with_path_checks("path", [PathCheck::Exists])
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::FlagTypeMismatch {
+                name: "path".to_string(),
+                expected: "alloc::string::String".to_string(),
+                requested: "std::path::PathBuf".to_string(),
+            },
+            err
+        );
+    }
+}
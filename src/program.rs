@@ -1,141 +1,868 @@
 use std::any::{type_name, TypeId};
-use std::fmt::Display;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
 use std::str::FromStr;
+use std::sync::Arc;
 
 use crate::error::ProgramError;
-use crate::flag::{Flag, FlagValue};
+use crate::exit::ExitCodes;
+use crate::external::ExternalSubcommand;
+use crate::flag::{Flag, FlagValue, FlagValues, ValueParser, ValueSource, ValueValidator};
+use crate::settings::ProgramSettings;
+use crate::strings::{Strings, StringsProvider};
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, PartialEq)]
 pub struct Program<'a> {
-    pub(crate) desc: &'a str,
+    pub(crate) name: Option<Cow<'a, str>>,
+    pub(crate) desc: Cow<'a, str>,
+    pub(crate) version: Option<Cow<'a, str>>,
+    pub(crate) author: Option<Cow<'a, str>>,
+    pub(crate) long_about: Option<Cow<'a, str>>,
+    pub(crate) after_help: Option<Cow<'a, str>>,
+    pub(crate) usage: Option<Cow<'a, str>>,
+    pub(crate) settings: ProgramSettings<'a>,
+    pub(crate) assume_yes_flag: Option<Cow<'a, str>>,
+    pub(crate) verbosity: bool,
+    pub(crate) strings: StringsProvider,
+    pub(crate) exit_codes: ExitCodes,
     pub(crate) flags: Vec<Flag<'a>>,
-    pub(crate) flag_defaults: Vec<FlagValue<'a>>,
-    pub(crate) flag_values: Vec<FlagValue<'a>>,
+    pub(crate) flag_indices: HashMap<Cow<'a, str>, usize>,
+    pub(crate) flag_defaults: HashMap<Cow<'a, str>, FlagValue<'a>>,
+    pub(crate) flag_values: HashMap<Cow<'a, str>, FlagValue<'a>>,
+    pub(crate) raw_args: Vec<String>,
+    /// The arguments after the `--` terminator.
+    pub(crate) trailing_args: Vec<String>,
+    pub(crate) subcommands: Vec<Program<'a>>,
+    pub(crate) subcommand: Option<Box<Program<'a>>>,
+    /// The full name of the `Program` this is a subcommand of, such as `mytool` for `build`.
+    pub(crate) parent_name: Option<Cow<'a, str>>,
+    pub(crate) external_subcommands: bool,
+    pub(crate) external_subcommand: Option<ExternalSubcommand>,
+    pub(crate) completions_flag: bool,
+    pub(crate) man_flag: bool,
+    pub(crate) dump_spec_flag: bool,
 }
 
 impl<'a> Default for Program<'a> {
     fn default() -> Program<'a> {
         Program {
-            desc: "",
+            name: None,
+            desc: Cow::Borrowed(""),
+            version: None,
+            author: None,
+            long_about: None,
+            after_help: None,
+            usage: None,
+            settings: ProgramSettings::default(),
+            assume_yes_flag: None,
+            verbosity: false,
+            strings: StringsProvider::default(),
+            exit_codes: ExitCodes::default(),
             flags: vec![],
-            flag_defaults: vec![],
-            flag_values: vec![],
+            flag_indices: HashMap::new(),
+            flag_defaults: HashMap::new(),
+            flag_values: HashMap::new(),
+            raw_args: vec![],
+            trailing_args: vec![],
+            subcommands: vec![],
+            subcommand: None,
+            parent_name: None,
+            external_subcommands: false,
+            external_subcommand: None,
+            completions_flag: false,
+            man_flag: false,
+            dump_spec_flag: false,
         }
     }
 }
 
+// The values of secret flags are redacted from the raw args, as they are from the flag values.
+impl Debug for Program<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let raw_args = self.redact_args(&self.raw_args);
+
+        f.debug_struct("Program")
+            .field("name", &self.name)
+            .field("desc", &self.desc)
+            .field("version", &self.version)
+            .field("author", &self.author)
+            .field("long_about", &self.long_about)
+            .field("after_help", &self.after_help)
+            .field("usage", &self.usage)
+            .field("settings", &self.settings)
+            .field("assume_yes_flag", &self.assume_yes_flag)
+            .field("verbosity", &self.verbosity)
+            .field("strings", &self.strings)
+            .field("exit_codes", &self.exit_codes)
+            .field("flags", &self.flags)
+            .field("flag_indices", &self.flag_indices)
+            .field("flag_defaults", &self.flag_defaults)
+            .field("flag_values", &self.flag_values)
+            .field("raw_args", &raw_args)
+            .field("trailing_args", &self.trailing_args())
+            .field("subcommands", &self.subcommands)
+            .field("subcommand", &self.subcommand)
+            .field("parent_name", &self.parent_name)
+            .field("external_subcommands", &self.external_subcommands)
+            .field("external_subcommand", &self.external_subcommand)
+            .field("completions_flag", &self.completions_flag)
+            .field("man_flag", &self.man_flag)
+            .field("dump_spec_flag", &self.dump_spec_flag)
+            .finish()
+    }
+}
+
+// A parsed `Program` is often shared between threads as the config of a service, so it must stay
+// `Send` and `Sync` as it grows.
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Program<'static>>();
+};
+
 impl<'a> Program<'a> {
     /// This is just an alias for `Program::default`.
     pub fn new() -> Program<'a> {
         Program::default()
     }
 
-    /// Add a description to the `Program`. This will be displayed by the help text.
-    pub fn with_description(mut self, desc: &'a str) -> Program {
-        self.desc = desc;
+    /// Give the `Program` a name, which is shown in the usage line and with the version. This is
+    /// otherwise taken from the name of the executable when the `Program` is parsed with
+    /// `Program::parse`, so this is mostly useful for tests, or tools which are run through a
+    /// wrapper.
+    pub fn with_name(mut self, name: impl Into<Cow<'a, str>>) -> Program<'a> {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// The name of the `Program`, given with `Program::with_name` or taken from the executable.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Add a description to the `Program`, which should be a one-line summary of what it does. This
+    /// is shown at the top of both the long and short help text, while the rest of the explanation
+    /// can be given with `Program::with_long_about`.
+    ///
+    /// Like flag names and descriptions, this can be either borrowed or an owned `String` built at
+    /// runtime.
+    pub fn with_description(mut self, desc: impl Into<Cow<'a, str>>) -> Program<'a> {
+        self.desc = desc.into();
+        self
+    }
+
+    /// Add a version to the `Program`, which is shown for the `version` flag, such as
+    /// `--version`, the same way the help text is shown for the help flag. A flag registered with
+    /// the name `version` is used instead, though.
+    ///
+    /// This is usually `env!("CARGO_PKG_VERSION")`.
+    pub fn with_version(mut self, version: impl Into<Cow<'a, str>>) -> Program<'a> {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Add the author of the `Program`, such as `Ollie <ollie@example.com>`, which is shown at the
+    /// end of the help text.
+    pub fn with_author(mut self, author: impl Into<Cow<'a, str>>) -> Program<'a> {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Add a longer explanation of the `Program`, which is shown in the long help text between the
+    /// description and the flags. It is wrapped to fit, keeping any line breaks it already has.
+    ///
+    /// The long help text is shown for the help flag, such as `--help`, while its aliases, such as
+    /// `-h`, show the short help text with only the description.
+    pub fn with_long_about(mut self, long_about: impl Into<Cow<'a, str>>) -> Program<'a> {
+        self.long_about = Some(long_about.into());
+        self
+    }
+
+    /// Add text shown after the flags in the long help text, such as examples or where to report
+    /// bugs.
+    pub fn with_after_help(mut self, after_help: impl Into<Cow<'a, str>>) -> Program<'a> {
+        self.after_help = Some(after_help.into());
+        self
+    }
+
+    /// Replace the usage line which is generated from the flags, for programs whose arguments
+    /// can't be described by it, such as `mytool [OPTIONS] <SRC>... <DST>`.
+    pub fn with_usage(mut self, usage: impl Into<Cow<'a, str>>) -> Program<'a> {
+        self.usage = Some(usage.into());
+        self
+    }
+
+    /// Change the prefix used to recognise flags on the command line, which is `--` by default.
+    /// Some tools prefer `-`, or something like `+` for DSL-like `+feature` arguments.
+    ///
+    /// The prefix is also used when rendering the help text and error messages, and should not be
+    /// empty.
+    pub fn with_prefix(mut self, prefix: impl Into<Cow<'a, str>>) -> Program<'a> {
+        self.settings.prefix = prefix.into();
+        self
+    }
+
+    /// Also accept long flags given with a single dash, such as `-name value`, alongside the
+    /// configured prefix. This is how Go's `flag` package and many Java tools behave, which makes
+    /// it easier for users coming from those conventions.
+    pub fn with_single_dash_long_flags(mut self, enabled: bool) -> Program<'a> {
+        self.settings.single_dash_long_flags = enabled;
+        self
+    }
+
+    /// Allow flags to be abbreviated on the command line, so `--verb` matches `--verbose`. This
+    /// works like GNU's `getopt_long`, where an exact match always wins and an abbreviation
+    /// matching more than one flag is an error.
+    pub fn with_abbreviations(mut self, enabled: bool) -> Program<'a> {
+        self.settings.abbreviations = enabled;
+        self
+    }
+
+    /// Match flags on the command line regardless of case, so `--Port` and `--PORT` both match
+    /// the `port` flag. Flags are matched exactly by default.
+    pub fn with_case_insensitive_flags(mut self, enabled: bool) -> Program<'a> {
+        self.settings.case_insensitive_flags = enabled;
+        self
+    }
+
+    /// Prompt for required flags which were not given, rather than failing to parse, when stdin is
+    /// a terminal. The flag's description is used as the prompt, which makes for a friendlier
+    /// first run of a tool.
+    pub fn with_prompt_for_missing_flags(mut self, enabled: bool) -> Program<'a> {
+        self.settings.prompt_for_missing_flags = enabled;
+        self
+    }
+
+    /// Show the help text through a pager, like git and man do, when it is too long to fit in
+    /// the terminal. The pager is `$PAGER`, or `less -R` when it is not set, and is only used when
+    /// stdout is a terminal. This is enabled by default.
+    pub fn with_help_pager(mut self, enabled: bool) -> Program<'a> {
+        self.settings.help_pager = enabled;
+        self
+    }
+
+    /// Rename the flag which shows the help text, which is `help` by default.
+    pub fn with_help_flag(mut self, name: impl Into<Cow<'a, str>>) -> Program<'a> {
+        self.settings.help_flag = Some(name.into());
+        self
+    }
+
+    /// Also show the help text for an argument given exactly as is, without the prefix, such as
+    /// `-h` alongside `--help`. Aliases show the short help text, leaving out the long
+    /// description added with `Program::with_long_about`.
+    pub fn with_help_alias(mut self, arg: impl Into<Cow<'a, str>>) -> Program<'a> {
+        self.settings.help_aliases.push(arg.into());
+        self
+    }
+
+    /// Don't show the help text automatically for the help flag, leaving it to be handled by
+    /// something else, such as a dispatch layer which `commandrs` is embedded in. Aliases added
+    /// with `Program::with_help_alias` still show the help text.
+    pub fn without_help_flag(mut self) -> Program<'a> {
+        self.settings.help_flag = None;
         self
     }
 
+    /// Report errors, such as a missing required flag, before showing the help text when the help
+    /// flag is given. By default the help text is shown first, so `mytool --help` always works.
+    pub fn with_errors_before_help(mut self, enabled: bool) -> Program<'a> {
+        self.settings.errors_before_help = enabled;
+        self
+    }
+
+    /// Replace the built-in text shown in the help text, prompts and error messages, such as
+    /// `(required)`, so tools can be fully localised.
+    pub fn with_strings(mut self, strings: impl Strings + 'static) -> Program<'a> {
+        self.strings = StringsProvider(Arc::new(strings));
+        self
+    }
+
+    /// The message to show users for an error, using the `Strings` of this `Program`.
+    pub fn error_message(&self, err: &ProgramError) -> String {
+        self.strings.0.error(err)
+    }
+
+    /// The message to show users for an error, followed by the usage line and a pointer to the
+    /// help flag, so users know how to fix what went wrong.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_required_flag::<u16>("port", "Port number")
+    ///     .unwrap();
+    /// let err = program.parse_from(Vec::<String>::new()).unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     "Required args was not given with name --port\n\n\
+    ///     Usage: --port <u16>\n\n\
+    ///     For more information, try --help",
+    ///     program.error_with_usage(&err)
+    /// );
+    /// ```
+    pub fn error_with_usage(&self, err: &ProgramError) -> String {
+        format!("{}\n\n{}", self.error_message(err), self.usage_with_hint())
+    }
+
+    /// The usage line, followed by a pointer to the help flag when there is one.
+    pub(crate) fn usage_with_hint(&self) -> String {
+        let help_flag = match (&self.settings.help_flag, self.settings.help_aliases.first()) {
+            (Some(help_flag), _) => format!("{}{}", self.settings.prefix, help_flag),
+            (None, Some(alias)) => alias.to_string(),
+            (None, None) => return self.usage(),
+        };
+        format!(
+            "{}\n\n{}",
+            self.usage(),
+            self.strings.0.help_hint(&help_flag)
+        )
+    }
+
     /// Add an optional flag to the `Program`. These do not have to be provided, but require a
     /// default value in the case of no value being provided.
     ///
-    /// The name must be unique.
+    /// The name must be unique, and the default must parse back as `T` from how it is displayed,
+    /// otherwise it could never be given on the command line.
     pub fn with_optional_flag<T>(
         mut self,
-        name: &'a str,
+        name: impl Into<Cow<'a, str>>,
         default: T,
-        desc: &'a str,
+        desc: impl Into<Cow<'a, str>>,
     ) -> Result<Program<'a>, ProgramError>
     where
-        T: Display + 'static,
+        T: Display + FromStr + Clone + Send + Sync + 'static,
+        T::Err: Error + Send + Sync + 'static,
     {
-        self = self.add_flag::<T>(name, desc, false)?;
-        self.flag_defaults.push(FlagValue {
-            name,
-            str_value: default.to_string(),
-        });
+        let name = name.into();
+        let flag = Flag::new::<T>(name.clone(), desc.into(), false);
+        let default = FlagValue::new(name.clone(), default.to_string(), default);
+        if (flag.parser.0)(&default.str_value).is_err() {
+            return Err(ProgramError::InvalidFlagDefinition {
+                name: name.to_string(),
+                reason: "the default does not parse as the type of the flag".to_string(),
+            });
+        }
+
+        self = self.add_flag(flag)?;
+        self.flag_defaults.insert(name, default);
         Ok(self)
     }
 
     /// Add a required flag to the `Program`. These must be provided when parsing the command line
     /// arguments.
     ///
-    /// The name must be unique.
-    pub fn with_required_flag<T: 'static>(
+    /// The name must be unique. `T` has the same bounds as it needs to be extracted with
+    /// `Program::get`, so a flag which could never be extracted does not compile:
+    ///
+    /// ```compile_fail
+    /// use std::str::FromStr;
+    ///
+    /// use commandrs::Program;
+    ///
+    /// struct Token(String);
+    ///
+    /// impl FromStr for Token {
+    ///     type Err = std::convert::Infallible;
+    ///
+    ///     fn from_str(s: &str) -> Result<Self, Self::Err> {
+    ///         Ok(Token(s.to_string()))
+    ///     }
+    /// }
+    ///
+    /// let program = Program::new().with_required_flag::<Token>("token", "API token");
+    /// ```
+    pub fn with_required_flag<T>(
         self,
-        name: &'a str,
-        desc: &'a str,
+        name: impl Into<Cow<'a, str>>,
+        desc: impl Into<Cow<'a, str>>,
+    ) -> Result<Program<'a>, ProgramError>
+    where
+        T: FromStr + Clone + Send + Sync + 'static,
+        T::Err: Error + Send + Sync + 'static,
+    {
+        self.add_flag(Flag::new::<T>(name.into(), desc.into(), true))
+    }
+
+    /// Allow the flag's value to begin with a `-`, such as `--pattern --foo` in grep-like tools.
+    /// Otherwise, a value which looks like another flag is not used as the flag's value.
+    pub fn with_hyphen_values_allowed(mut self, name: &str) -> Result<Program<'a>, ProgramError> {
+        self.flag_mut(name)?.allow_hyphen_values = true;
+        Ok(self)
+    }
+
+    /// Fall back to an environment variable when the flag is not given on the command line, before
+    /// prompting or using the default. The variable is also shown in the help text, such as
+    /// `[env: MYAPP_PORT]`, so users can discover it.
+    pub fn with_env_var(
+        mut self,
+        name: &str,
+        env_var: impl Into<Cow<'a, str>>,
     ) -> Result<Program<'a>, ProgramError> {
-        self.add_flag::<T>(name, desc, true)
+        self.flag_mut(name)?.env_var = Some(env_var.into());
+        Ok(self)
     }
 
-    /// Extract the parsed value by its unique name. This can fail if the argument passed cannot be
-    /// parsed as a type of `T` or not registered.
-    pub fn get<T>(&self, name: &'a str) -> Result<T, ProgramError>
+    /// Restrict the flag to a set of possible values, such as `fast`, `slow` or `auto`. These are
+    /// shown in the help text, and in the error when any other value is given.
+    pub fn with_possible_values<V>(
+        mut self,
+        name: &str,
+        possible_values: impl IntoIterator<Item = V>,
+    ) -> Result<Program<'a>, ProgramError>
     where
-        T: Display + FromStr + 'static,
+        V: Into<Cow<'a, str>>,
     {
-        match self.flag_values.iter().find(|fv| fv.name == name) {
-            Some(flag_value) => flag_value.str_value.parse::<T>().map_err(|_| {
-                let type_name = type_name::<T>().to_string();
-                ProgramError::FailedToParseFlagValue {
-                    name: name.to_string(),
-                    type_name,
-                }
-            }),
-            None => Err(ProgramError::NoSuchFlagExistsWithName {
+        self.flag_mut(name)?.possible_values =
+            possible_values.into_iter().map(Into::into).collect();
+        Ok(self)
+    }
+
+    /// Add an example value for the flag, such as `level>=warn`, which is shown under its
+    /// description in the long help text as `--filter 'level>=warn'`. This can be given more than
+    /// once for more examples.
+    pub fn with_example(
+        mut self,
+        name: &str,
+        example: impl Into<Cow<'a, str>>,
+    ) -> Result<Program<'a>, ProgramError> {
+        self.flag_mut(name)?.examples.push(example.into());
+        Ok(self)
+    }
+
+    /// Check every value given for the flag with `validator` when the `Program` is parsed, which
+    /// gives back why a value is not valid, such as `must be a power of two`. Defaults are not
+    /// checked, as they are up to the program rather than the user.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_required_flag::<u32>("workers", "Number of workers")
+    ///     .unwrap()
+    ///     .with_validator::<u32, _>("workers", |workers| match workers {
+    ///         0 => Err("there must be at least one worker".to_string()),
+    ///         _ => Ok(()),
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert!(program.parse_from(["--workers", "0"]).is_err());
+    /// ```
+    pub fn with_validator<T, F>(
+        mut self,
+        name: &str,
+        validator: F,
+    ) -> Result<Program<'a>, ProgramError>
+    where
+        T: 'static,
+        F: Fn(&T) -> Result<(), String> + Send + Sync + 'static,
+    {
+        let flag = self.flag_mut(name)?;
+        if flag.type_id != TypeId::of::<T>() {
+            return Err(ProgramError::FlagTypeMismatch {
                 name: name.to_string(),
-            }),
+                expected: flag.type_name.to_string(),
+                requested: type_name::<T>().to_string(),
+            });
+        }
+
+        flag.validators.push(ValueValidator(Arc::new(move |value| {
+            value.downcast_ref::<T>().map_or(Ok(()), &validator)
+        })));
+        Ok(self)
+    }
+
+    /// Leave the flag's default out of the help text, which is useful for sensitive defaults such
+    /// as tokens, or ugly ones such as long generated paths.
+    pub fn with_default_hidden(mut self, name: &str) -> Result<Program<'a>, ProgramError> {
+        self.flag_mut(name)?.hide_default = true;
+        Ok(self)
+    }
+
+    /// Show something other than the flag's default in the help text, such as
+    /// `$HOME/.config/app` when the real default is the expanded path. The real default is still
+    /// what the flag is given when it is not on the command line.
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    ///
+    /// use commandrs::Program;
+    ///
+    /// let home = PathBuf::from(std::env::var("HOME").unwrap_or_default());
+    /// let program = Program::new()
+    ///     .with_optional_path_flag("config", home.join(".config/app"), "Config directory")
+    ///     .unwrap()
+    ///     .with_default_display("config", "$HOME/.config/app")
+    ///     .unwrap();
+    ///
+    /// let parsed = program.parse_from_strings(vec![]).unwrap();
+    /// assert_eq!(home.join(".config/app"), parsed.get_path("config").unwrap());
+    /// ```
+    pub fn with_default_display(
+        mut self,
+        name: &str,
+        display: impl Into<Cow<'a, str>>,
+    ) -> Result<Program<'a>, ProgramError> {
+        self.flag_mut(name)?.default_display = Some(display.into());
+        Ok(self)
+    }
+
+    /// Mark the flag as secret, such as a token which has a default. Its value is never shown in
+    /// the help text, error messages, `Program::to_args` or debug output, and it is prompted for
+    /// with echo disabled when it is required and not given.
+    pub fn with_secret(mut self, name: &str) -> Result<Program<'a>, ProgramError> {
+        self.flag_mut(name)?.is_secret = true;
+        if let Some(default) = self.flag_defaults.get_mut(name) {
+            default.is_secret = true;
         }
+        Ok(self)
+    }
+
+    /// Add a raw flag to the `Program`. Every argument after a raw flag is taken as one of its
+    /// values verbatim, even if it looks like a flag, such as `--cmd ls -la /tmp`.
+    ///
+    /// Raw flags are never required, and have no values when not given. Their values can be
+    /// extracted with `Program::get_raw`.
+    ///
+    /// The name must be unique.
+    pub fn with_raw_flag(
+        mut self,
+        name: impl Into<Cow<'a, str>>,
+        desc: impl Into<Cow<'a, str>>,
+    ) -> Result<Program<'a>, ProgramError> {
+        let name = name.into();
+        let parser = ValueParser(|s| Ok(Arc::new(vec![s.to_string()])));
+        let mut flag = Flag::with_parser::<Vec<String>>(name.clone(), desc.into(), false, parser);
+        flag.is_raw = true;
+
+        self = self.add_flag(flag)?;
+        self.flag_defaults
+            .insert(name.clone(), FlagValue::raw(name, vec![]));
+        Ok(self)
+    }
+
+    /// Graft every flag of another `Program` onto this one, along with their defaults. This lets
+    /// libraries ship pre-built bundles of flags which applications add to their own `Program`.
+    ///
+    /// The description and prefix of this `Program` are kept, and the flag names must still be
+    /// unique across both.
+    pub fn merge(mut self, other: Program<'a>) -> Result<Program<'a>, ProgramError> {
+        for flag in other.flags {
+            self = self.add_flag(flag)?;
+        }
+        self.flag_defaults.extend(other.flag_defaults);
+        Ok(self)
+    }
+
+    /// Merge the flags of another `Program` under a group name, so `host` in a `db` group is given
+    /// as `--db.host`. This lets nested config structs map naturally onto the command line.
+    ///
+    /// The group's flags can be extracted again with `Program::group`.
+    pub fn with_group(self, group: &str, other: Program<'a>) -> Result<Program<'a>, ProgramError> {
+        let mut grouped = Program::new();
+        for flag in other.flags {
+            let name = format!("{}.{}", group, flag.name);
+            let default = other.flag_defaults.get(&flag.name);
+            grouped = grouped.add_renamed_flag(flag, name, default, None)?;
+        }
+        self.merge(grouped)
+    }
+
+    /// Give back the flags of a group, and their parsed values, as their own `Program` with the
+    /// group name stripped, so `--db.host` can be extracted as `host`. This is useful for
+    /// extracting nested config structs with `Program::into_struct` or `Program::extract`.
+    pub fn group(&self, group: &str) -> Program<'a> {
+        let prefix = format!("{}.", group);
+        let mut program = Program::new().with_prefix(self.settings.prefix.clone());
+
+        // The names within a group are unique as the names with the group prefix are, so the flags
+        // can be inserted without checking for duplicates.
+        for flag in &self.flags {
+            if let Some(name) = flag.name.strip_prefix(prefix.as_str()) {
+                program.insert_renamed_flag(
+                    flag.clone(),
+                    name.to_string(),
+                    self.flag_defaults.get(&flag.name),
+                    self.flag_values.get(&flag.name),
+                );
+            }
+        }
+        program
+    }
+
+    /// Add a secret flag to the `Program`, such as a password or token. When it is not given, the
+    /// user is prompted for it on the terminal with echo disabled, and it is never shown in the
+    /// help text.
+    ///
+    /// Secret flags are required `String` flags, so parsing fails if they are not given and stdin
+    /// is not a terminal.
+    ///
+    /// The name must be unique.
+    pub fn with_secret_flag(
+        self,
+        name: impl Into<Cow<'a, str>>,
+        desc: impl Into<Cow<'a, str>>,
+    ) -> Result<Program<'a>, ProgramError> {
+        let mut flag = Flag::new::<String>(name.into(), desc.into(), true);
+        flag.is_secret = true;
+        self.add_flag(flag)
+    }
+
+    /// Add a boolean flag, such as `--yes`, which approves every `Program::confirm` without
+    /// prompting. This is handy for running tools in scripts.
+    ///
+    /// The name must be unique.
+    pub fn with_assume_yes_flag(
+        mut self,
+        name: impl Into<Cow<'a, str>>,
+        desc: impl Into<Cow<'a, str>>,
+    ) -> Result<Program<'a>, ProgramError> {
+        let name = name.into();
+        self = self.with_optional_flag::<bool>(name.clone(), false, desc)?;
+        self.assume_yes_flag = Some(name);
+        Ok(self)
+    }
+
+    /// Clear every parsed value, and the arguments they were parsed from, leaving only the flags
+    /// and their defaults. The `Program` can then be parsed again with fresh arguments, such as
+    /// when a long-running daemon re-reads its invocation.
+    pub fn reset(mut self) -> Program<'a> {
+        self.flag_values.clear();
+        self.raw_args.clear();
+        self.trailing_args.clear();
+        self.subcommand = None;
+        self.external_subcommand = None;
+        self
+    }
+
+    /// Extract the parsed value by its unique name. Values are parsed once when the `Program` is
+    /// parsed, so this only fails if the flag was registered as something other than `T` or not
+    /// registered at all.
+    ///
+    /// `T` only needs the bounds it was registered with, so types which are not `Display`, such
+    /// as `PathBuf`, can be extracted too.
+    pub fn get<T>(&self, name: &str) -> Result<T, ProgramError>
+    where
+        T: FromStr + Clone + 'static,
+    {
+        self.get_value::<T>(name).cloned()
+    }
+
+    /// A softer `Program::get`, giving back the fallback instead of an error when there is no
+    /// such flag, it has no value, or it was registered as something other than `T`.
+    pub fn get_or<T>(&self, name: &str, fallback: T) -> T
+    where
+        T: FromStr + Clone + 'static,
+    {
+        self.get::<T>(name).unwrap_or(fallback)
+    }
+
+    /// Whether the flag was explicitly given by the user, such as on the command line or when
+    /// prompted, rather than using its default. This is `false` for flags which do not exist.
+    pub fn is_present(&self, name: &str) -> bool {
+        self.flag_values
+            .get(name)
+            .is_some_and(|v| v.source != ValueSource::Default)
+    }
+
+    /// How many times the flag was given on the command line, such as `3` for
+    /// `--verbose --verbose --verbose`. This is `0` for flags which were not given, or do not
+    /// exist.
+    pub fn occurrences_of(&self, name: &str) -> usize {
+        self.flag_values
+            .get(name)
+            .map(|v| v.occurrences)
+            .unwrap_or(0)
+    }
+
+    /// Iterate over every flag with a value, in the order they were registered, giving back the
+    /// name, string value and where the value came from. This lets generic code, such as config
+    /// printers, walk the values without knowing the flag names up front.
+    pub fn values(&self) -> impl Iterator<Item = (&str, &str, ValueSource)> {
+        self.flags.iter().filter_map(|f| {
+            self.flag_values
+                .get(&f.name)
+                .map(|v| (f.name.as_ref(), v.str_value.as_str(), v.source))
+        })
+    }
+
+    /// The exact arguments the `Program` was parsed from, which is useful for error reporting,
+    /// audit logs, or running the tool again with the same arguments. These are empty when they
+    /// are not kept, with `Program::with_raw_args`.
+    pub fn raw_args(&self) -> &[String] {
+        &self.raw_args
+    }
+
+    /// Every argument after the `--` terminator, untouched, even if it looks like a flag. This is
+    /// for tools which run another command, such as `mytool run -- cargo test --release`.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_optional_flag::<bool>("watch", false, "Run again when files change")
+    ///     .unwrap()
+    ///     .parse_from_str_arr(&["--watch", "--", "cargo", "test", "--release"])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(["cargo", "test", "--release"], program.trailing_args());
+    /// ```
+    pub fn trailing_args(&self) -> &[String] {
+        &self.trailing_args
     }
 
     /// A wrapper for `Program::get`, but this does not need to be converted as command line
     /// arguments are already Strings.
-    pub fn get_string(&self, name: &'a str) -> Result<String, ProgramError> {
-        match self.flag_values.iter().find(|fv| fv.name == name) {
-            Some(flag_value) => Ok(flag_value.str_value.to_string()),
+    pub fn get_string(&self, name: &str) -> Result<String, ProgramError> {
+        self.get_str(name).map(str::to_string)
+    }
+
+    /// Borrow the value of a flag as it was given, without allocating like `Program::get_string`.
+    /// This works for a flag of any type, such as `"0x1F"` for a `u8` flag.
+    pub fn get_str(&self, name: &str) -> Result<&str, ProgramError> {
+        self.str_value_in(&self.flag_values, name)
+    }
+
+    /// Borrows the value of a flag from the `values`, as it was given.
+    pub(crate) fn str_value_in<'v>(
+        &self,
+        values: &'v FlagValues<'a>,
+        name: &str,
+    ) -> Result<&'v str, ProgramError> {
+        match values.get(name) {
+            Some(flag_value) => Ok(&flag_value.str_value),
             None => Err(ProgramError::NoSuchFlagExistsWithName {
                 name: name.to_string(),
             }),
         }
     }
 
-    fn add_flag<T: 'static>(
-        mut self,
-        name: &'a str,
-        desc: &'a str,
-        is_required: bool,
-    ) -> Result<Program<'a>, ProgramError> {
-        let already_has_flag_with_name = self.flags.iter().any(|f| f.name == name);
+    /// Extract every value given to a raw flag, untouched. If the raw flag was not given at all,
+    /// then there are no values.
+    pub fn get_raw(&self, name: &str) -> Result<Vec<String>, ProgramError> {
+        self.get_value::<Vec<String>>(name).cloned()
+    }
+
+    /// Borrows the parsed value of a flag, checking that `T` is the type the flag was registered
+    /// with first.
+    pub(crate) fn get_value<T: 'static>(&self, name: &str) -> Result<&T, ProgramError> {
+        self.value_in::<T>(&self.flag_values, name)
+    }
+
+    /// Borrows the parsed value of a flag from the `values`, checking that `T` is the type the
+    /// flag was registered with first.
+    pub(crate) fn value_in<'v, T: 'static>(
+        &self,
+        values: &'v FlagValues<'a>,
+        name: &str,
+    ) -> Result<&'v T, ProgramError> {
+        let no_such_flag = || ProgramError::NoSuchFlagExistsWithName {
+            name: name.to_string(),
+        };
+        let flag = self.flag(name).ok_or_else(no_such_flag)?;
+        let type_mismatch = || ProgramError::FlagTypeMismatch {
+            name: name.to_string(),
+            expected: flag.type_name.to_string(),
+            requested: type_name::<T>().to_string(),
+        };
+
+        if flag.type_id != TypeId::of::<T>() {
+            return Err(type_mismatch());
+        }
+
+        values
+            .get(name)
+            .ok_or_else(no_such_flag)?
+            .value
+            .downcast_ref::<T>()
+            .ok_or_else(type_mismatch)
+    }
+
+    pub(crate) fn flag(&self, name: &str) -> Option<&Flag<'a>> {
+        self.flag_indices.get(name).and_then(|&i| self.flags.get(i))
+    }
+
+    pub(crate) fn flag_mut(&mut self, name: &str) -> Result<&mut Flag<'a>, ProgramError> {
+        let flag = self
+            .flag_indices
+            .get(name)
+            .and_then(|&i| self.flags.get_mut(i));
+        flag.ok_or_else(|| ProgramError::NoSuchFlagExistsWithName {
+            name: name.to_string(),
+        })
+    }
+
+    fn add_flag(mut self, flag: Flag<'a>) -> Result<Program<'a>, ProgramError> {
+        let already_has_flag_with_name = self.flag_indices.contains_key(&flag.name);
         if already_has_flag_with_name {
             // Flag names cannot be duplicate, if they are then there would be no way to parse the
             // arguments on the command line and understand which flag we want.
             return Err(ProgramError::FlagAlreadyExistsWithName {
-                name: name.to_string(),
+                name: flag.name.to_string(),
             });
         }
 
-        let type_id = TypeId::of::<T>();
-        self.flags.push(Flag {
-            name,
-            desc,
-            type_id,
-            is_required,
-        });
+        self.flag_indices
+            .insert(flag.name.clone(), self.flags.len());
+        self.flags.push(flag);
         Ok(self)
     }
 
-    /// Attempts to acquire the default value for a flag by name. The reason for the "unwrap" prefix
-    /// is to indicate that this will call `unwrap` instead of handling `Option<FlagValue>`
-    /// correctly. The assumption is made that the caller will only use this when a default flag can
-    /// be used.
-    pub(crate) fn unwrap_default_flag_value(&self, name: &str) -> &String {
-        &self
-            .flag_defaults
-            .iter()
-            .find(|fv| fv.name == name)
-            .unwrap()
-            .str_value
+    fn add_renamed_flag(
+        mut self,
+        flag: Flag<'a>,
+        name: String,
+        default: Option<&FlagValue<'a>>,
+        value: Option<&FlagValue<'a>>,
+    ) -> Result<Program<'a>, ProgramError> {
+        if self.flag_indices.contains_key(name.as_str()) {
+            return Err(ProgramError::FlagAlreadyExistsWithName { name });
+        }
+        self.insert_renamed_flag(flag, name, default, value);
+        Ok(self)
+    }
+
+    /// Adds the flag under a new name, along with its default and value, replacing any flag which
+    /// already has the name.
+    fn insert_renamed_flag(
+        &mut self,
+        mut flag: Flag<'a>,
+        name: String,
+        default: Option<&FlagValue<'a>>,
+        value: Option<&FlagValue<'a>>,
+    ) {
+        let name: Cow<'a, str> = Cow::Owned(name);
+        let renamed = |value: &FlagValue<'a>| FlagValue {
+            name: name.clone(),
+            ..value.clone()
+        };
+
+        if let Some(default) = default {
+            self.flag_defaults.insert(name.clone(), renamed(default));
+        }
+        if let Some(value) = value {
+            self.flag_values.insert(name.clone(), renamed(value));
+        }
+        flag.name = name.clone();
+        match self.flag_mut(&name) {
+            Ok(existing) => *existing = flag,
+            Err(_) => {
+                self.flag_indices.insert(name, self.flags.len());
+                self.flags.push(flag);
+            }
+        }
+    }
+
+    /// The default value of an optional flag. Every optional flag is given a default when it is
+    /// added, so this only fails if that has somehow been missed, which is an error in the
+    /// definition rather than something a user could cause.
+    pub(crate) fn default_flag(&self, name: &str) -> Result<&FlagValue<'a>, ProgramError> {
+        self.flag_defaults
+            .get(name)
+            .ok_or_else(|| ProgramError::InvalidFlagDefinition {
+                name: name.to_string(),
+                reason: "optional flags must have a default".to_string(),
+            })
     }
 }
 
@@ -146,10 +873,12 @@ mod tests {
     #[test]
     fn should_add_description_when_using_with_description() {
         let expected = Program {
-            desc: "A very cool test program",
+            desc: "A very cool test program".into(),
             flags: vec![],
-            flag_defaults: vec![],
-            flag_values: vec![],
+            flag_indices: HashMap::new(),
+            flag_defaults: HashMap::new(),
+            flag_values: HashMap::new(),
+            ..Program::default()
         };
 
         let builder = Program::default().with_description("A very cool test program");
@@ -160,38 +889,30 @@ mod tests {
     #[test]
     fn should_add_optional_flags_when_calling_with_optional_flag_multiple_times() {
         let expected = Program {
-            desc: "",
+            desc: "".into(),
             flags: vec![
-                Flag {
-                    name: "flag0",
-                    desc: "Zero-th flag",
-                    type_id: TypeId::of::<bool>(),
-                    is_required: false,
-                },
-                Flag {
-                    name: "flag1",
-                    desc: "First flag",
-                    type_id: TypeId::of::<&str>(),
-                    is_required: false,
-                },
-            ],
-            flag_defaults: vec![
-                FlagValue {
-                    name: "flag0",
-                    str_value: "false".to_string(),
-                },
-                FlagValue {
-                    name: "flag1",
-                    str_value: "lol".to_string(),
-                },
+                Flag::new::<bool>("flag0", "Zero-th flag", false),
+                Flag::new::<String>("flag1", "First flag", false),
             ],
-            flag_values: vec![],
+            flag_indices: HashMap::from([("flag0".into(), 0), ("flag1".into(), 1)]),
+            flag_defaults: HashMap::from([
+                (
+                    "flag0".into(),
+                    FlagValue::new("flag0", "false".to_string(), false),
+                ),
+                (
+                    "flag1".into(),
+                    FlagValue::new("flag1", "lol".to_string(), "lol".to_string()),
+                ),
+            ]),
+            flag_values: HashMap::new(),
+            ..Program::default()
         };
 
         let program = Program::new()
             .with_optional_flag("flag0", false, "Zero-th flag")
             .unwrap()
-            .with_optional_flag("flag1", "lol", "First flag")
+            .with_optional_flag("flag1", "lol".to_string(), "First flag")
             .unwrap();
 
         assert_eq!(expected, program);
@@ -200,40 +921,332 @@ mod tests {
     #[test]
     fn should_add_required_flags_when_calling_with_required_flag_multiple_times() {
         let expected = Program {
-            desc: "",
+            desc: "".into(),
             flags: vec![
-                Flag {
-                    name: "flag0",
-                    desc: "Zero-th flag",
-                    type_id: TypeId::of::<bool>(),
-                    is_required: true,
-                },
-                Flag {
-                    name: "flag1",
-                    desc: "First flag",
-                    type_id: TypeId::of::<&str>(),
-                    is_required: true,
-                },
+                Flag::new::<bool>("flag0", "Zero-th flag", true),
+                Flag::new::<String>("flag1", "First flag", true),
             ],
-            flag_defaults: vec![],
-            flag_values: vec![],
+            flag_indices: HashMap::from([("flag0".into(), 0), ("flag1".into(), 1)]),
+            flag_defaults: HashMap::new(),
+            flag_values: HashMap::new(),
+            ..Program::default()
         };
 
         let program = Program::new()
             .with_required_flag::<bool>("flag0", "Zero-th flag")
             .unwrap()
-            .with_required_flag::<&str>("flag1", "First flag")
+            .with_required_flag::<String>("flag1", "First flag")
             .unwrap();
 
         assert_eq!(expected, program);
     }
 
+    #[test]
+    fn should_add_flags_with_owned_names_and_descriptions() {
+        let plugins = ["compress", "encrypt"];
+
+        let program = plugins
+            .iter()
+            .fold(
+                Program::new().with_description(format!("{} plugins", plugins.len())),
+                |program, plugin| {
+                    program
+                        .with_optional_flag::<bool>(
+                            format!("enable-{}", plugin),
+                            false,
+                            format!("Enable the {} plugin", plugin),
+                        )
+                        .unwrap()
+                },
+            )
+            .parse_from_str_arr(&["--enable-encrypt"])
+            .unwrap();
+
+        assert_eq!("2 plugins", program.desc);
+        assert!(!program.get::<bool>("enable-compress").unwrap());
+        assert!(program.get::<bool>("enable-encrypt").unwrap());
+    }
+
+    #[test]
+    fn should_add_secret_flags_as_required_strings() {
+        let program = Program::new()
+            .with_secret_flag("password", "Password of the user")
+            .unwrap()
+            .parse_from_str_arr(&["--password", "hunter2"])
+            .unwrap();
+
+        assert!(program.flags[0].is_secret);
+        assert!(program.flags[0].is_required);
+        assert_eq!("hunter2", program.get::<String>("password").unwrap());
+    }
+
+    #[test]
+    fn should_redact_secret_values_in_debug_output() {
+        let program = Program::new()
+            .with_secret_flag("password", "Password of the user")
+            .unwrap()
+            .with_optional_flag("token", "t0ken".to_string(), "API token")
+            .unwrap()
+            .with_secret("token")
+            .unwrap()
+            .parse_from_str_arr(&["mytool", "--password", "hunter2"])
+            .unwrap();
+
+        let debug = format!("{:?}", program);
+
+        assert!(!debug.contains("hunter2"));
+        assert!(!debug.contains("t0ken"));
+        assert!(debug.contains(r#"raw_args: ["mytool", "--password", "****"]"#));
+        assert_eq!("t0ken", program.get::<String>("token").unwrap());
+    }
+
+    #[test]
+    fn should_redact_secret_values_of_subcommands_in_debug_output() {
+        let program = Program::new()
+            .with_subcommand(
+                "login",
+                Program::new()
+                    .with_secret_flag("password", "Password of the user")
+                    .unwrap(),
+            )
+            .unwrap()
+            .parse_from_str_arr(&["login", "--password", "hunter2"])
+            .unwrap();
+
+        let debug = format!("{:?}", program);
+
+        assert!(!debug.contains("hunter2"));
+        assert!(debug.contains(r#"raw_args: ["login", "--password", "****"]"#));
+    }
+
+    #[test]
+    fn should_redact_every_secret_value_by_position_in_debug_output() {
+        let program = Program::new()
+            .with_secret_flag("token", "API token")
+            .unwrap()
+            .with_optional_flag("name", String::new(), "Name of the key")
+            .unwrap()
+            .parse_from_str_arr(&["--token", "t0ken", "--name", "first-secret", "--token", "e"])
+            .unwrap();
+
+        let debug = format!("{:?}", program);
+
+        assert!(debug.contains(
+            r#"raw_args: ["--token", "****", "--name", "first-secret", "--token", "****"]"#
+        ));
+    }
+
+    #[test]
+    fn should_redact_secret_values_in_errors() {
+        let err = Program::new()
+            .with_required_flag::<u32>("pin", "PIN of the card")
+            .unwrap()
+            .with_secret("pin")
+            .unwrap()
+            .parse_from_str_arr(&["--pin", "12a4"])
+            .unwrap_err();
+
+        assert_eq!(Some("****"), err.value());
+        assert!(!format!("{:?}", err).contains("12a4"));
+    }
+
+    #[test]
+    fn should_only_be_present_when_flag_was_given() {
+        let program = Program::new()
+            .with_optional_flag::<bool>("use-tls", false, "TLS PLS?")
+            .unwrap()
+            .with_optional_flag::<bool>("verbose", false, "Log everything")
+            .unwrap()
+            .parse_from_str_arr(&["--use-tls", "false"])
+            .unwrap();
+
+        assert!(program.is_present("use-tls"));
+        assert!(!program.is_present("verbose"));
+        assert!(!program.is_present("unknown"));
+        assert_eq!(
+            program.get::<bool>("use-tls").unwrap(),
+            program.get::<bool>("verbose").unwrap()
+        );
+    }
+
+    #[test]
+    fn should_use_fallback_with_get_or_only_when_flag_cannot_be_got() {
+        let program = Program::new()
+            .with_optional_flag::<u16>("port", 8080, "Port number")
+            .unwrap()
+            .parse_from_str_arr(&["--port", "443"])
+            .unwrap();
+
+        assert_eq!(443, program.get_or::<u16>("port", 80));
+        assert_eq!(80, program.get_or::<u16>("unknown", 80));
+        assert_eq!("80", program.get_or::<String>("port", "80".to_string()));
+    }
+
+    #[test]
+    fn should_iterate_over_values_in_registration_order() {
+        let program = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<String>("host", "localhost".to_string(), "Host to bind")
+            .unwrap()
+            .parse_from_str_arr(&["--port", "8080"])
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                ("port", "8080", ValueSource::CommandLine),
+                ("host", "localhost", ValueSource::Default),
+            ],
+            program.values().collect::<Vec<_>>()
+        );
+        assert_eq!(0, Program::new().values().count());
+    }
+
+    #[test]
+    fn should_have_no_values_after_reset_and_parse_again() {
+        let definition = Program::new()
+            .with_optional_flag::<u16>("port", 8080, "Port number")
+            .unwrap();
+
+        let program = definition
+            .clone()
+            .parse_from_str_arr(&["--port", "443"])
+            .unwrap()
+            .reset();
+
+        assert_eq!(definition, program);
+        assert_eq!(
+            8080,
+            program
+                .parse_from_str_arr(&[])
+                .unwrap()
+                .get::<u16>("port")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn should_share_parsed_program_between_threads() {
+        let program = Program::new()
+            .with_optional_flag::<u16>("port", 8080, "Port number")
+            .unwrap()
+            .parse_from_str_arr(&["--port", "443"])
+            .unwrap();
+
+        let ports: Vec<u16> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| s.spawn(|| program.get::<u16>("port").unwrap()))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let cloned = program.clone();
+
+        assert_eq!(vec![443; 4], ports);
+        assert_eq!(program, cloned);
+    }
+
+    #[test]
+    fn should_allow_hyphen_values_for_registered_flags() {
+        let program = Program::new()
+            .with_required_flag::<String>("pattern", "Pattern to search for")
+            .unwrap()
+            .with_hyphen_values_allowed("pattern")
+            .unwrap();
+
+        assert!(program.flags[0].allow_hyphen_values);
+    }
+
+    #[test]
+    fn should_not_be_able_to_allow_hyphen_values_for_unknown_flags() {
+        let err = Program::new()
+            .with_hyphen_values_allowed("pattern")
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::NoSuchFlagExistsWithName {
+                name: "pattern".to_string()
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn should_get_values_of_types_which_are_not_display() {
+        #[derive(Clone, Debug, PartialEq)]
+        struct Tags(Vec<String>);
+
+        impl FromStr for Tags {
+            type Err = std::convert::Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(Tags(s.split(',').map(str::to_string).collect()))
+            }
+        }
+
+        let program = Program::new()
+            .with_required_flag::<Tags>("tags", "Tags to filter by")
+            .unwrap()
+            .with_required_flag::<std::path::PathBuf>("out", "Where to write to")
+            .unwrap()
+            .parse_from_str_arr(&["--tags", "a,b", "--out", "target"])
+            .unwrap();
+
+        assert_eq!(
+            Tags(vec!["a".to_string(), "b".to_string()]),
+            program.get::<Tags>("tags").unwrap()
+        );
+        assert_eq!(
+            std::path::PathBuf::from("target"),
+            program.get::<std::path::PathBuf>("out").unwrap()
+        );
+    }
+
+    #[test]
+    fn should_borrow_values_as_they_were_given() {
+        let program = Program::new()
+            .with_required_flag::<u8>("mask", "Bit mask")
+            .unwrap()
+            .with_optional_flag::<String>("host", "localhost".to_string(), "Host to bind")
+            .unwrap()
+            .parse_from_str_arr(&["--mask", "0x1F"])
+            .unwrap();
+
+        assert_eq!("0x1F", program.get_str("mask").unwrap());
+        assert_eq!("localhost", program.get_str("host").unwrap());
+        assert_eq!(
+            ProgramError::NoSuchFlagExistsWithName {
+                name: "port".to_string()
+            },
+            program.get_str("port").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn should_result_in_an_error_when_getting_a_flag_as_the_wrong_type() {
+        let err = Program::new()
+            .with_required_flag::<String>("port", "Port number")
+            .unwrap()
+            .parse_from_str_arr(&["--port", "8080"])
+            .unwrap()
+            .get::<u16>("port")
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::FlagTypeMismatch {
+                name: "port".to_string(),
+                expected: "alloc::string::String".to_string(),
+                requested: "u16".to_string(),
+            },
+            err
+        );
+    }
+
     #[test]
     fn should_not_be_able_to_add_flags_with_the_same_name() {
         let err = Program::new()
             .with_required_flag::<bool>("oh-noes", "Ruh roh")
             .unwrap()
-            .with_required_flag::<&str>("oh-noes", "Ruh roh")
+            .with_required_flag::<String>("oh-noes", "Ruh roh")
             .unwrap_err();
 
         assert_eq!(
@@ -243,4 +1256,98 @@ mod tests {
             err
         );
     }
+
+    #[test]
+    fn should_not_be_able_to_add_optional_flags_with_defaults_which_do_not_parse() {
+        /// Displays with a unit which it can't be parsed back from.
+        #[derive(Clone)]
+        struct Percent(u8);
+
+        impl FromStr for Percent {
+            type Err = std::num::ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map(Percent)
+            }
+        }
+
+        impl Display for Percent {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}%", self.0)
+            }
+        }
+
+        let err = Program::new()
+            .with_optional_flag("volume", Percent(50), "How loud to play")
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::InvalidFlagDefinition {
+                name: "volume".to_string(),
+                reason: "the default does not parse as the type of the flag".to_string(),
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn should_add_flags_and_defaults_of_other_program_when_merging() {
+        let logging = Program::new()
+            .with_optional_flag::<bool>("verbose", false, "Log everything")
+            .unwrap();
+
+        let program = Program::new()
+            .with_description("An HTTP server")
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .merge(logging)
+            .unwrap()
+            .parse_from_str_arr(&["--port", "8080"])
+            .unwrap();
+
+        assert_eq!("An HTTP server", program.desc);
+        assert_eq!(8080, program.get::<u16>("port").unwrap());
+        assert!(!program.get::<bool>("verbose").unwrap());
+    }
+
+    #[test]
+    fn should_not_be_able_to_merge_programs_with_the_same_flags() {
+        let logging = || {
+            Program::new()
+                .with_optional_flag::<bool>("verbose", false, "Log everything")
+                .unwrap()
+        };
+
+        let err = logging().merge(logging()).unwrap_err();
+
+        assert_eq!(
+            ProgramError::FlagAlreadyExistsWithName {
+                name: "verbose".to_string()
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn should_prefix_flags_with_group_name_and_strip_it_when_extracting() {
+        let db = Program::new()
+            .with_required_flag::<String>("host", "Database host")
+            .unwrap()
+            .with_optional_flag::<u16>("port", 5432, "Database port")
+            .unwrap();
+
+        let program = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_group("db", db)
+            .unwrap()
+            .parse_from_str_arr(&["--port", "8080", "--db.host", "localhost"])
+            .unwrap();
+        let db = program.group("db");
+
+        assert_eq!(8080, program.get::<u16>("port").unwrap());
+        assert_eq!("localhost", program.get::<String>("db.host").unwrap());
+        assert_eq!("localhost", db.get::<String>("host").unwrap());
+        assert_eq!(5432, db.get::<u16>("port").unwrap());
+    }
 }
@@ -0,0 +1,190 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use crate::error::ProgramError;
+use crate::flag::{Flag, FlagValue, ValueSource};
+use crate::strings::Strings;
+use crate::Program;
+
+impl<'a> Program<'a> {
+    /// Prompt on the terminal for the value of a required flag which was not given, using the
+    /// flag's description as the prompt. Only when prompting is enabled, or the flag is secret, and
+    /// stdin is a terminal, otherwise this gives back `None` so the flag is treated as missing.
+    ///
+    /// Secret flags are read with echo disabled.
+    pub(crate) fn prompt_for_flag(
+        &self,
+        flag: &Flag<'a>,
+    ) -> Option<Result<FlagValue<'a>, ProgramError>> {
+        if !(self.settings.prompt_for_missing_flags || flag.is_secret) || !io::stdin().is_terminal()
+        {
+            return None;
+        }
+
+        let prompt = self.flag_prompt(flag);
+        let answer = if flag.is_secret {
+            read_secret(&prompt)?
+        } else {
+            read_answer(&mut io::stdin().lock(), &mut io::stderr(), &prompt).ok()??
+        };
+        Some(self.parse_answer(flag, answer))
+    }
+
+    /// Ask the user to confirm something, such as deleting files, giving back whether they
+    /// approved. This is approved without prompting when the flag added with
+    /// `Program::with_assume_yes_flag` was given, and denied when stdin is not a terminal.
+    ///
+    /// ```no_run
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_assume_yes_flag("yes", "Assume yes to every question")?
+    ///     .parse()?;
+    ///
+    /// if program.confirm("Delete every file?")? {
+    ///     // ...
+    /// }
+    /// # Ok::<(), commandrs::error::ProgramError>(())
+    /// ```
+    pub fn confirm(&self, prompt: &str) -> Result<bool, ProgramError> {
+        if let Some(name) = &self.assume_yes_flag {
+            if self.get::<bool>(name)? {
+                return Ok(true);
+            }
+        }
+
+        if !io::stdin().is_terminal() {
+            return Ok(false);
+        }
+
+        let strings = self.strings.0.as_ref();
+        Ok(
+            read_confirmation(&mut io::stdin().lock(), &mut io::stderr(), prompt, strings)
+                .unwrap_or(false),
+        )
+    }
+
+    fn flag_prompt(&self, flag: &Flag<'a>) -> String {
+        if flag.desc.is_empty() {
+            format!("{}{}: ", self.settings.prefix, flag.name)
+        } else {
+            format!("{} ({}{}): ", flag.desc, self.settings.prefix, flag.name)
+        }
+    }
+
+    fn parse_answer(&self, flag: &Flag<'a>, answer: String) -> Result<FlagValue<'a>, ProgramError> {
+        FlagValue::parse(flag, answer).map(|v| v.with_source(ValueSource::Prompt))
+    }
+}
+
+/// Reads a secret with echo disabled, giving back `None` for an empty answer.
+#[cfg(any(unix, windows))]
+fn read_secret(prompt: &str) -> Option<String> {
+    rpassword::prompt_password(prompt)
+        .ok()
+        .filter(|a| !a.is_empty())
+}
+
+/// Echo can't be disabled without a terminal, so secrets are never prompted for.
+#[cfg(not(any(unix, windows)))]
+fn read_secret(_prompt: &str) -> Option<String> {
+    None
+}
+
+/// Writes the prompt and reads one line as the answer, without its line ending. An empty answer,
+/// or the input ending, gives back `None`.
+fn read_answer(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    prompt: &str,
+) -> io::Result<Option<String>> {
+    write!(output, "{}", prompt)?;
+    output.flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    let answer = answer.trim_end_matches(['\r', '\n']);
+
+    Ok(Some(answer.to_string()).filter(|a| !a.is_empty()))
+}
+
+/// Asks a yes or no question, where anything other than a yes is a no.
+fn read_confirmation(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    prompt: &str,
+    strings: &dyn Strings,
+) -> io::Result<bool> {
+    let prompt = format!("{} {} ", prompt, strings.confirm_choices());
+    let answer = read_answer(input, output, &prompt)?;
+    Ok(answer.is_some_and(|a| strings.is_yes(&a)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::strings::EnglishStrings;
+
+    #[test]
+    fn should_write_prompt_and_read_answer_line() {
+        let mut output = vec![];
+
+        let answer = read_answer(&mut Cursor::new("8080\nmore\n"), &mut output, "Port: ").unwrap();
+
+        assert_eq!(Some("8080".to_string()), answer);
+        assert_eq!("Port: ", String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn should_not_have_an_answer_when_it_is_empty() {
+        assert_eq!(
+            None,
+            read_answer(&mut Cursor::new("\r\n"), &mut vec![], "Port: ").unwrap()
+        );
+        assert_eq!(
+            None,
+            read_answer(&mut Cursor::new(""), &mut vec![], "Port: ").unwrap()
+        );
+    }
+
+    #[test]
+    fn should_only_confirm_when_answer_is_yes() {
+        let mut output = vec![];
+        let confirm = |input: &str, output: &mut Vec<u8>| {
+            read_confirmation(&mut Cursor::new(input), output, "Delete?", &EnglishStrings).unwrap()
+        };
+
+        assert!(confirm("y\n", &mut output));
+        assert!(confirm("YES\n", &mut vec![]));
+        assert!(!confirm("\n", &mut vec![]));
+        assert!(!confirm("nope\n", &mut vec![]));
+        assert_eq!("Delete? [y/N] ", String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn should_confirm_without_prompting_when_assume_yes_flag_is_given() {
+        let program = Program::new()
+            .with_assume_yes_flag("yes", "Assume yes to every question")
+            .unwrap()
+            .parse_from_str_arr(&["--yes"])
+            .unwrap();
+
+        assert!(program.confirm("Delete every file?").unwrap());
+    }
+
+    #[test]
+    fn should_use_flag_description_as_prompt() {
+        let program = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_required_flag::<String>("host", "")
+            .unwrap();
+
+        assert_eq!(
+            "Port number (--port): ",
+            program.flag_prompt(&program.flags[0])
+        );
+        assert_eq!("--host: ", program.flag_prompt(&program.flags[1]));
+    }
+}
@@ -0,0 +1,160 @@
+//! Ranges such as `1..100` or `8000-8100`, for tools which take port, line or ID ranges.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A range of values, given as `start..end`, which does not include its end, or as
+/// `start..=end` or `start-end`, which do. The start can't come after the end.
+///
+/// It is shown as `start..end` or `start..=end`, such as in the help text for defaults.
+///
+/// ```
+/// use commandrs::range::ValueRange;
+/// use commandrs::Program;
+///
+/// let program = Program::new()
+///     .with_required_flag::<ValueRange<u16>>("ports", "Ports to scan")
+///     .unwrap()
+///     .parse_from_str_arr(&["--ports", "8000-8100"])
+///     .unwrap();
+///
+/// let ports = program.get::<ValueRange<u16>>("ports").unwrap();
+/// assert!(ports.contains(&8100));
+/// assert_eq!(8000..=8100, ports.start..=ports.end);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ValueRange<T> {
+    pub start: T,
+    pub end: T,
+    /// Whether the end is part of the range.
+    pub inclusive: bool,
+}
+
+impl<T: PartialOrd> ValueRange<T> {
+    /// Whether the value is within the range.
+    pub fn contains(&self, value: &T) -> bool {
+        &self.start <= value && (value < &self.end || self.inclusive && value == &self.end)
+    }
+}
+
+impl<T> FromStr for ValueRange<T>
+where
+    T: FromStr + PartialOrd,
+    T::Err: Display,
+{
+    type Err = ParseRangeError;
+
+    fn from_str(s: &str) -> Result<ValueRange<T>, ParseRangeError> {
+        let error = |reason: String| ParseRangeError { reason };
+
+        let (start, end, inclusive) = if let Some((start, end)) = s.split_once("..") {
+            match end.strip_prefix('=') {
+                Some(end) => (start, end, true),
+                None => (start, end, false),
+            }
+        } else {
+            // The first character is skipped, so the start of the range can be negative.
+            let split = s
+                .char_indices()
+                .skip(1)
+                .find(|(_, c)| *c == '-')
+                .map(|(i, _)| i)
+                .ok_or_else(|| error("expected a range such as 1..10 or 1-10".to_string()))?;
+            (&s[..split], &s[split + 1..], true)
+        };
+
+        let start: T = start
+            .parse()
+            .map_err(|err| error(format!("invalid start of the range, {}", err)))?;
+        let end: T = end
+            .parse()
+            .map_err(|err| error(format!("invalid end of the range, {}", err)))?;
+        if start > end {
+            return Err(error("the start of the range is after its end".to_string()));
+        }
+
+        Ok(ValueRange {
+            start,
+            end,
+            inclusive,
+        })
+    }
+}
+
+impl<T: Display> Display for ValueRange<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let separator = if self.inclusive { "..=" } else { ".." };
+        write!(f, "{}{}{}", self.start, separator, self.end)
+    }
+}
+
+/// Why a `ValueRange` could not be parsed.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseRangeError {
+    reason: String,
+}
+
+impl Display for ParseRangeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl Error for ParseRangeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<ValueRange<i32>, String> {
+        s.parse::<ValueRange<i32>>().map_err(|err| err.to_string())
+    }
+
+    fn range(start: i32, end: i32, inclusive: bool) -> ValueRange<i32> {
+        ValueRange {
+            start,
+            end,
+            inclusive,
+        }
+    }
+
+    #[test]
+    fn should_parse_ranges_in_every_form() {
+        assert_eq!(Ok(range(1, 100, false)), parse("1..100"));
+        assert_eq!(Ok(range(1, 100, true)), parse("1..=100"));
+        assert_eq!(Ok(range(10, 20, true)), parse("10-20"));
+        assert_eq!(Ok(range(-5, 5, true)), parse("-5-5"));
+        assert_eq!(Ok(range(-10, -5, false)), parse("-10..-5"));
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_invalid_ranges() {
+        assert_eq!(
+            Err("expected a range such as 1..10 or 1-10".to_string()),
+            parse("10")
+        );
+        assert_eq!(
+            Err("invalid end of the range, invalid digit found in string".to_string()),
+            parse("1..x")
+        );
+        assert_eq!(
+            Err("the start of the range is after its end".to_string()),
+            parse("20-10")
+        );
+    }
+
+    #[test]
+    fn should_only_contain_the_end_of_inclusive_ranges() {
+        assert!(range(1, 10, true).contains(&10));
+        assert!(!range(1, 10, false).contains(&10));
+        assert!(range(1, 10, false).contains(&1));
+        assert!(!range(1, 10, false).contains(&0));
+    }
+
+    #[test]
+    fn should_display_what_it_parses() {
+        assert_eq!("1..100", range(1, 100, false).to_string());
+        assert_eq!("10..=20", parse("10-20").unwrap().to_string());
+    }
+}
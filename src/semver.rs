@@ -0,0 +1,86 @@
+use ::semver::{Version, VersionReq};
+
+use crate::error::ProgramError;
+use crate::Program;
+
+impl<'a> Program<'a> {
+    /// Only accept versions matching the requirement for a `semver::Version` flag, such as
+    /// `>=1.4`, so an unsupported version is rejected when the `Program` is parsed. Versions can
+    /// also be compared once they are fetched, as `semver::Version` is ordered.
+    ///
+    /// This is only available with the `semver` feature.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    /// use semver::{Version, VersionReq};
+    ///
+    /// let program = Program::new()
+    ///     .with_required_flag::<Version>("min-version", "Oldest release to include")
+    ///     .unwrap()
+    ///     .with_version_req("min-version", VersionReq::parse(">=1.4").unwrap())
+    ///     .unwrap();
+    ///
+    /// let parsed = program.parse_from(["--min-version", "1.4.2"]).unwrap();
+    /// assert!(parsed.get::<Version>("min-version").unwrap() > Version::new(1, 4, 0));
+    /// assert!(program.parse_from(["--min-version", "1.3.0"]).is_err());
+    /// ```
+    pub fn with_version_req(
+        self,
+        name: &str,
+        req: VersionReq,
+    ) -> Result<Program<'a>, ProgramError> {
+        self.with_validator::<Version, _>(name, move |version| {
+            if req.matches(version) {
+                return Ok(());
+            }
+            Err(format!("the version must match {}", req))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program() -> Program<'static> {
+        Program::new()
+            .with_required_flag::<Version>("min-version", "Oldest release to include")
+            .unwrap()
+            .with_version_req("min-version", VersionReq::parse(">=1.4, <2").unwrap())
+            .unwrap()
+    }
+
+    #[test]
+    fn should_accept_versions_matching_the_requirement() {
+        let program = program()
+            .parse_from_str_arr(&["--min-version", "1.10.0"])
+            .unwrap();
+
+        let version = program.get::<Version>("min-version").unwrap();
+        assert_eq!(Version::new(1, 10, 0), version);
+        assert!(version > Version::new(1, 9, 0));
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_versions_not_matching_the_requirement() {
+        let err = program()
+            .parse_from(["--min-version", "2.0.0"])
+            .unwrap_err();
+
+        assert_eq!(
+            "Value 2.0.0 is not valid for --min-version, the version must match >=1.4, <2",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_values_which_are_not_versions() {
+        let err = program().parse_from(["--min-version", "1.4"]).unwrap_err();
+
+        assert_eq!(
+            "Could not parse min-version as type of semver::Version, unexpected end of input \
+            while parsing minor version number",
+            err.to_string()
+        );
+    }
+}
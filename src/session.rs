@@ -0,0 +1,108 @@
+use std::io::BufRead;
+
+use crate::error::ProgramError;
+use crate::matches::Matches;
+use crate::tokenizer::tokenize;
+use crate::Program;
+
+/// Parses a `Program` repeatedly against successive lines of input, such as the commands typed
+/// into an interactive shell. Every line is parsed into `Matches` of its own, so nothing given on
+/// one line is remembered for the next.
+///
+/// ```
+/// use commandrs::Program;
+///
+/// let session = Program::new()
+///     .with_optional_flag::<u32>("times", 1, "Times to say hello")
+///     .unwrap()
+///     .into_session();
+///
+/// let times: Vec<u32> = session
+///     .lines("--times 3\n\n".as_bytes())
+///     .map(|program| program.unwrap().get::<u32>("times").unwrap())
+///     .collect();
+///
+/// assert_eq!(vec![3, 1], times);
+/// ```
+#[derive(Debug)]
+pub struct Session<'a> {
+    program: Program<'a>,
+}
+
+impl<'a> Session<'a> {
+    /// Parse one line of input, which is split into arguments with `tokenize`.
+    pub fn parse_line(&self, line: &str) -> Result<Matches<'_, 'a>, ProgramError> {
+        self.program.parse_from(tokenize(line))
+    }
+
+    /// Parse every line of the input in turn, stopping when it ends or can't be read.
+    pub fn lines<'s>(
+        &'s self,
+        input: impl BufRead + 's,
+    ) -> impl Iterator<Item = Result<Matches<'s, 'a>, ProgramError>> + 's {
+        input
+            .lines()
+            .map_while(Result::ok)
+            .map(|line| self.parse_line(&line))
+    }
+}
+
+impl<'a> Program<'a> {
+    /// Turn the `Program` into a `Session`, for parsing it against many lines of input.
+    pub fn into_session(self) -> Session<'a> {
+        Session { program: self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ValueParseError;
+
+    use super::*;
+
+    #[test]
+    fn should_not_remember_values_between_lines() {
+        let session = Program::new()
+            .with_optional_flag::<bool>("verbose", false, "Log everything")
+            .unwrap()
+            .into_session();
+
+        assert!(session
+            .parse_line("--verbose")
+            .unwrap()
+            .get::<bool>("verbose")
+            .unwrap());
+        assert!(!session
+            .parse_line("")
+            .unwrap()
+            .get::<bool>("verbose")
+            .unwrap());
+    }
+
+    #[test]
+    fn should_keep_parsing_lines_after_an_error() {
+        let session = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .into_session();
+
+        let ports: Vec<Result<u16, ProgramError>> = session
+            .lines("--port nope\n--port 8080\n".as_bytes())
+            .map(|program| program.and_then(|p| p.get::<u16>("port")))
+            .collect();
+
+        assert_eq!(
+            vec![
+                Err(ProgramError::FailedToParseFlagValue {
+                    name: "port".to_string(),
+                    type_name: "u16".to_string(),
+                    value: "nope".to_string(),
+                    index: Some(1),
+                    source: ValueParseError::new("nope".parse::<u16>().unwrap_err()),
+                }),
+                Ok(8080)
+            ],
+            ports
+        );
+    }
+}
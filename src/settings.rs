@@ -0,0 +1,149 @@
+//! Everything which changes how a `Program` parses arguments and shows itself, kept together in
+//! `ProgramSettings` so it can be set up once and shared between tools.
+
+use std::borrow::Cow;
+
+use crate::parser::{ARG_PREFIX, HELP_FLAG};
+use crate::style::ColorChoice;
+use crate::Program;
+
+/// How a `Program` parses arguments and shows its help text and errors. Each setting can also be
+/// changed on its own with the `Program` method of the same name, such as
+/// `Program::with_abbreviations`.
+///
+/// ```
+/// use commandrs::settings::ProgramSettings;
+/// use commandrs::Program;
+///
+/// let settings = ProgramSettings {
+///     strict: true,
+///     abbreviations: true,
+///     ..ProgramSettings::default()
+/// };
+///
+/// let program = Program::new()
+///     .with_settings(settings)
+///     .with_optional_flag::<bool>("verbose", false, "Log everything")
+///     .unwrap();
+///
+/// assert!(program.parse_from(["--verb"]).is_ok());
+/// assert!(program.parse_from(["--quiet"]).is_err());
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct ProgramSettings<'a> {
+    /// Recognises flags on the command line, which is `--` by default.
+    pub prefix: Cow<'a, str>,
+    /// Also accept long flags given with a single dash, such as `-name value`.
+    pub single_dash_long_flags: bool,
+    /// Accept unambiguous prefixes of flag names, so `--verb` matches `--verbose`.
+    pub abbreviations: bool,
+    /// Match flags regardless of case, so `--Port` matches `--port`.
+    pub case_insensitive_flags: bool,
+    /// Fail to parse when a flag which was not registered is given, rather than ignoring it.
+    pub strict: bool,
+    /// Prompt for required flags which were not given when stdin is a terminal.
+    pub prompt_for_missing_flags: bool,
+    /// Whether errors are coloured.
+    pub color: ColorChoice,
+    /// The flag which shows the long help text, which is `help` by default, or `None` to leave it
+    /// to something else.
+    pub help_flag: Option<Cow<'a, str>>,
+    /// Arguments which show the short help text, given exactly as is, such as `-h`.
+    pub help_aliases: Vec<Cow<'a, str>>,
+    /// Show the help text through a pager when it is too long for the terminal.
+    pub help_pager: bool,
+    /// Report errors, such as a missing required flag, before showing the help text.
+    pub errors_before_help: bool,
+    /// Show the help text when no arguments are given at all, rather than reporting every missing
+    /// required flag.
+    pub args_required_else_help: bool,
+    /// Keep a copy of every argument for `Program::raw_args`.
+    pub keep_raw_args: bool,
+}
+
+impl Default for ProgramSettings<'_> {
+    fn default() -> Self {
+        ProgramSettings {
+            prefix: Cow::Borrowed(ARG_PREFIX),
+            single_dash_long_flags: false,
+            abbreviations: false,
+            case_insensitive_flags: false,
+            strict: false,
+            prompt_for_missing_flags: false,
+            color: ColorChoice::default(),
+            help_flag: Some(Cow::Borrowed(HELP_FLAG)),
+            help_aliases: vec![],
+            help_pager: true,
+            errors_before_help: false,
+            args_required_else_help: false,
+            keep_raw_args: true,
+        }
+    }
+}
+
+impl<'a> Program<'a> {
+    /// Replace every setting at once, such as with settings shared between every tool in a
+    /// workspace.
+    pub fn with_settings(mut self, settings: ProgramSettings<'a>) -> Program<'a> {
+        self.settings = settings;
+        self
+    }
+
+    /// The settings the `Program` parses arguments and shows itself with.
+    pub fn settings(&self) -> &ProgramSettings<'a> {
+        &self.settings
+    }
+
+    /// Show the long help text when no arguments are given at all, giving back
+    /// `ProgramError::NoArgsGiven`, rather than an error for every missing required flag. This is
+    /// friendlier for tools which can't do anything without arguments.
+    ///
+    /// ```
+    /// use commandrs::error::ProgramError;
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_help_pager(false)
+    ///     .with_args_required_else_help(true)
+    ///     .with_required_flag::<u16>("port", "Port number")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     Err(ProgramError::NoArgsGiven),
+    ///     program.parse_from(Vec::<String>::new())
+    /// );
+    /// ```
+    pub fn with_args_required_else_help(mut self, enabled: bool) -> Program<'a> {
+        self.settings.args_required_else_help = enabled;
+        self
+    }
+
+    /// Fail to parse when a flag which was not registered is given, such as a typo of a real
+    /// flag, rather than ignoring it.
+    pub fn with_strict(mut self, enabled: bool) -> Program<'a> {
+        self.settings.strict = enabled;
+        self
+    }
+
+    /// Keep a copy of every argument for `Program::raw_args`, which is done by default. Without
+    /// it, each value is moved out of the arguments into its flag rather than copied, so no
+    /// argument is held twice, but `Program::raw_args` is always empty.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_raw_args(false)
+    ///     .with_required_flag::<String>("name", "Your name")
+    ///     .unwrap()
+    ///     .parse_from_strings(vec!["--name".to_string(), "Ollie".to_string()])
+    ///     .unwrap();
+    ///
+    /// assert_eq!("Ollie", program.get_str("name").unwrap());
+    /// assert!(program.raw_args().is_empty());
+    /// ```
+    pub fn with_raw_args(mut self, enabled: bool) -> Program<'a> {
+        self.settings.keep_raw_args = enabled;
+        self
+    }
+}
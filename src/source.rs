@@ -0,0 +1,180 @@
+use std::env;
+use std::ffi::OsString;
+
+use crate::error::ProgramError;
+use crate::parser::executable_name;
+use crate::Program;
+
+/// Where the arguments a `Program` is parsed from come from. This is the process's own arguments
+/// for `Program::parse`, but embedders with no `env::args`, such as a WASI shim, a plugin host or
+/// a test fixture, can give their own with `Program::parse_from_source`.
+///
+/// ```
+/// use commandrs::error::ProgramError;
+/// use commandrs::source::ArgSource;
+/// use commandrs::Program;
+///
+/// /// The arguments a host passed to a plugin, as one line.
+/// struct PluginArgs(&'static str);
+///
+/// impl ArgSource for PluginArgs {
+///     fn name(&self) -> Option<String> {
+///         Some("my-plugin".to_string())
+///     }
+///
+///     fn into_args(self) -> Result<Vec<String>, ProgramError> {
+///         Ok(commandrs::tokenize(self.0))
+///     }
+/// }
+///
+/// let program = Program::new()
+///     .with_required_flag::<u16>("port", "Port number")
+///     .unwrap()
+///     .parse_from_source(PluginArgs("--port 8080"))
+///     .unwrap();
+///
+/// assert_eq!(Some("my-plugin"), program.name());
+/// assert_eq!(8080, program.get::<u16>("port").unwrap());
+/// ```
+pub trait ArgSource {
+    /// The name the program was started as, which is used as the name of a `Program` which was
+    /// not given one with `Program::with_name`.
+    fn name(&self) -> Option<String> {
+        None
+    }
+
+    /// The arguments to parse, leaving out the name.
+    fn into_args(self) -> Result<Vec<String>, ProgramError>;
+}
+
+/// The arguments the process was started with, where the first is the executable, which is taken
+/// as the name.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProcessArgs;
+
+impl ArgSource for ProcessArgs {
+    fn name(&self) -> Option<String> {
+        let executable = env::args_os().next()?;
+        Some(executable_name(&executable.to_string_lossy()))
+    }
+
+    fn into_args(self) -> Result<Vec<String>, ProgramError> {
+        into_strings(env::args_os().skip(1))
+    }
+}
+
+impl ArgSource for Vec<String> {
+    fn into_args(self) -> Result<Vec<String>, ProgramError> {
+        Ok(self)
+    }
+}
+
+impl ArgSource for &[&str] {
+    fn into_args(self) -> Result<Vec<String>, ProgramError> {
+        Ok(self.iter().map(|arg| arg.to_string()).collect())
+    }
+}
+
+impl ArgSource for Vec<OsString> {
+    fn into_args(self) -> Result<Vec<String>, ProgramError> {
+        into_strings(self.into_iter())
+    }
+}
+
+/// Converts each argument as it is taken, stopping at the first which is not valid Unicode.
+pub(crate) fn into_strings(
+    args: impl Iterator<Item = OsString>,
+) -> Result<Vec<String>, ProgramError> {
+    let mut strings = Vec::with_capacity(args.size_hint().0);
+    for (i, arg) in args.enumerate() {
+        let arg = arg
+            .into_string()
+            .map_err(|arg| ProgramError::ArgNotUnicode {
+                arg: arg.to_string_lossy().into_owned(),
+                index: Some(i),
+            })?;
+        strings.push(arg);
+    }
+    Ok(strings)
+}
+
+impl<'a> Program<'a> {
+    /// Parse the arguments from any `ArgSource`, rather than the process's own arguments.
+    pub fn parse_from_source(
+        mut self,
+        source: impl ArgSource,
+    ) -> Result<Program<'a>, ProgramError> {
+        let args = self.take_args(source)?;
+        self.parse_from_strings(args)
+    }
+
+    /// The arguments of the source, taking its name as the name of the `Program` if it has none.
+    pub(crate) fn take_args(
+        &mut self,
+        source: impl ArgSource,
+    ) -> Result<Vec<String>, ProgramError> {
+        if self.name.is_none() {
+            self.name = source.name().map(Into::into);
+        }
+        source.into_args()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixture;
+
+    impl ArgSource for Fixture {
+        fn name(&self) -> Option<String> {
+            Some("fixture".to_string())
+        }
+
+        fn into_args(self) -> Result<Vec<String>, ProgramError> {
+            Ok(vec!["--verbose".to_string()])
+        }
+    }
+
+    fn program() -> Program<'static> {
+        Program::new()
+            .with_optional_flag::<bool>("verbose", false, "Log more")
+            .unwrap()
+    }
+
+    #[test]
+    fn should_take_the_name_of_the_source_only_when_there_is_none() {
+        let unnamed = program().parse_from_source(Fixture).unwrap();
+        let named = program()
+            .with_name("mytool")
+            .parse_from_source(Fixture)
+            .unwrap();
+
+        assert_eq!(Some("fixture"), unnamed.name());
+        assert_eq!(Some("mytool"), named.name());
+        assert!(unnamed.get::<bool>("verbose").unwrap());
+    }
+
+    #[test]
+    fn should_parse_from_the_provided_sources() {
+        let args: &[&str] = &["--verbose"];
+
+        assert!(program()
+            .parse_from_source(args)
+            .unwrap()
+            .get::<bool>("verbose")
+            .unwrap());
+        assert!(program()
+            .parse_from_source(vec![OsString::from("--verbose")])
+            .unwrap()
+            .get::<bool>("verbose")
+            .unwrap());
+        assert_eq!(
+            None,
+            program()
+                .parse_from_source(Vec::<String>::new())
+                .unwrap()
+                .name()
+        );
+    }
+}
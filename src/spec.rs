@@ -0,0 +1,504 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::byte_size::ByteSize;
+use crate::duration::HumanDuration;
+use crate::error::ProgramError;
+use crate::flag::REDACTED;
+use crate::json::Json;
+use crate::Program;
+
+/// The hidden flag added by `Program::with_dump_spec_flag`.
+pub(crate) const DUMP_SPEC_FLAG: &str = "dump-cli-spec";
+
+impl Program<'static> {
+    /// Build a `Program` from a JSON spec, in the same shape as `Program::to_spec_json` writes.
+    /// This allows command line interfaces to be maintained as data and shared between binaries.
+    /// Only JSON is read, so specs kept in another format, such as TOML, must be converted first.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::from_spec(r#"{
+    ///     "description": "An HTTP server",
+    ///     "flags": [
+    ///         { "name": "port", "type": "u16", "required": true },
+    ///         { "name": "use-tls", "type": "bool", "default": false }
+    ///     ]
+    /// }"#)
+    /// .unwrap()
+    /// .parse_from_str_arr(&["--port", "8080"])
+    /// .unwrap();
+    ///
+    /// assert_eq!(8080, program.get::<u16>("port").unwrap());
+    /// assert!(!program.get::<bool>("use-tls").unwrap());
+    /// ```
+    ///
+    /// Only `flags` is required, and each flag only needs a `name`. Each of the `subcommands` is
+    /// a spec of its own, which must have a `name`. Flags are a `String` by default, and any flag
+    /// which is not required must have a `default`, unless it is `raw`. The supported types are
+    /// `bool`, `char`, `String`, the integer and float types, `IpAddr`, `Ipv4Addr`, `Ipv6Addr`,
+    /// `SocketAddr`, `PathBuf`, `HumanDuration` and `ByteSize`.
+    pub fn from_spec(spec: &str) -> Result<Program<'static>, ProgramError> {
+        let spec = Json::parse(spec).map_err(invalid_spec)?;
+        Program::from_spec_json(&spec)
+    }
+
+    fn from_spec_json(spec: &Json) -> Result<Program<'static>, ProgramError> {
+        let mut program =
+            Program::new().with_description(spec_string(spec, "description")?.unwrap_or_default());
+        if let Some(name) = spec_string(spec, "name")? {
+            program = program.with_name(name);
+        }
+        if let Some(version) = spec_string(spec, "version")? {
+            program = program.with_version(version);
+        }
+        if let Some(prefix) = spec_string(spec, "prefix")? {
+            program = program.with_prefix(prefix);
+        }
+
+        let flags = match spec.get("flags") {
+            Some(Json::Array(flags)) => flags,
+            _ => return Err(invalid_spec("flags must be an array")),
+        };
+        let program = flags.iter().try_fold(program, add_spec_flag)?;
+
+        let subcommands = match spec.get("subcommands") {
+            None | Some(Json::Null) => return Ok(program),
+            Some(Json::Array(subcommands)) => subcommands,
+            Some(_) => return Err(invalid_spec("subcommands must be an array")),
+        };
+        subcommands.iter().try_fold(program, |program, subcommand| {
+            let name = spec_string(subcommand, "name")?
+                .ok_or_else(|| invalid_spec("subcommands must have a name"))?;
+            program.with_subcommand(name, Program::from_spec_json(subcommand)?)
+        })
+    }
+}
+
+impl<'a> Program<'a> {
+    /// Describe the `Program` as a JSON document, with every flag's name, type, default,
+    /// requiredness and description, and the spec of each subcommand. This lets external tooling,
+    /// such as docs generators or completion frameworks, introspect the command line interface
+    /// without running it.
+    pub fn to_spec_json(&self) -> String {
+        self.to_spec().to_string()
+    }
+
+    /// Accept a hidden `--dump-cli-spec` flag, which writes the JSON spec of the `Program` from
+    /// `Program::to_spec_json` to stdout instead of parsing, failing with
+    /// `ProgramError::SpecDumped`. This lets audit tooling and UI generators introspect installed
+    /// binaries.
+    ///
+    /// Like the help flag, `Program::parse_or_exit` and `Program::run` exit successfully after
+    /// writing it.
+    pub fn with_dump_spec_flag(mut self) -> Program<'a> {
+        self.dump_spec_flag = true;
+        self
+    }
+
+    /// Writes the spec for the dump spec flag.
+    pub(crate) fn dump_spec(&self) -> ProgramError {
+        println!("{}", self.to_spec_json());
+        ProgramError::SpecDumped
+    }
+
+    pub(crate) fn to_spec(&self) -> Json {
+        let flags = self
+            .flags
+            .iter()
+            .map(|f| {
+                // Defaults are shown as they are in the help text, so secrets are never written.
+                let default = match (self.flag_defaults.get(&f.name), &f.default_display) {
+                    (Some(_), _) if f.is_raw || f.hide_default => Json::Null,
+                    (Some(_), _) if f.is_secret => Json::string(REDACTED),
+                    (Some(_), Some(display)) => Json::string(display.as_ref()),
+                    (Some(default), None) => Json::string(default.str_value.as_str()),
+                    (None, _) => Json::Null,
+                };
+
+                Json::object([
+                    ("name", Json::string(f.name.as_ref())),
+                    ("description", Json::string(f.desc.as_ref())),
+                    ("type", Json::string(short_type_name(f.type_name))),
+                    ("required", Json::Bool(f.is_required)),
+                    ("default", default),
+                    ("raw", Json::Bool(f.is_raw)),
+                ])
+            })
+            .collect();
+
+        let mut spec = vec![];
+        if let Some(name) = &self.name {
+            spec.push(("name", Json::string(name.as_ref())));
+        }
+        if let Some(version) = &self.version {
+            spec.push(("version", Json::string(version.as_ref())));
+        }
+        spec.push(("description", Json::string(self.desc.as_ref())));
+        spec.push(("prefix", Json::string(self.settings.prefix.as_ref())));
+        spec.push(("flags", Json::Array(flags)));
+        if !self.subcommands.is_empty() {
+            let subcommands = self.subcommands.iter().map(|s| s.to_spec()).collect();
+            spec.push(("subcommands", Json::Array(subcommands)));
+        }
+        Json::object(spec)
+    }
+}
+
+fn add_spec_flag(program: Program<'static>, flag: &Json) -> Result<Program<'static>, ProgramError> {
+    let name = spec_string(flag, "name")?.ok_or_else(|| invalid_spec("flags must have a name"))?;
+    let desc = spec_string(flag, "description")?.unwrap_or_default();
+    let type_name = spec_string(flag, "type")?.unwrap_or_else(|| "String".to_string());
+    let is_required = spec_bool(flag, "required")?;
+
+    if spec_bool(flag, "raw")? {
+        return program.with_raw_flag(name, desc);
+    }
+
+    let default = match flag.get("default") {
+        None | Some(Json::Null) => None,
+        Some(Json::String(s)) => Some(s.to_string()),
+        Some(default @ (Json::Bool(_) | Json::Number(_))) => Some(default.to_string()),
+        Some(_) => return Err(invalid_spec("default must be a string, number or bool")),
+    };
+
+    let add_flag = match short_type_name(&type_name).as_str() {
+        "bool" => add_typed_spec_flag::<bool>,
+        "char" => add_typed_spec_flag::<char>,
+        "String" | "str" => add_typed_spec_flag::<String>,
+        "i8" => add_typed_spec_flag::<i8>,
+        "i16" => add_typed_spec_flag::<i16>,
+        "i32" => add_typed_spec_flag::<i32>,
+        "i64" => add_typed_spec_flag::<i64>,
+        "i128" => add_typed_spec_flag::<i128>,
+        "isize" => add_typed_spec_flag::<isize>,
+        "u8" => add_typed_spec_flag::<u8>,
+        "u16" => add_typed_spec_flag::<u16>,
+        "u32" => add_typed_spec_flag::<u32>,
+        "u64" => add_typed_spec_flag::<u64>,
+        "u128" => add_typed_spec_flag::<u128>,
+        "usize" => add_typed_spec_flag::<usize>,
+        "f32" => add_typed_spec_flag::<f32>,
+        "f64" => add_typed_spec_flag::<f64>,
+        "IpAddr" => add_typed_spec_flag::<IpAddr>,
+        "Ipv4Addr" => add_typed_spec_flag::<Ipv4Addr>,
+        "Ipv6Addr" => add_typed_spec_flag::<Ipv6Addr>,
+        "SocketAddr" => add_typed_spec_flag::<SocketAddr>,
+        "HumanDuration" => add_typed_spec_flag::<HumanDuration>,
+        "ByteSize" => add_typed_spec_flag::<ByteSize>,
+        "PathBuf" => add_path_spec_flag,
+        _ => return Err(invalid_spec(format!("unsupported type {}", type_name))),
+    };
+
+    add_flag(program, name, desc, is_required, default)
+}
+
+fn add_typed_spec_flag<T>(
+    program: Program<'static>,
+    name: String,
+    desc: String,
+    is_required: bool,
+    default: Option<String>,
+) -> Result<Program<'static>, ProgramError>
+where
+    T: Display + FromStr + Clone + Send + Sync + 'static,
+    T::Err: Error + Send + Sync + 'static,
+{
+    if is_required {
+        return program.with_required_flag::<T>(name, desc);
+    }
+
+    let default = default
+        .ok_or_else(|| invalid_spec(format!("optional flag {} must have a default", name)))?;
+    let default = default.parse::<T>().map_err(|_| {
+        invalid_spec(format!(
+            "default {} for flag {} is not a valid {}",
+            default,
+            name,
+            short_type_name(std::any::type_name::<T>())
+        ))
+    })?;
+
+    program.with_optional_flag::<T>(name, default, desc)
+}
+
+/// Paths are not `Display`, so they are added with `Program::with_optional_path_flag` instead.
+fn add_path_spec_flag(
+    program: Program<'static>,
+    name: String,
+    desc: String,
+    is_required: bool,
+    default: Option<String>,
+) -> Result<Program<'static>, ProgramError> {
+    if is_required {
+        return program.with_required_flag::<PathBuf>(name, desc);
+    }
+
+    let default = default
+        .ok_or_else(|| invalid_spec(format!("optional flag {} must have a default", name)))?;
+    program.with_optional_path_flag(name, default, desc)
+}
+
+fn spec_string(json: &Json, key: &str) -> Result<Option<String>, ProgramError> {
+    match json.get(key) {
+        None | Some(Json::Null) => Ok(None),
+        Some(Json::String(s)) => Ok(Some(s.to_string())),
+        Some(_) => Err(invalid_spec(format!("{} must be a string", key))),
+    }
+}
+
+fn spec_bool(json: &Json, key: &str) -> Result<bool, ProgramError> {
+    match json.get(key) {
+        None | Some(Json::Null) => Ok(false),
+        Some(Json::Bool(b)) => Ok(*b),
+        Some(_) => Err(invalid_spec(format!("{} must be a bool", key))),
+    }
+}
+
+pub(crate) fn invalid_spec(reason: impl Into<String>) -> ProgramError {
+    ProgramError::InvalidSpec {
+        reason: reason.into(),
+    }
+}
+
+/// Strips the module paths from a type name, so `alloc::vec::Vec<alloc::string::String>` becomes
+/// `Vec<String>`.
+pub(crate) fn short_type_name(type_name: &str) -> String {
+    let mut short = String::new();
+    let mut segment_start = 0;
+
+    for (i, c) in type_name.char_indices() {
+        if matches!(c, '<' | '>' | ',' | ' ' | '(' | ')' | '[' | ']' | ';' | '&') {
+            short.push_str(last_path_segment(&type_name[segment_start..i]));
+            short.push(c);
+            segment_start = i + c.len_utf8();
+        }
+    }
+    short.push_str(last_path_segment(&type_name[segment_start..]));
+    short
+}
+
+fn last_path_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_spec_json_happy_path() {
+        let program = Program::new()
+            .with_description("An HTTP server")
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<String>("host", "localhost".to_string(), "Host to bind")
+            .unwrap()
+            .with_raw_flag("cmd", "Command to run on startup")
+            .unwrap();
+
+        assert_eq!(
+            r#"{
+  "description": "An HTTP server",
+  "prefix": "--",
+  "flags": [
+    {
+      "name": "port",
+      "description": "Port number",
+      "type": "u16",
+      "required": true,
+      "default": null,
+      "raw": false
+    },
+    {
+      "name": "host",
+      "description": "Host to bind",
+      "type": "String",
+      "required": false,
+      "default": "localhost",
+      "raw": false
+    },
+    {
+      "name": "cmd",
+      "description": "Command to run on startup",
+      "type": "Vec<String>",
+      "required": false,
+      "default": null,
+      "raw": true
+    }
+  ]
+}"#,
+            program.to_spec_json()
+        );
+    }
+
+    #[test]
+    fn to_spec_json_never_writes_secret_or_hidden_defaults() {
+        let program = Program::new()
+            .with_optional_flag::<String>("token", "hunter2".to_string(), "API token")
+            .unwrap()
+            .with_secret("token")
+            .unwrap()
+            .with_optional_flag::<String>("salt", "s4lt".to_string(), "Hash salt")
+            .unwrap()
+            .with_default_hidden("salt")
+            .unwrap()
+            .with_optional_flag::<u32>("workers", 8, "Worker threads")
+            .unwrap()
+            .with_default_display("workers", "one per core")
+            .unwrap();
+
+        let spec = program.to_spec_json();
+
+        assert!(!spec.contains("hunter2"));
+        assert!(!spec.contains("s4lt"));
+        assert!(spec.contains(r#""default": "****""#));
+        assert!(spec.contains(r#""default": null"#));
+        assert!(spec.contains(r#""default": "one per core""#));
+    }
+
+    #[test]
+    fn to_spec_json_empty_program() {
+        let program = Program::new().with_description("A boring tool that does nothing");
+
+        assert_eq!(
+            r#"{
+  "description": "A boring tool that does nothing",
+  "prefix": "--",
+  "flags": []
+}"#,
+            program.to_spec_json()
+        );
+    }
+
+    #[test]
+    fn from_spec_reads_what_to_spec_json_writes() {
+        let program = Program::new()
+            .with_description("An HTTP server")
+            .with_prefix("-")
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<String>("host", "localhost".to_string(), "Host to bind")
+            .unwrap()
+            .with_optional_flag::<bool>("use-tls", false, "TLS PLS?")
+            .unwrap()
+            .with_raw_flag("cmd", "Command to run on startup")
+            .unwrap();
+
+        assert_eq!(
+            program,
+            Program::from_spec(&program.to_spec_json()).unwrap()
+        );
+    }
+
+    #[test]
+    fn from_spec_reads_path_flags_which_to_spec_json_writes() {
+        let program = Program::new()
+            .with_required_flag::<PathBuf>("input", "File to read")
+            .unwrap()
+            .with_optional_path_flag("output", "out.txt", "File to write")
+            .unwrap();
+
+        assert_eq!(
+            program,
+            Program::from_spec(&program.to_spec_json()).unwrap()
+        );
+    }
+
+    #[test]
+    fn from_spec_keeps_integer_defaults_exactly() {
+        let program = Program::from_spec(
+            r#"{
+                "flags": [
+                    { "name": "max", "type": "u64", "default": 18446744073709551615 },
+                    { "name": "seed", "type": "u64", "default": 9007199254740993 }
+                ]
+            }"#,
+        )
+        .unwrap()
+        .parse_from_str_arr(&[])
+        .unwrap();
+
+        assert_eq!(u64::MAX, program.get::<u64>("max").unwrap());
+        assert_eq!(9007199254740993, program.get::<u64>("seed").unwrap());
+    }
+
+    #[test]
+    fn from_spec_reads_subcommands_what_to_spec_json_writes() {
+        let program = Program::new()
+            .with_name("mytool")
+            .with_version("1.2.0")
+            .with_description("A build tool")
+            .with_subcommand(
+                "build",
+                Program::new()
+                    .with_description("Build the project")
+                    .with_optional_flag::<bool>("release", false, "Build with optimisations")
+                    .unwrap(),
+            )
+            .unwrap();
+
+        let spec = program.to_spec_json();
+        assert!(spec.starts_with("{\n  \"name\": \"mytool\",\n  \"version\": \"1.2.0\",\n"));
+        assert!(spec.contains("  \"subcommands\": [\n    {\n      \"name\": \"build\",\n"));
+        assert_eq!(program, Program::from_spec(&spec).unwrap());
+    }
+
+    #[test]
+    fn should_dump_the_spec_instead_of_parsing() {
+        let err = Program::new()
+            .with_dump_spec_flag()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .parse_from(["--dump-cli-spec"])
+            .unwrap_err();
+
+        assert_eq!(ProgramError::SpecDumped, err);
+    }
+
+    #[test]
+    fn from_spec_fails_for_unsupported_types() {
+        let err = Program::from_spec(
+            r#"{ "description": "", "flags": [{ "name": "when", "type": "Instant" }] }"#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            ProgramError::InvalidSpec {
+                reason: "unsupported type Instant".to_string()
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn from_spec_fails_for_defaults_of_the_wrong_type() {
+        let err = Program::from_spec(
+            r#"{ "flags": [{ "name": "port", "type": "u16", "default": "eighty" }] }"#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            ProgramError::InvalidSpec {
+                reason: "default eighty for flag port is not a valid u16".to_string()
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn short_type_name_strips_module_paths() {
+        assert_eq!("u16", short_type_name("u16"));
+        assert_eq!("PathBuf", short_type_name("std::path::PathBuf"));
+        assert_eq!(
+            "HashMap<String, Vec<u8>>",
+            short_type_name(
+                "std::collections::hash::map::HashMap<alloc::string::String, alloc::vec::Vec<u8>>"
+            )
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Every piece of text `commandrs` shows to users goes through `Strings`, so tools which are not
+//! in English can replace all of it without forking the crate.
+
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use crate::error::ProgramError;
+
+/// Provides the built-in text shown in help texts, prompts and error messages. Every method gives
+/// back English by default, so only the text which needs to change has to be implemented.
+///
+/// ```
+/// use commandrs::strings::Strings;
+/// use commandrs::Program;
+///
+/// struct French;
+///
+/// impl Strings for French {
+///     fn required(&self) -> String {
+///         "(obligatoire)".to_string()
+///     }
+/// }
+///
+/// let program = Program::new()
+///     .with_strings(French)
+///     .with_required_flag::<u16>("port", "Numéro de port")
+///     .unwrap();
+/// ```
+pub trait Strings: Send + Sync {
+    /// Shown in the help text next to required flags.
+    fn required(&self) -> String {
+        "(required)".to_string()
+    }
+
+    /// Shown in the help text next to secret flags.
+    fn secret(&self) -> String {
+        "(secret)".to_string()
+    }
+
+    /// Shown in the help text next to raw flags.
+    fn remaining_args(&self) -> String {
+        "(remaining args)".to_string()
+    }
+
+    /// Shown in the help text next to optional flags which hide their default.
+    fn optional(&self) -> String {
+        "(optional)".to_string()
+    }
+
+    /// Shown in the help text next to optional flags.
+    fn default_value(&self, default: &str) -> String {
+        format!("(default: {})", default)
+    }
+
+    /// Shown in the help text after the description of flags with possible values.
+    fn possible_values(&self, possible_values: &[&str]) -> String {
+        format!("[possible values: {}]", possible_values.join(", "))
+    }
+
+    /// Shown in the help text after the description of flags with an environment variable.
+    fn env_var(&self, env_var: &str) -> String {
+        format!("[env: {}]", env_var)
+    }
+
+    /// Shown in the long help text under the description of flags with examples, for each one.
+    fn example(&self, example: &str) -> String {
+        format!("[example: {}]", example)
+    }
+
+    /// Shown in the help text after the description of `ByteSize` flags, explaining the units.
+    fn byte_size_units(&self) -> String {
+        "[sizes: 1KB = 1000 bytes, 1KiB = 1024 bytes]".to_string()
+    }
+
+    /// Shown at the end of the help text for programs with an author.
+    fn author(&self, author: &str) -> String {
+        format!("Author: {}", author)
+    }
+
+    /// The heading of the list of subcommands in the help text.
+    fn commands(&self) -> String {
+        "Commands:".to_string()
+    }
+
+    /// The heading of the list of external subcommands in the help text.
+    fn external_commands(&self) -> String {
+        "External commands:".to_string()
+    }
+
+    /// The one-line description of the `help` subcommand in the list of subcommands.
+    fn help_command(&self) -> String {
+        "Show the help text of a command".to_string()
+    }
+
+    /// Shown in the help text instead of the flags when there are none.
+    fn no_args(&self) -> String {
+        "(no args)".to_string()
+    }
+
+    /// Added after the question asked by `Program::confirm`.
+    fn confirm_choices(&self) -> String {
+        "[y/N]".to_string()
+    }
+
+    /// Whether an answer to `Program::confirm` approves it.
+    fn is_yes(&self, answer: &str) -> bool {
+        matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+    }
+
+    /// The usage line shown with errors, given the arguments the `Program` takes.
+    fn usage(&self, args: &str) -> String {
+        format!("Usage: {}", args)
+    }
+
+    /// Points users towards the help text when showing an error.
+    fn help_hint(&self, help_flag: &str) -> String {
+        format!("For more information, try {}", help_flag)
+    }
+
+    /// Shown in front of errors written to stderr.
+    fn error_label(&self) -> String {
+        "error:".to_string()
+    }
+
+    /// The message shown to users for an error, which is its `Display` by default.
+    fn error(&self, err: &ProgramError) -> String {
+        err.to_string()
+    }
+}
+
+/// The English text used when no other `Strings` are given.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnglishStrings;
+
+impl Strings for EnglishStrings {}
+
+/// Holds the `Strings` of a `Program`, which can't be compared or printed themselves.
+#[derive(Clone)]
+pub(crate) struct StringsProvider(pub Arc<dyn Strings>);
+
+impl Default for StringsProvider {
+    fn default() -> StringsProvider {
+        StringsProvider(Arc::new(EnglishStrings))
+    }
+}
+
+// Like flag parsers, the strings only affect how a `Program` is shown, not what it is.
+impl PartialEq for StringsProvider {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Debug for StringsProvider {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("StringsProvider")
+    }
+}
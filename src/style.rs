@@ -0,0 +1,191 @@
+//! Styling of what `commandrs` writes to the terminal, such as errors with `error:` in red.
+
+use std::env;
+use std::fmt::Display;
+use std::io::{self, IsTerminal};
+
+use crate::error::ProgramError;
+use crate::error::ProgramError::{
+    CompletionsGenerated, HelpFlagGiven, ManPageGenerated, NoArgsGiven, SpecDumped,
+    VersionFlagGiven,
+};
+use crate::Program;
+
+const RED: &str = "\x1b[1;31m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Whether to style text with colours.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum ColorChoice {
+    /// Colour text only when it is written to a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    /// Always colour text, even when it is written to a file or pipe.
+    Always,
+    /// Never colour text.
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether text written to stderr should be coloured.
+    fn for_stderr(self) -> bool {
+        match self {
+            ColorChoice::Auto => io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+impl<'a> Program<'a> {
+    /// Choose whether errors written by `Program::print_error` are coloured, which is only when
+    /// stderr is a terminal by default.
+    pub fn with_color(mut self, color: ColorChoice) -> Program<'a> {
+        self.settings.color = color;
+        self
+    }
+
+    /// Write an error to stderr the way `Program::parse_or_exit` does, starting with `error:` in
+    /// red, then the flag which caused it, and the usage line with a pointer to the help flag.
+    /// Nothing is written when the help text or version has already been shown instead, such as
+    /// for the help flag.
+    ///
+    /// This is useful for custom main loops which handle errors themselves.
+    pub fn print_error(&self, err: &ProgramError) {
+        if !matches!(
+            err,
+            HelpFlagGiven
+                | VersionFlagGiven
+                | NoArgsGiven
+                | CompletionsGenerated
+                | ManPageGenerated
+                | SpecDumped
+        ) {
+            eprintln!(
+                "{}",
+                self.render_error(err, self.settings.color.for_stderr())
+            );
+        }
+    }
+
+    /// Write an error which is not a `ProgramError`, such as one from the application itself, to
+    /// stderr starting with `error:` in red. Unlike `Program::print_error`, there is no usage line,
+    /// as the arguments were fine.
+    pub fn print_failure(&self, err: &dyn Display) {
+        eprintln!(
+            "{}",
+            self.render_failure(err, self.settings.color.for_stderr())
+        );
+    }
+
+    /// The error as `Program::print_failure` writes it, with or without colours.
+    pub fn render_failure(&self, err: &dyn Display, color: bool) -> String {
+        let label = self.strings.0.error_label();
+        format!("{} {}", paint(RED, &label, color), err)
+    }
+
+    /// The error as `Program::print_error` writes it, with or without colours.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_required_flag::<u16>("port", "Port number")
+    ///     .unwrap();
+    /// let err = program.parse_from(["--port", "eighty"]).unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     "error: Could not parse port as type of u16, invalid digit found in string\n\n  \
+    ///     --port eighty\n\n\
+    ///     Usage: --port <u16>\n\n\
+    ///     For more information, try --help",
+    ///     program.render_error(&err, false)
+    /// );
+    /// ```
+    pub fn render_error(&self, err: &ProgramError, color: bool) -> String {
+        let label = self.strings.0.error_label();
+        let mut text = format!("{} {}", paint(RED, &label, color), self.error_message(err));
+        if let Some(arg) = self.offending_arg(err) {
+            text = format!("{}\n\n  {}", text, paint(BOLD, &arg, color));
+        }
+        format!("{}\n\n{}", text, self.usage_with_hint())
+    }
+
+    /// The flag which caused the error as it would be given on the command line, along with the
+    /// value which was given for it, if any.
+    fn offending_arg(&self, err: &ProgramError) -> Option<String> {
+        let flag = format!("{}{}", self.settings.prefix, err.flag_name()?);
+        match err.value() {
+            Some(value) => Some(format!("{} {}", flag, value)),
+            None => Some(flag),
+        }
+    }
+}
+
+fn paint(style: &str, text: &str, color: bool) -> String {
+    match color {
+        true => format!("{}{}{}", style, text, RESET),
+        false => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program() -> Program<'static> {
+        Program::new()
+            .with_optional_flag::<String>("speed", "auto".to_string(), "How fast to go")
+            .unwrap()
+            .with_possible_values("speed", ["fast", "slow"])
+            .unwrap()
+    }
+
+    #[test]
+    fn should_render_errors_in_colour() {
+        let program = program();
+        let err = program.parse_from(["--speed", "ludicrous"]).unwrap_err();
+
+        assert_eq!(
+            "\x1b[1;31merror:\x1b[0m Value ludicrous is not possible for --speed [possible \
+            values: fast, slow]\n\n  \
+            \x1b[1m--speed ludicrous\x1b[0m\n\n\
+            Usage: [--speed <String>]\n\n\
+            For more information, try --help",
+            program.render_error(&err, true)
+        );
+    }
+
+    #[test]
+    fn should_leave_out_the_flag_of_errors_without_one() {
+        let program = program().with_help_alias("-h").without_help_flag();
+        let err = ProgramError::InvalidSpec {
+            reason: "flags must be an array".to_string(),
+        };
+
+        assert_eq!(
+            "error: Invalid program spec, flags must be an array\n\n\
+            Usage: [--speed <String>]\n\n\
+            For more information, try -h",
+            program.render_error(&err, false)
+        );
+    }
+
+    #[test]
+    fn should_render_other_errors_without_usage() {
+        let err = std::io::Error::other("disk full");
+
+        assert_eq!("error: disk full", program().render_failure(&err, false));
+        assert_eq!(
+            "\x1b[1;31merror:\x1b[0m disk full",
+            program().render_failure(&err, true)
+        );
+    }
+
+    #[test]
+    fn should_only_colour_when_chosen() {
+        assert!(ColorChoice::Always.for_stderr());
+        assert!(!ColorChoice::Never.for_stderr());
+    }
+}
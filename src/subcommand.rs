@@ -0,0 +1,482 @@
+//! Subcommands, such as `build` in `mytool build --release`, which are each their own `Program`
+//! with their own flags and help text.
+
+use std::borrow::Cow;
+
+use crate::error::ProgramError;
+use crate::flag::FlagValues;
+use crate::parser::ArgStream;
+use crate::Program;
+
+/// How many edits away from an unknown subcommand a subcommand can be to be suggested instead.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Shows the help text of the subcommand after it, such as `mytool help build`, or of the
+/// `Program` itself when there is none.
+pub(crate) const HELP_SUBCOMMAND: &str = "help";
+
+impl<'a> Program<'a> {
+    /// Add a subcommand, such as `build` in `mytool build --release`. The subcommand is its own
+    /// `Program`, whose description is shown as a one-line summary in the help text of this one.
+    ///
+    /// The first argument which is not a flag or a value, and is the name of a subcommand, starts
+    /// the subcommand. Every argument after it is parsed by the subcommand, including the help
+    /// flag, so `mytool build --help` shows the help text of `build`, as does
+    /// `mytool help build`. Subcommands are parsed with the settings and strings of the `Program`
+    /// they were added to.
+    ///
+    /// The name must be unique.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_optional_flag::<bool>("verbose", false, "Log everything")
+    ///     .unwrap()
+    ///     .with_subcommand(
+    ///         "build",
+    ///         Program::new()
+    ///             .with_description("Build the project")
+    ///             .with_optional_flag::<bool>("release", false, "Build with optimisations")
+    ///             .unwrap(),
+    ///     )
+    ///     .unwrap()
+    ///     .parse_from_str_arr(&["--verbose", "build", "--release"])
+    ///     .unwrap();
+    ///
+    /// let build = program.subcommand().unwrap();
+    /// assert_eq!(Some("build"), program.subcommand_name());
+    /// assert!(build.get::<bool>("release").unwrap());
+    /// ```
+    pub fn with_subcommand(
+        mut self,
+        name: impl Into<Cow<'a, str>>,
+        subcommand: Program<'a>,
+    ) -> Result<Program<'a>, ProgramError> {
+        let name = name.into();
+        if self.find_subcommand(&name).is_some() {
+            return Err(ProgramError::SubcommandAlreadyExistsWithName {
+                name: name.to_string(),
+            });
+        }
+
+        self.subcommands.push(subcommand.with_name(name));
+        Ok(self)
+    }
+
+    /// Propagate the flag to every subcommand, and their subcommands, so it can be given before or
+    /// after any of them, such as `mytool --verbose build` or `mytool build --verbose`. Flags are
+    /// otherwise local to the `Program` they were added to.
+    ///
+    /// The value is the same for the `Program` and every subcommand it was propagated to, wherever
+    /// it was given. A subcommand with its own flag of the same name keeps its own flag instead.
+    ///
+    /// Required flags cannot be propagated, as they would be required before every subcommand.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_optional_flag::<bool>("verbose", false, "Log everything")
+    ///     .unwrap()
+    ///     .with_global("verbose")
+    ///     .unwrap()
+    ///     .with_subcommand("build", Program::new())
+    ///     .unwrap()
+    ///     .parse_from_str_arr(&["build", "--verbose"])
+    ///     .unwrap();
+    ///
+    /// assert!(program.get::<bool>("verbose").unwrap());
+    /// assert!(program.subcommand().unwrap().get::<bool>("verbose").unwrap());
+    /// ```
+    pub fn with_global(mut self, name: &str) -> Result<Program<'a>, ProgramError> {
+        let flag = self.flag_mut(name)?;
+        if flag.is_required {
+            return Err(ProgramError::InvalidFlagDefinition {
+                name: name.to_string(),
+                reason: "required flags cannot be propagated to subcommands".to_string(),
+            });
+        }
+        flag.is_global = true;
+        Ok(self)
+    }
+
+    /// The subcommand which was given, parsed with the arguments after it.
+    pub fn subcommand(&self) -> Option<&Program<'a>> {
+        self.subcommand.as_deref()
+    }
+
+    /// The name of the subcommand which was given, such as `build`.
+    pub fn subcommand_name(&self) -> Option<&str> {
+        self.subcommand().and_then(|s| s.name())
+    }
+
+    pub(crate) fn find_subcommand(&self, name: &str) -> Option<&Program<'a>> {
+        self.subcommands.iter().find(|s| s.name() == Some(name))
+    }
+
+    /// Whether `help` is taken as a subcommand showing help text. It is only when there are
+    /// other subcommands, and none of them are already called `help`.
+    pub(crate) fn has_help_subcommand(&self) -> bool {
+        !self.subcommands.is_empty() && self.find_subcommand(HELP_SUBCOMMAND).is_none()
+    }
+
+    /// Shows the long help text of the subcommand with the given name, such as for
+    /// `mytool help build`, or of this `Program` when there is no such subcommand.
+    pub(crate) fn show_subcommand_help(&self, name: Option<&str>) -> ProgramError {
+        match name.and_then(|n| self.find_subcommand(n)) {
+            Some(subcommand) => self.inherit(subcommand.clone()).show_help(true),
+            None => self.show_help(true),
+        }
+    }
+
+    /// The error for an argument which is not a subcommand, suggesting the subcommands it is
+    /// closest to, in case it was a typo.
+    pub(crate) fn unknown_subcommand(&self, arg: &str) -> ProgramError {
+        let help = Some(HELP_SUBCOMMAND).filter(|_| self.has_help_subcommand());
+        let mut suggestions: Vec<(usize, &str)> = self
+            .subcommands
+            .iter()
+            .filter_map(|s| s.name())
+            .chain(help)
+            .map(|name| (edit_distance(arg, name), name))
+            .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .collect();
+        suggestions.sort_by_key(|(distance, _)| *distance);
+
+        ProgramError::UnknownSubcommand {
+            name: arg.to_string(),
+            suggestions: suggestions.iter().map(|(_, s)| s.to_string()).collect(),
+            index: None,
+        }
+    }
+
+    /// Parse the arguments after the subcommand given at `at`, pointing any error at the argument
+    /// it was caused by among all of the arguments. The `values` are those parsed for this
+    /// `Program`, which the global flags of the subcommand take as their defaults.
+    pub(crate) fn parse_subcommand<S>(
+        &self,
+        subcommand: Program<'a>,
+        values: &FlagValues<'a>,
+        args: &mut ArgStream<S>,
+        at: usize,
+    ) -> Result<Program<'a>, ProgramError>
+    where
+        S: AsRef<str> + Into<String> + Clone,
+    {
+        let mut subcommand = self.inherit_with(subcommand, values);
+        let mut remaining = args.remaining();
+        let parsed = subcommand
+            .parse_values(&mut remaining)
+            .map_err(|err| match err.index() {
+                Some(i) => err.at_index(at + 1 + i),
+                None => err,
+            })?;
+        let kept = remaining.kept.unwrap_or_default();
+
+        subcommand.set_parsed(parsed);
+        subcommand.raw_args = kept.iter().cloned().map(Into::into).collect();
+        if let Some(parent_kept) = &mut args.kept {
+            parent_kept.extend(kept);
+        }
+        Ok(subcommand)
+    }
+
+    /// Takes the values of the global flags from the subcommand into the `values` of this
+    /// `Program`, as they may have been given after it.
+    pub(crate) fn take_global_values(&self, values: &mut FlagValues<'a>, subcommand: &Program<'a>) {
+        for flag in self.flags.iter().filter(|f| f.is_global) {
+            let is_propagated = subcommand.flag(&flag.name).is_some_and(|f| f.is_global);
+            let value = subcommand.flag_values.get(&flag.name);
+            if let Some(value) = value.filter(|_| is_propagated) {
+                values.insert(flag.name.clone(), value.clone());
+            }
+        }
+    }
+
+    /// Whether the flag was propagated to the subcommand from this `Program`, rather than being
+    /// the subcommand's own.
+    pub(crate) fn is_propagated_to(&self, subcommand: &Program<'a>, name: &str) -> bool {
+        let is_global = |program: &Program<'a>| program.flag(name).is_some_and(|f| f.is_global);
+        is_global(self) && is_global(subcommand)
+    }
+
+    /// Gives the subcommand the settings and strings of this `Program`, and puts it under its name.
+    /// Global flags are added to it too, with the values given to this `Program` as their
+    /// defaults.
+    pub(crate) fn inherit(&self, subcommand: Program<'a>) -> Program<'a> {
+        self.inherit_with(subcommand, &self.flag_values)
+    }
+
+    /// The same as `Program::inherit`, but with the given values of the global flags, rather than
+    /// those of this `Program`.
+    fn inherit_with(&self, mut subcommand: Program<'a>, values: &FlagValues<'a>) -> Program<'a> {
+        subcommand.settings = self.settings.clone();
+        subcommand.strings = self.strings.clone();
+        subcommand.parent_name = self.full_name();
+
+        for flag in self.flags.iter().filter(|f| f.is_global) {
+            if subcommand.flag(&flag.name).is_some() {
+                continue;
+            }
+            let mut flag = flag.clone();
+            let value = match values.get(&flag.name) {
+                // The environment variable was already read for the value, if it had to be.
+                Some(value) => {
+                    flag.env_var = None;
+                    Some(value)
+                }
+                None => self.flag_defaults.get(&flag.name),
+            };
+            if let Some(value) = value {
+                subcommand
+                    .flag_defaults
+                    .insert(flag.name.clone(), value.clone());
+            }
+            subcommand
+                .flag_indices
+                .insert(flag.name.clone(), subcommand.flags.len());
+            subcommand.flags.push(flag);
+        }
+        subcommand
+    }
+
+    /// The name of the `Program` along with the subcommands it is under, such as `mytool build`.
+    pub(crate) fn full_name(&self) -> Option<Cow<'a, str>> {
+        match (&self.parent_name, &self.name) {
+            (Some(parent), Some(name)) => Some(Cow::Owned(format!("{} {}", parent, name))),
+            (None, name) => name.clone(),
+            (parent, None) => parent.clone(),
+        }
+    }
+}
+
+/// The number of characters which have to be inserted, removed, or replaced to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = replace.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program() -> Program<'static> {
+        Program::new()
+            .with_help_pager(false)
+            .with_name("mytool")
+            .with_optional_flag::<bool>("verbose", false, "Log everything")
+            .unwrap()
+            .with_subcommand(
+                "build",
+                Program::new()
+                    .with_description("Build the project")
+                    .with_required_flag::<String>("target", "Target to build")
+                    .unwrap(),
+            )
+            .unwrap()
+            .with_subcommand("test", Program::new().with_description("Run the tests"))
+            .unwrap()
+    }
+
+    #[test]
+    fn should_parse_flags_after_the_subcommand_with_the_subcommand() {
+        let program = program()
+            .parse_from_str_arr(&["--verbose", "build", "--target", "x86"])
+            .unwrap();
+
+        assert!(program.get::<bool>("verbose").unwrap());
+        assert_eq!(Some("build"), program.subcommand_name());
+        assert_eq!(
+            "x86",
+            program
+                .subcommand()
+                .unwrap()
+                .get::<String>("target")
+                .unwrap()
+        );
+        assert_eq!(
+            vec!["--verbose", "build", "--target", "x86"],
+            program.to_explicit_args()
+        );
+    }
+
+    #[test]
+    fn should_take_any_boolean_as_the_value_of_a_bool_flag_before_the_subcommand() {
+        for (value, expected) in [("yes", true), ("OFF", false), ("0", false)] {
+            let program = program()
+                .parse_from_str_arr(&["--verbose", value, "build", "--target", "x86"])
+                .unwrap();
+
+            assert_eq!(expected, program.get::<bool>("verbose").unwrap());
+            assert_eq!(Some("build"), program.subcommand_name());
+        }
+    }
+
+    #[test]
+    fn should_show_the_help_of_the_subcommand_when_given_after_it() {
+        let err = program()
+            .parse_from(["build", "--help", "--target"])
+            .unwrap_err();
+
+        assert_eq!(ProgramError::HelpFlagGiven, err);
+    }
+
+    #[test]
+    fn should_show_help_for_the_help_subcommand() {
+        assert_eq!(
+            ProgramError::HelpFlagGiven,
+            program().parse_from(["help", "build"]).unwrap_err()
+        );
+        assert_eq!(
+            ProgramError::HelpFlagGiven,
+            program().parse_from(["--verbose", "help"]).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn should_run_a_subcommand_called_help_rather_than_show_help() {
+        let program = program()
+            .with_subcommand("help", Program::new())
+            .unwrap()
+            .parse_from_str_arr(&["help", "build"])
+            .unwrap();
+
+        assert_eq!(Some("help"), program.subcommand_name());
+    }
+
+    #[test]
+    fn should_have_no_subcommand_when_none_was_given() {
+        let program = program()
+            .parse_from_str_arr(&["--verbose", "true"])
+            .unwrap();
+
+        assert_eq!(None, program.subcommand());
+        assert_eq!(None, program.subcommand_name());
+    }
+
+    #[test]
+    fn should_point_errors_of_subcommands_at_the_whole_command_line() {
+        let err = program()
+            .parse_from(["--verbose", "build", "--target"])
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::RequiredArgWasNotGiven {
+                name: "target".to_string(),
+                prefix: "--".to_string(),
+                index: Some(2),
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn should_use_the_full_name_in_the_usage_of_subcommands() {
+        let program = program()
+            .parse_from_str_arr(&["build", "--target", "x"])
+            .unwrap();
+        let build = program.subcommand().unwrap();
+
+        assert_eq!("Usage: mytool build --target <String>", build.usage());
+    }
+
+    #[test]
+    fn should_suggest_the_closest_subcommands_for_unknown_ones() {
+        let err = program().parse_from(["--verbose", "biuld"]).unwrap_err();
+
+        assert_eq!(
+            ProgramError::UnknownSubcommand {
+                name: "biuld".to_string(),
+                suggestions: vec!["build".to_string()],
+                index: Some(1),
+            },
+            err
+        );
+        assert_eq!(
+            "Unknown command 'biuld', did you mean 'build'?",
+            err.to_string()
+        );
+        assert_eq!(
+            "Unknown command 'deploy'",
+            program().parse_from(["deploy"]).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn should_count_the_edits_between_names() {
+        assert_eq!(0, edit_distance("build", "build"));
+        assert_eq!(2, edit_distance("biuld", "build"));
+        assert_eq!(1, edit_distance("tst", "test"));
+        assert_eq!(5, edit_distance("", "build"));
+    }
+
+    #[test]
+    fn should_propagate_global_flags_to_subcommands() {
+        let program = program().with_global("verbose").unwrap();
+
+        for args in [["--verbose", "build"], ["build", "--verbose"]] {
+            let parsed = program
+                .parse_from(args.into_iter().chain(["--target", "x86"]))
+                .unwrap()
+                .into_program();
+
+            assert!(parsed.get::<bool>("verbose").unwrap());
+            assert!(parsed.subcommand().unwrap().get::<bool>("verbose").unwrap());
+            assert_eq!(
+                vec!["--verbose", "build", "--target", "x86"],
+                parsed.to_explicit_args()
+            );
+        }
+    }
+
+    #[test]
+    fn should_keep_flags_local_unless_they_are_global() {
+        let parsed = program()
+            .parse_from_str_arr(&["build", "--verbose", "--target", "x86"])
+            .unwrap();
+
+        assert!(!parsed.get::<bool>("verbose").unwrap());
+        assert!(parsed.subcommand().unwrap().get::<bool>("verbose").is_err());
+    }
+
+    #[test]
+    fn should_not_be_able_to_propagate_required_flags() {
+        let err = Program::new()
+            .with_required_flag::<String>("token", "API token")
+            .unwrap()
+            .with_global("token")
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::InvalidFlagDefinition {
+                name: "token".to_string(),
+                reason: "required flags cannot be propagated to subcommands".to_string(),
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn should_not_be_able_to_add_subcommands_with_the_same_name() {
+        let err = program()
+            .with_subcommand("test", Program::new())
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::SubcommandAlreadyExistsWithName {
+                name: "test".to_string()
+            },
+            err
+        );
+    }
+}
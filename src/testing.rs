@@ -0,0 +1,158 @@
+//! Helpers for snapshot testing the help text, usage line and errors of a `Program`, so crates
+//! using it can catch unintended changes to their command line interface.
+//!
+//! Everything is rendered the same way wherever the tests run: the help text is always wrapped to
+//! the same width, nothing is coloured or paged, and external subcommands on `PATH` are left out.
+//!
+//! ```
+//! use commandrs::testing::{assert_snapshot, render_usage};
+//! use commandrs::Program;
+//!
+//! let program = Program::new()
+//!     .with_name("mytool")
+//!     .with_required_flag::<u16>("port", "Port number")
+//!     .unwrap();
+//!
+//! assert_snapshot(&render_usage(&program), "Usage: mytool --port <u16>");
+//! ```
+
+use crate::error::ProgramError;
+use crate::Program;
+
+/// Parse the arguments with a copy of the `Program`, failing with the error as users would see it
+/// when they can't be parsed. Together with `program!`, this makes throwaway programs in tests a
+/// couple of lines:
+///
+/// ```
+/// use commandrs::program;
+/// use commandrs::testing::parse;
+///
+/// let program = program! { flags: { port: u16 required "Port number" } }.unwrap();
+///
+/// assert_eq!(80, parse(&program, ["--port", "80"]).get::<u16>("port").unwrap());
+/// ```
+#[track_caller]
+pub fn parse<'a, I>(program: &Program<'a>, args: I) -> Program<'a>
+where
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    let args: Vec<String> = args.into_iter().map(Into::into).collect();
+    match program.parse_from(args.clone()) {
+        Ok(parsed) => parsed.into_program(),
+        Err(err) => panic!(
+            "could not parse {:?}\n\n{}",
+            args,
+            render_error(program, &err)
+        ),
+    }
+}
+
+/// The long help text, as shown for `--help`.
+pub fn render_help(program: &Program) -> String {
+    deterministic(program).generate_help_text()
+}
+
+/// The short help text, as shown for aliases of the help flag, such as `-h`.
+pub fn render_short_help(program: &Program) -> String {
+    deterministic(program).generate_short_help_text()
+}
+
+/// The usage line, such as `Usage: mytool --port <u16>`.
+pub fn render_usage(program: &Program) -> String {
+    deterministic(program).usage()
+}
+
+/// The error as `Program::print_error` writes it, without any colours.
+pub fn render_error(program: &Program, err: &ProgramError) -> String {
+    deterministic(program).render_error(err, false)
+}
+
+/// Assert the rendered text matches the expected snapshot, ignoring whitespace at the ends of
+/// lines and of the whole text, along with the difference between `\r\n` and `\n`. When they do
+/// not match, the first line which differs is pointed out along with both texts.
+#[track_caller]
+pub fn assert_snapshot(actual: &str, expected: &str) {
+    let actual_lines = normalised_lines(actual);
+    let expected_lines = normalised_lines(expected);
+    if actual_lines == expected_lines {
+        return;
+    }
+
+    let line = actual_lines
+        .iter()
+        .zip(&expected_lines)
+        .position(|(a, e)| a != e)
+        .unwrap_or(actual_lines.len().min(expected_lines.len()));
+    panic!(
+        "snapshot differs at line {}\n  expected: {:?}\n    actual: {:?}\n\n--- expected ---\n{}\n--- actual ---\n{}",
+        line + 1,
+        expected_lines.get(line).copied().unwrap_or_default(),
+        actual_lines.get(line).copied().unwrap_or_default(),
+        expected_lines.join("\n"),
+        actual_lines.join("\n"),
+    );
+}
+
+fn normalised_lines(text: &str) -> Vec<&str> {
+    text.trim().lines().map(|line| line.trim_end()).collect()
+}
+
+/// A copy of the `Program` which renders the same way everywhere.
+fn deterministic<'a>(program: &Program<'a>) -> Program<'a> {
+    program.clone().with_external_subcommands(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program() -> Program<'static> {
+        Program::new()
+            .with_name("mytool")
+            .with_description("A bunny observing tool!")
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+    }
+
+    #[test]
+    fn should_match_snapshots_ignoring_trailing_whitespace() {
+        assert_snapshot(
+            &render_help(&program()),
+            "
+            A bunny observing tool!
+
+            \t--port (required): Port number
+            "
+            .lines()
+            .map(|l| l.trim_start_matches(' '))
+            .collect::<Vec<&str>>()
+            .join("\r\n")
+            .as_str(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "snapshot differs at line 3")]
+    fn should_point_out_the_first_line_which_differs() {
+        assert_snapshot(&render_help(&program()), "A bunny observing tool!\n\nPort");
+    }
+
+    #[test]
+    #[should_panic(expected = "could not parse [\"--port\", \"eighty\"]")]
+    fn should_fail_to_parse_with_the_error_users_would_see() {
+        parse(&program(), ["--port", "eighty"]);
+    }
+
+    #[test]
+    fn should_render_errors_without_colours() {
+        let program = program();
+        let err = program.parse_from(["--port", "eighty"]).unwrap_err();
+
+        assert_snapshot(
+            &render_error(&program, &err),
+            "error: Could not parse port as type of u16, invalid digit found in string\n\n  \
+             --port eighty\n\nUsage: mytool --port <u16>\n\nFor more information, try --help",
+        );
+    }
+}
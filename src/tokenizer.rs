@@ -0,0 +1,119 @@
+/// Split a command line into arguments the way a POSIX shell would, without expanding anything.
+/// Whitespace separates arguments unless it is quoted or escaped.
+///
+/// - Single quotes keep everything inside them as it is.
+/// - Double quotes keep everything inside them, except `\` can escape `"`, `\`, `$` and `` ` ``.
+/// - Outside of quotes, `\` escapes any character.
+///
+/// An unterminated quote runs until the end of the line.
+///
+/// ```
+/// use commandrs::tokenize;
+///
+/// assert_eq!(
+///     vec!["--name", "Ollie Smith", "--greeting", "it's \"nice\""],
+///     tokenize(r#"--name 'Ollie Smith' --greeting "it's \"nice\"""#)
+/// );
+/// ```
+pub fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut token: Option<String> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => tokens.extend(token.take()),
+            '\'' => {
+                let token = token.get_or_insert_with(String::new);
+                token.extend(chars.by_ref().take_while(|&c| c != '\''));
+            }
+            '"' => {
+                let token = token.get_or_insert_with(String::new);
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => token.push(c),
+                            Some(c) => {
+                                token.push('\\');
+                                token.push(c);
+                            }
+                            None => token.push('\\'),
+                        },
+                        c => token.push(c),
+                    }
+                }
+            }
+            '\\' => token.get_or_insert_with(String::new).extend(chars.next()),
+            c => token.get_or_insert_with(String::new).push(c),
+        }
+    }
+    tokens.extend(token);
+
+    tokens
+}
+
+/// Quote an argument so `tokenize` splits it back out as it is, leaving it alone when it has
+/// nothing a shell would treat specially.
+pub(crate) fn quote(arg: &str) -> String {
+    let is_plain = |c: char| c.is_alphanumeric() || "-_./:=,@+%".contains(c);
+    if !arg.is_empty() && arg.chars().all(is_plain) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_split_on_whitespace() {
+        assert_eq!(
+            vec!["--port", "8080", "--use-tls"],
+            tokenize("  --port\t8080 \n --use-tls ")
+        );
+    }
+
+    #[test]
+    fn should_keep_quoted_whitespace_and_empty_quotes() {
+        assert_eq!(
+            vec!["--name", "Ollie Smith", "--nickname", ""],
+            tokenize(r#"--name "Ollie Smith" --nickname ''"#)
+        );
+    }
+
+    #[test]
+    fn should_join_adjacent_quoted_and_unquoted_parts() {
+        assert_eq!(
+            vec!["--name=Ollie Smith"],
+            tokenize(r#"--name="Ollie Smith""#)
+        );
+    }
+
+    #[test]
+    fn should_honour_escapes() {
+        assert_eq!(
+            vec![r"a b", r#"say "hi""#, r"c:\dir", r"\n"],
+            tokenize(r#"a\ b "say \"hi\"" 'c:\dir' "\n""#)
+        );
+    }
+
+    #[test]
+    fn should_quote_args_which_tokenize_back_to_themselves() {
+        assert_eq!("--port=8080", quote("--port=8080"));
+        assert_eq!("'level>=warn'", quote("level>=warn"));
+        assert_eq!("''", quote(""));
+
+        let arg = "it's \"nice\"";
+        assert_eq!(vec![arg], tokenize(&quote(arg)));
+    }
+
+    #[test]
+    fn should_run_unterminated_quotes_until_the_end() {
+        assert_eq!(
+            vec!["--name", "Ollie Smith"],
+            tokenize("--name 'Ollie Smith")
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use std::env;
+use std::error::Error;
+
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+
+use crate::verbosity::{LogFormat, LogLevel};
+use crate::Program;
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> LevelFilter {
+        match level {
+            LogLevel::Off => LevelFilter::OFF,
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+impl Program<'_> {
+    /// A `tracing_subscriber` filter at the level given with the flags added by
+    /// `Program::with_verbosity`. Like `Program::init_logger`, `RUST_LOG` is still used for
+    /// per-target filters, and for the level when none of the verbosity flags were given.
+    ///
+    /// This is only available with the `tracing` feature.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_verbosity()
+    ///     .unwrap()
+    ///     .parse_from_str_arr(&["--log-level", "warn"])
+    ///     .unwrap();
+    ///
+    /// let subscriber = tracing_subscriber::fmt().with_env_filter(program.tracing_filter());
+    /// ```
+    pub fn tracing_filter(&self) -> EnvFilter {
+        let level = LevelFilter::from(self.log_level());
+        if env::var_os("RUST_LOG").is_none() {
+            return EnvFilter::default().add_directive(level.into());
+        }
+
+        let filter = EnvFilter::from_default_env();
+        match self.is_verbosity_given() {
+            true => filter.add_directive(level.into()),
+            false => filter,
+        }
+    }
+
+    /// Set up a `tracing_subscriber` formatter as the global subscriber, filtered by
+    /// `Program::tracing_filter` and writing in the format given with the flag added by
+    /// `Program::with_log_format`.
+    ///
+    /// This is only available with the `tracing` feature, and fails if a global subscriber has
+    /// already been set.
+    ///
+    /// ```no_run
+    /// use commandrs::Program;
+    ///
+    /// Program::new()
+    ///     .with_verbosity()
+    ///     .unwrap()
+    ///     .with_log_format()
+    ///     .unwrap()
+    ///     .parse_or_exit()
+    ///     .init_tracing()
+    ///     .unwrap();
+    /// ```
+    pub fn init_tracing(&self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let builder = tracing_subscriber::fmt().with_env_filter(self.tracing_filter());
+        match self.log_format() {
+            LogFormat::Text => builder.try_init(),
+            LogFormat::Json => builder.json().try_init(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_every_log_level_into_a_level_filter() {
+        assert_eq!(LevelFilter::OFF, LevelFilter::from(LogLevel::Off));
+        assert_eq!(LevelFilter::WARN, LevelFilter::from(LogLevel::Warn));
+        assert_eq!(LevelFilter::TRACE, LevelFilter::from(LogLevel::Trace));
+    }
+
+    #[test]
+    fn should_filter_at_the_given_log_level() {
+        let program = Program::new()
+            .with_verbosity()
+            .unwrap()
+            .parse_from_str_arr(&["-q"])
+            .unwrap();
+
+        assert_eq!(
+            Some(LevelFilter::WARN),
+            program.tracing_filter().max_level_hint()
+        );
+    }
+
+    #[test]
+    fn should_only_set_the_subscriber_once() {
+        let program = Program::new()
+            .with_verbosity()
+            .unwrap()
+            .with_log_format()
+            .unwrap()
+            .parse_from_str_arr(&["--log-format", "json"])
+            .unwrap();
+
+        assert!(program.init_tracing().is_ok());
+        assert!(program.init_tracing().is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use ::url::Url;
+
+use crate::error::ProgramError;
+use crate::Program;
+
+impl<'a> Program<'a> {
+    /// Only accept URLs with one of the given schemes for a `url::Url` flag, such as only `https`,
+    /// so a plain `http` URL is rejected when the `Program` is parsed rather than deep in the
+    /// application. Schemes are not case sensitive.
+    ///
+    /// This is only available with the `url` feature.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    /// use url::Url;
+    ///
+    /// let program = Program::new()
+    ///     .with_required_flag::<Url>("endpoint", "Where to send reports")
+    ///     .unwrap()
+    ///     .with_url_schemes("endpoint", ["https"])
+    ///     .unwrap();
+    ///
+    /// assert!(program.parse_from(["--endpoint", "https://example.com"]).is_ok());
+    /// assert!(program.parse_from(["--endpoint", "http://example.com"]).is_err());
+    /// ```
+    pub fn with_url_schemes<S>(
+        self,
+        name: &str,
+        schemes: impl IntoIterator<Item = S>,
+    ) -> Result<Program<'a>, ProgramError>
+    where
+        S: Into<String>,
+    {
+        let schemes: Vec<String> = schemes
+            .into_iter()
+            .map(|s| s.into().to_lowercase())
+            .collect();
+
+        self.with_validator::<Url, _>(name, move |url| {
+            if schemes.iter().any(|s| s == url.scheme()) {
+                return Ok(());
+            }
+            Err(format!("the scheme must be one of {}", schemes.join(", ")))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program() -> Program<'static> {
+        Program::new()
+            .with_required_flag::<Url>("endpoint", "Where to send reports")
+            .unwrap()
+            .with_url_schemes("endpoint", ["HTTPS", "wss"])
+            .unwrap()
+    }
+
+    #[test]
+    fn should_accept_urls_with_an_allowed_scheme() {
+        let program = program()
+            .parse_from_str_arr(&["--endpoint", "wss://example.com/feed"])
+            .unwrap();
+
+        assert_eq!(
+            Url::parse("wss://example.com/feed").unwrap(),
+            program.get::<Url>("endpoint").unwrap()
+        );
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_other_schemes() {
+        let err = program()
+            .parse_from(["--endpoint", "http://example.com"])
+            .unwrap_err();
+
+        assert_eq!(
+            "Value http://example.com is not valid for --endpoint, the scheme must be one of \
+            https, wss",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_values_which_are_not_urls() {
+        let err = program()
+            .parse_from(["--endpoint", "example.com"])
+            .unwrap_err();
+
+        assert_eq!(
+            "Could not parse endpoint as type of url::Url, relative URL without a base",
+            err.to_string()
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use crate::error::ProgramError;
+use crate::flag::Flag;
+use crate::Program;
+
+impl<'a> Program<'a> {
+    /// Check the `Program` and its flags for mistakes in how they were defined, which would
+    /// otherwise only show up when a user runs into them. The flags of every subcommand are
+    /// checked too. This is best called right after building the `Program`, and catches:
+    ///
+    /// - A prefix which is empty, as every argument would then be taken as a flag.
+    /// - Names which are empty, contain whitespace or begin with a `-`.
+    /// - Required flags which also have a default.
+    /// - Defaults which do not parse back as the type of their flag.
+    /// - Defaults which are not one of the flag's possible values.
+    ///
+    /// ```
+    /// use commandrs::Program;
+    ///
+    /// let err = Program::new()
+    ///     .with_optional_flag::<bool>("--verbose", false, "Log everything")
+    ///     .unwrap()
+    ///     .validate()
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     "Flag --verbose is not defined correctly, names cannot begin with a -",
+    ///     err.to_string()
+    /// );
+    /// ```
+    pub fn validate(self) -> Result<Program<'a>, ProgramError> {
+        if self.settings.prefix.is_empty() {
+            return Err(ProgramError::InvalidProgramDefinition {
+                reason: "the prefix cannot be empty".to_string(),
+            });
+        }
+        self.validate_flags()?;
+        Ok(self)
+    }
+
+    /// Checks the flags of this `Program`, then those of its subcommands. Subcommands are parsed
+    /// with the prefix of the `Program` they were added to, so only its prefix is checked.
+    fn validate_flags(&self) -> Result<(), ProgramError> {
+        for flag in &self.flags {
+            self.validate_flag(flag)
+                .map_err(|reason| ProgramError::InvalidFlagDefinition {
+                    name: flag.name.to_string(),
+                    reason: reason.to_string(),
+                })?;
+        }
+        self.subcommands
+            .iter()
+            .try_for_each(|subcommand| subcommand.validate_flags())
+    }
+
+    fn validate_flag(&self, flag: &Flag<'a>) -> Result<(), &'static str> {
+        if flag.name.is_empty() {
+            return Err("names cannot be empty");
+        }
+        if flag.name.contains(char::is_whitespace) {
+            return Err("names cannot contain whitespace");
+        }
+        if flag.name.starts_with('-') {
+            return Err("names cannot begin with a -");
+        }
+
+        match self.flag_defaults.get(&flag.name) {
+            Some(_) if flag.is_required => Err("required flags cannot have a default"),
+            Some(default) if !flag.is_raw && (flag.parser.0)(&default.str_value).is_err() => {
+                Err("the default does not parse as the type of the flag")
+            }
+            Some(default)
+                if !flag.possible_values.is_empty()
+                    && !flag
+                        .possible_values
+                        .iter()
+                        .any(|v| v.as_ref() == default.str_value) =>
+            {
+                Err("the default is not one of the possible values")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::fmt::{Display, Formatter};
+    use std::str::FromStr;
+
+    use crate::flag::FlagValue;
+
+    use super::*;
+
+    fn invalid(name: &str, reason: &str) -> ProgramError {
+        ProgramError::InvalidFlagDefinition {
+            name: name.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    #[test]
+    fn should_accept_correctly_defined_flags() {
+        let program = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_optional_flag::<String>("host", "localhost".to_string(), "Host to bind")
+            .unwrap()
+            .with_raw_flag("cmd", "Command to run on startup")
+            .unwrap();
+
+        assert_eq!(Ok(program.clone()), program.validate());
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_badly_defined_flags_of_subcommands() {
+        let build = Program::new()
+            .with_optional_flag::<bool>("--bad", false, "Build badly")
+            .unwrap();
+        let err = Program::new()
+            .with_subcommand("build", build)
+            .unwrap()
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(invalid("--bad", "names cannot begin with a -"), err);
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_an_empty_prefix() {
+        let err = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap()
+            .with_prefix("")
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(
+            ProgramError::InvalidProgramDefinition {
+                reason: "the prefix cannot be empty".to_string(),
+            },
+            err
+        );
+        assert_eq!(
+            "Program is not defined correctly, the prefix cannot be empty",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_bad_names() {
+        let validate = |name: &str| {
+            Program::new()
+                .with_required_flag::<u16>(name.to_string(), "")
+                .unwrap()
+                .validate()
+                .unwrap_err()
+        };
+
+        assert_eq!(invalid("", "names cannot be empty"), validate(""));
+        assert_eq!(
+            invalid("my port", "names cannot contain whitespace"),
+            validate("my port")
+        );
+        assert_eq!(
+            invalid("-port", "names cannot begin with a -"),
+            validate("-port")
+        );
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_required_flags_with_defaults() {
+        let optional = Program::new()
+            .with_optional_flag::<u16>("port", 8080, "Port number")
+            .unwrap();
+        let mut program = Program::new()
+            .with_required_flag::<u16>("port", "Port number")
+            .unwrap();
+        program.flag_defaults = optional.flag_defaults;
+
+        assert_eq!(
+            invalid("port", "required flags cannot have a default"),
+            program.validate().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_defaults_which_are_not_possible() {
+        let err = Program::new()
+            .with_optional_flag::<String>("speed", "ludicrous".to_string(), "How fast to go")
+            .unwrap()
+            .with_possible_values("speed", ["fast", "slow"])
+            .unwrap()
+            .validate()
+            .unwrap_err();
+
+        assert_eq!(
+            invalid("speed", "the default is not one of the possible values"),
+            err
+        );
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_defaults_which_do_not_parse() {
+        /// Displays differently from how it is parsed, so the default can't be parsed back.
+        #[derive(Clone)]
+        struct Level(u8);
+
+        impl FromStr for Level {
+            type Err = std::num::ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map(Level)
+            }
+        }
+
+        impl Display for Level {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                write!(f, "level {}", self.0)
+            }
+        }
+
+        // Optional flags already refuse these when added, so this can only be put together by hand.
+        let mut program = Program::new()
+            .with_required_flag::<Level>("level", "Log level")
+            .unwrap();
+        program.flag_mut("level").unwrap().is_required = false;
+        program.flag_defaults.insert(
+            "level".into(),
+            FlagValue::new(Cow::Borrowed("level"), Level(1).to_string(), Level(1)),
+        );
+        let err = program.validate().unwrap_err();
+
+        assert_eq!(
+            invalid(
+                "level",
+                "the default does not parse as the type of the flag"
+            ),
+            err
+        );
+    }
+}
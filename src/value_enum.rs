@@ -0,0 +1,149 @@
+//! Plain Rust enums as flag values, where each variant is one of the values the flag accepts, such
+//! as `fast`, `slow` or `auto`.
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::error::ProgramError;
+use crate::Program;
+
+/// An enum whose variants are the values a flag accepts, which are shown in the help text and
+/// checked when parsing with `Program::with_optional_choice_flag` and
+/// `Program::with_required_choice_flag`.
+///
+/// This is usually derived with `#[derive(ValueEnum)]` when the `derive` feature is enabled,
+/// which names each value after its variant in kebab-case, such as `dry-run` for `DryRun`.
+///
+/// ```
+/// use std::fmt::{Display, Formatter};
+/// use std::str::FromStr;
+///
+/// use commandrs::value_enum::{ParseValueEnumError, ValueEnum};
+/// use commandrs::Program;
+///
+/// #[derive(Clone, Copy, PartialEq, Debug)]
+/// enum Speed {
+///     Fast,
+///     Slow,
+/// }
+///
+/// impl ValueEnum for Speed {
+///     fn values() -> &'static [&'static str] {
+///         &["fast", "slow"]
+///     }
+/// }
+///
+/// impl FromStr for Speed {
+///     type Err = ParseValueEnumError;
+///
+///     fn from_str(s: &str) -> Result<Speed, ParseValueEnumError> {
+///         match s {
+///             "fast" => Ok(Speed::Fast),
+///             "slow" => Ok(Speed::Slow),
+///             _ => Err(ParseValueEnumError::new(Speed::values())),
+///         }
+///     }
+/// }
+///
+/// impl Display for Speed {
+///     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+///         match self {
+///             Speed::Fast => write!(f, "fast"),
+///             Speed::Slow => write!(f, "slow"),
+///         }
+///     }
+/// }
+///
+/// let program = Program::new()
+///     .with_optional_choice_flag("speed", Speed::Slow, "How fast to go")
+///     .unwrap()
+///     .parse_from_str_arr(&["--speed", "fast"])
+///     .unwrap();
+///
+/// assert_eq!(Speed::Fast, program.get::<Speed>("speed").unwrap());
+/// ```
+pub trait ValueEnum:
+    FromStr<Err = ParseValueEnumError> + Display + Clone + Send + Sync + 'static
+{
+    /// Every value the flag accepts, in the order they are shown in the help text.
+    fn values() -> &'static [&'static str];
+}
+
+/// A value which is not one of the values of a `ValueEnum`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseValueEnumError {
+    possible_values: &'static [&'static str],
+}
+
+impl ParseValueEnumError {
+    pub fn new(possible_values: &'static [&'static str]) -> ParseValueEnumError {
+        ParseValueEnumError { possible_values }
+    }
+
+    /// Every value which would have been accepted.
+    pub fn possible_values(&self) -> &'static [&'static str] {
+        self.possible_values
+    }
+}
+
+impl Display for ParseValueEnumError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.possible_values.split_last() {
+            Some((last, [])) => write!(f, "expected {}", last),
+            Some((last, rest)) => write!(f, "expected one of {} or {}", rest.join(", "), last),
+            None => write!(f, "expected no value at all"),
+        }
+    }
+}
+
+impl Error for ParseValueEnumError {}
+
+impl<'a> Program<'a> {
+    /// Add an optional flag whose values are the variants of a `ValueEnum`, which are shown in
+    /// the help text, and in the error when any other value is given.
+    ///
+    /// The name must be unique.
+    pub fn with_optional_choice_flag<T: ValueEnum>(
+        self,
+        name: impl Into<Cow<'a, str>>,
+        default: T,
+        desc: impl Into<Cow<'a, str>>,
+    ) -> Result<Program<'a>, ProgramError> {
+        let name = name.into();
+        self.with_optional_flag(name.clone(), default, desc)?
+            .with_possible_values(&name, T::values().iter().copied())
+    }
+
+    /// Add a required flag whose values are the variants of a `ValueEnum`, like
+    /// `Program::with_optional_choice_flag`.
+    ///
+    /// The name must be unique.
+    pub fn with_required_choice_flag<T: ValueEnum>(
+        self,
+        name: impl Into<Cow<'a, str>>,
+        desc: impl Into<Cow<'a, str>>,
+    ) -> Result<Program<'a>, ProgramError> {
+        let name = name.into();
+        self.with_required_flag::<T>(name.clone(), desc)?
+            .with_possible_values(&name, T::values().iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_list_the_possible_values_in_parse_errors() {
+        assert_eq!(
+            "expected one of fast, slow or auto",
+            ParseValueEnumError::new(&["fast", "slow", "auto"]).to_string()
+        );
+        assert_eq!(
+            "expected fast",
+            ParseValueEnumError::new(&["fast"]).to_string()
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! A standard set of verbosity flags, so every tool gets the same `-v`, `-vv` and `-q` behaviour
+//! from one call to `Program::with_verbosity`.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::error::ProgramError;
+use crate::Program;
+
+pub(crate) const VERBOSE_FLAG: &str = "verbose";
+pub(crate) const QUIET_FLAG: &str = "quiet";
+pub(crate) const LOG_LEVEL_FLAG: &str = "log-level";
+pub(crate) const LOG_FORMAT_FLAG: &str = "log-format";
+
+/// How much a program should log, from nothing at all to everything.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+const LOG_LEVELS: [LogLevel; 6] = [
+    LogLevel::Off,
+    LogLevel::Error,
+    LogLevel::Warn,
+    LogLevel::Info,
+    LogLevel::Debug,
+    LogLevel::Trace,
+];
+
+impl LogLevel {
+    fn name(self) -> &'static str {
+        match self {
+            LogLevel::Off => "off",
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+
+    /// Moves up or down by a number of levels, stopping at `Off` and `Trace`.
+    fn shifted(self, by: isize) -> LogLevel {
+        let i = (self as isize + by).clamp(0, LOG_LEVELS.len() as isize - 1);
+        LOG_LEVELS[i as usize]
+    }
+}
+
+impl Display for LogLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = ParseLogLevelError;
+
+    fn from_str(s: &str) -> Result<LogLevel, ParseLogLevelError> {
+        let s = s.to_ascii_lowercase();
+        let name = if s == "warning" { "warn" } else { s.as_str() };
+        LOG_LEVELS
+            .into_iter()
+            .find(|level| level.name() == name)
+            .ok_or(ParseLogLevelError)
+    }
+}
+
+/// A log level which is not one of `off`, `error`, `warn`, `info`, `debug` or `trace`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseLogLevelError;
+
+impl Display for ParseLogLevelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected one of off, error, warn, info, debug or trace")
+    }
+}
+
+impl Error for ParseLogLevelError {}
+
+/// How a program should write its logs, either for people reading a terminal or for machines.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl Display for LogFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogFormat::Text => write!(f, "text"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = ParseLogFormatError;
+
+    fn from_str(s: &str) -> Result<LogFormat, ParseLogFormatError> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(ParseLogFormatError),
+        }
+    }
+}
+
+/// A log format which is not one of `text` or `json`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseLogFormatError;
+
+impl Display for ParseLogFormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected one of text or json")
+    }
+}
+
+impl Error for ParseLogFormatError {}
+
+impl<'a> Program<'a> {
+    /// Add `--verbose`, `--quiet` and `--log-level` flags, which `Program::log_level` resolves to
+    /// a `LogLevel`. `-v` and `-q` can be given as short forms, and repeated such as `-vv` for
+    /// even more or less logging.
+    ///
+    /// The level is `info` unless `--log-level` is given, and each `-v` or `-q` moves it up or
+    /// down one level from there.
+    ///
+    /// ```
+    /// use commandrs::verbosity::LogLevel;
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_verbosity()
+    ///     .unwrap()
+    ///     .parse_from_str_arr(&["-vv"])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(LogLevel::Trace, program.log_level());
+    /// ```
+    pub fn with_verbosity(mut self) -> Result<Program<'a>, ProgramError> {
+        self = self
+            .with_optional_flag(
+                VERBOSE_FLAG,
+                false,
+                "Log more, which can be given more than once, such as -vv",
+            )?
+            .with_optional_flag(
+                QUIET_FLAG,
+                false,
+                "Log less, which can be given more than once, such as -qq",
+            )?
+            .with_optional_flag(
+                LOG_LEVEL_FLAG,
+                LogLevel::Info,
+                "Log level, one of off, error, warn, info, debug or trace",
+            )?;
+        self.verbosity = true;
+        Ok(self)
+    }
+
+    /// The log level given with the flags added by `Program::with_verbosity`, or `info` when
+    /// they were not added.
+    pub fn log_level(&self) -> LogLevel {
+        let level = self.get_or(LOG_LEVEL_FLAG, LogLevel::Info);
+        let by =
+            self.occurrences_of(VERBOSE_FLAG) as isize - self.occurrences_of(QUIET_FLAG) as isize;
+        level.shifted(by)
+    }
+
+    /// Add a `--log-format` flag, which `Program::log_format` resolves to a `LogFormat`. This is
+    /// `text` unless `json` is given, for services whose logs are collected by machines.
+    ///
+    /// ```
+    /// use commandrs::verbosity::LogFormat;
+    /// use commandrs::Program;
+    ///
+    /// let program = Program::new()
+    ///     .with_log_format()
+    ///     .unwrap()
+    ///     .parse_from_str_arr(&["--log-format", "json"])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(LogFormat::Json, program.log_format());
+    /// ```
+    pub fn with_log_format(self) -> Result<Program<'a>, ProgramError> {
+        self.with_optional_flag(
+            LOG_FORMAT_FLAG,
+            LogFormat::Text,
+            "Log format, one of text or json",
+        )?
+        .with_possible_values(LOG_FORMAT_FLAG, ["text", "json"])
+    }
+
+    /// The log format given with the flag added by `Program::with_log_format`, or `text` when it
+    /// was not added.
+    pub fn log_format(&self) -> LogFormat {
+        self.get_or(LOG_FORMAT_FLAG, LogFormat::Text)
+    }
+
+    /// Whether any of the flags added by `Program::with_verbosity` were given, rather than the
+    /// level being left as the default.
+    #[cfg(any(feature = "log", feature = "tracing"))]
+    pub(crate) fn is_verbosity_given(&self) -> bool {
+        self.is_present(LOG_LEVEL_FLAG)
+            || self.occurrences_of(VERBOSE_FLAG) > 0
+            || self.occurrences_of(QUIET_FLAG) > 0
+    }
+
+    /// The flag and how many times it was given for short verbosity flags such as `-vv` or `-q`.
+    pub(crate) fn short_verbosity_flag(&self, arg: &str) -> Option<(&'static str, usize)> {
+        if !self.verbosity {
+            return None;
+        }
+
+        let letters = arg.strip_prefix('-')?;
+        let name = match letters.chars().next()? {
+            'v' => VERBOSE_FLAG,
+            'q' => QUIET_FLAG,
+            _ => return None,
+        };
+        let first = letters.bytes().next()?;
+        match letters.bytes().all(|b| b == first) {
+            true => Some((name, letters.len())),
+            false => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_level(args: &[&str]) -> LogLevel {
+        Program::new()
+            .with_verbosity()
+            .unwrap()
+            .parse_from_str_arr(args)
+            .unwrap()
+            .log_level()
+    }
+
+    #[test]
+    fn should_log_info_by_default() {
+        assert_eq!(LogLevel::Info, log_level(&[]));
+        assert_eq!(LogLevel::Info, Program::new().log_level());
+    }
+
+    #[test]
+    fn should_move_the_level_for_each_verbose_or_quiet_flag() {
+        assert_eq!(LogLevel::Debug, log_level(&["-v"]));
+        assert_eq!(LogLevel::Trace, log_level(&["-vvv"]));
+        assert_eq!(LogLevel::Debug, log_level(&["--verbose"]));
+        assert_eq!(LogLevel::Trace, log_level(&["-v", "--verbose"]));
+        assert_eq!(LogLevel::Warn, log_level(&["-q"]));
+        assert_eq!(LogLevel::Off, log_level(&["-qqqq"]));
+        assert_eq!(LogLevel::Info, log_level(&["-v", "-q"]));
+    }
+
+    #[test]
+    fn should_move_the_level_from_the_given_log_level() {
+        assert_eq!(LogLevel::Error, log_level(&["--log-level", "ERROR"]));
+        assert_eq!(LogLevel::Warn, log_level(&["--log-level", "error", "-v"]));
+        assert_eq!(LogLevel::Warn, log_level(&["--log-level", "warning"]));
+    }
+
+    #[test]
+    fn should_result_in_an_error_for_unknown_log_levels() {
+        let err = Program::new()
+            .with_verbosity()
+            .unwrap()
+            .parse_from_str_arr(&["--log-level", "loud"])
+            .unwrap_err();
+
+        assert_eq!(
+            "Could not parse log-level as type of commandrs::verbosity::LogLevel, expected one \
+            of off, error, warn, info, debug or trace",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn should_write_text_logs_unless_json_is_given() {
+        let program = Program::new().with_log_format().unwrap();
+
+        assert_eq!(
+            LogFormat::Text,
+            program
+                .parse_from(Vec::<String>::new())
+                .unwrap()
+                .into_program()
+                .log_format()
+        );
+        assert_eq!(
+            LogFormat::Json,
+            program
+                .parse_from(["--log-format", "json"])
+                .unwrap()
+                .into_program()
+                .log_format()
+        );
+        assert!(program.parse_from(["--log-format", "xml"]).is_err());
+        assert_eq!(LogFormat::Text, Program::new().log_format());
+    }
+
+    #[test]
+    fn should_not_treat_other_short_flags_as_verbosity() {
+        let program = Program::new().with_verbosity().unwrap();
+
+        assert_eq!(None, program.short_verbosity_flag("-vq"));
+        assert_eq!(None, program.short_verbosity_flag("-x"));
+        assert_eq!(None, program.short_verbosity_flag("--v"));
+        assert_eq!(None, Program::new().short_verbosity_flag("-v"));
+    }
+}